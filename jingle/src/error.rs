@@ -23,6 +23,18 @@ pub enum JingleError {
     IndirectConstantRead,
     #[error("Attempted to perform a write of a bitvector to a VarNode with leftover space. Sleigh guarantees this will be done with an explicit extension operation.")]
     Mismatched,
+    #[error("Jingle can only model floating point values of 4, 8, 10, or 16 bytes")]
+    UnsupportedFloatSize,
+    #[error("z3 rejected a floating point term built by jingle")]
+    FloatEncoding,
+    #[error("z3 did not parse the expected floating point function out of: {0}")]
+    FloatDeclParse(String),
+    #[error("An uninterpreted function standing in for a p-code op returned something other than a bitvector")]
+    UninterpretedSort,
+    #[error("A CALLOTHER handler referenced an argument the userop was not given")]
+    MissingCallOtherArgument,
+    #[error("A CALLOTHER handler tried to write the output of a userop that has none")]
+    MissingCallOtherOutput,
     #[error("Jingle does not yet model this instruction")]
     UnmodeledInstruction(Box<PcodeOperation>),
 }
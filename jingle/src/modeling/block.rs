@@ -2,11 +2,11 @@ use crate::error::JingleError;
 use crate::error::JingleError::DisassemblyLengthBound;
 use crate::modeling::branch::BranchConstraint;
 use crate::modeling::state::State;
-use crate::modeling::{ModelingContext, TranslationContext};
+use crate::modeling::{CallOtherRegistry, ModelingContext, TranslationContext};
 use crate::varnode::ResolvedVarnode;
 use crate::JingleError::EmptyBlock;
-use jingle_sleigh::Instruction;
 use jingle_sleigh::PcodeOperation;
+use jingle_sleigh::{Instruction, JingleSleighError};
 use jingle_sleigh::{SpaceInfo, SpaceManager};
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
@@ -22,12 +22,16 @@ pub struct ModeledBlock<'ctx> {
     branch_constraint: BranchConstraint,
     inputs: HashSet<ResolvedVarnode<'ctx>>,
     outputs: HashSet<ResolvedVarnode<'ctx>>,
+    callother: CallOtherRegistry<'ctx>,
 }
 
 impl<'ctx> Display for ModeledBlock<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for x in self.instructions.iter() {
             writeln!(f, "{:x} {}", x.address, x.disassembly)?;
+            for slot in x.delay_slots.iter() {
+                writeln!(f, "  _{}", slot)?;
+            }
         }
         Ok(())
     }
@@ -49,6 +53,7 @@ impl<'ctx, T: ModelingContext<'ctx>> TryFrom<&'ctx [T]> for ModeledBlock<'ctx> {
             branch_constraint: BranchConstraint::with_same_final_branch(
                 vec.last().ok_or(EmptyBlock)?.get_branch_constraint(),
             ),
+            callother: vec[0].get_callother_registry().clone(),
         };
 
         for ctx in vec {
@@ -61,10 +66,29 @@ impl<'ctx, T: ModelingContext<'ctx>> TryFrom<&'ctx [T]> for ModeledBlock<'ctx> {
 }
 
 impl<'ctx> ModeledBlock<'ctx> {
-    pub fn read<T: Iterator<Item = Instruction>, S: SpaceManager>(
+    /// Read a block out of a stream of decoded instructions (e.g. from
+    /// [SleighContext::try_read](jingle_sleigh::context::SleighContext::try_read)), up to and
+    /// including the first instruction that terminates it.
+    ///
+    /// If an instruction fails to decode before the block terminates, the decoding error is
+    /// returned; [DisassemblyLengthBound] is only returned if the stream runs out cleanly.
+    pub fn read<T: Iterator<Item = Result<Instruction, JingleSleighError>>, S: SpaceManager>(
         z3: &'ctx Context,
         space_manager: &S,
         instr_iter: T,
+    ) -> Result<Self, JingleError> {
+        Self::read_with_callother(z3, space_manager, instr_iter, CallOtherRegistry::default())
+    }
+
+    /// Read a block, using the given handlers for any `CALLOTHER` operations
+    pub fn read_with_callother<
+        T: Iterator<Item = Result<Instruction, JingleSleighError>>,
+        S: SpaceManager,
+    >(
+        z3: &'ctx Context,
+        space_manager: &S,
+        instr_iter: T,
+        callother: CallOtherRegistry<'ctx>,
     ) -> Result<Self, JingleError> {
         let original_state = State::new(z3, space_manager);
         let state = original_state.clone();
@@ -76,6 +100,7 @@ impl<'ctx> ModeledBlock<'ctx> {
         // in cases where this has been initialized with an actual value.
         let mut naive_fallthrough_address: u64 = 0;
         for instr in instr_iter {
+            let instr = instr?;
             ops.extend_from_slice(&instr.ops);
             if instr.terminates_basic_block() {
                 block_terminated = true;
@@ -102,6 +127,7 @@ impl<'ctx> ModeledBlock<'ctx> {
             branch_constraint: BranchConstraint::new(&vn),
             inputs: Default::default(),
             outputs: Default::default(),
+            callother,
         };
         for op in ops {
             model.model_pcode_op(&op)?
@@ -110,7 +136,12 @@ impl<'ctx> ModeledBlock<'ctx> {
     }
 
     pub fn fresh(&self) -> Result<Self, JingleError> {
-        ModeledBlock::read(self.z3, self, self.instructions.clone().into_iter())
+        ModeledBlock::read_with_callother(
+            self.z3,
+            self,
+            self.instructions.clone().into_iter().map(Ok),
+            self.callother.clone(),
+        )
     }
 
     pub fn get_address(&self) -> u64 {
@@ -169,6 +200,10 @@ impl<'ctx> ModelingContext<'ctx> for ModeledBlock<'ctx> {
     fn get_branch_constraint(&self) -> &BranchConstraint {
         &self.branch_constraint
     }
+
+    fn get_callother_registry(&self) -> &CallOtherRegistry<'ctx> {
+        &self.callother
+    }
 }
 
 impl<'ctx> TranslationContext<'ctx> for ModeledBlock<'ctx> {
@@ -0,0 +1,183 @@
+use crate::error::JingleError;
+use crate::modeling::State;
+use jingle_sleigh::{GeneralizedVarNode, VarNode};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use z3::ast::BV;
+use z3::Context;
+
+/// Custom semantics for a SLEIGH userop (a `CALLOTHER` operation).
+///
+/// Handlers describe their effects through the given [CallOtherInvocation], which the
+/// modeling context then applies to its state, tracking inputs and outputs as it would for any
+/// other operation. Any closure taking a `&mut CallOtherInvocation` implements this trait.
+pub trait CallOtherHandler<'ctx> {
+    fn model(&self, call: &mut CallOtherInvocation<'_, 'ctx>) -> Result<(), JingleError>;
+}
+
+impl<'ctx, F> CallOtherHandler<'ctx> for F
+where
+    F: Fn(&mut CallOtherInvocation<'_, 'ctx>) -> Result<(), JingleError>,
+{
+    fn model(&self, call: &mut CallOtherInvocation<'_, 'ctx>) -> Result<(), JingleError> {
+        self(call)
+    }
+}
+
+/// A single `CALLOTHER` being modeled by a [CallOtherHandler].
+///
+/// Reads are performed against the state as it was before the userop; writes are buffered
+/// and only applied once the handler returns.
+pub struct CallOtherInvocation<'a, 'ctx> {
+    z3: &'ctx Context,
+    state: &'a State<'ctx>,
+    /// The index of the userop being invoked
+    pub index: u64,
+    /// The name of the userop being invoked, if known
+    pub name: Option<&'a str>,
+    /// The arguments of the userop, not including the userop index
+    pub args: &'a [VarNode],
+    /// The output of the userop, if it has one
+    pub output: Option<&'a VarNode>,
+    reads: Vec<GeneralizedVarNode>,
+    writes: Vec<(GeneralizedVarNode, BV<'ctx>)>,
+    branch: Option<GeneralizedVarNode>,
+}
+
+impl<'a, 'ctx> CallOtherInvocation<'a, 'ctx> {
+    pub(crate) fn new(
+        z3: &'ctx Context,
+        state: &'a State<'ctx>,
+        index: u64,
+        name: Option<&'a str>,
+        args: &'a [VarNode],
+        output: Option<&'a VarNode>,
+    ) -> Self {
+        Self {
+            z3,
+            state,
+            index,
+            name,
+            args,
+            output,
+            reads: vec![],
+            writes: vec![],
+            branch: None,
+        }
+    }
+
+    pub fn get_z3(&self) -> &'ctx Context {
+        self.z3
+    }
+
+    /// Read the value of an arbitrary varnode, e.g. a register holding a syscall number.
+    /// The varnode is tracked as an input of the operation.
+    pub fn read(&mut self, vn: &GeneralizedVarNode) -> Result<BV<'ctx>, JingleError> {
+        self.reads.push(vn.clone());
+        self.state.read(vn.clone())
+    }
+
+    /// Read the value of the `i`th userop argument
+    pub fn arg(&mut self, i: usize) -> Result<BV<'ctx>, JingleError> {
+        let vn = self
+            .args
+            .get(i)
+            .ok_or(JingleError::MissingCallOtherArgument)?;
+        self.read(&vn.into())
+    }
+
+    /// Write a value to an arbitrary varnode. The varnode is tracked as an output
+    /// of the operation.
+    pub fn write(&mut self, vn: &GeneralizedVarNode, val: BV<'ctx>) {
+        self.writes.push((vn.clone(), val))
+    }
+
+    /// Write a value to the output of the userop
+    pub fn write_output(&mut self, val: BV<'ctx>) -> Result<(), JingleError> {
+        let output = self.output.ok_or(JingleError::MissingCallOtherOutput)?;
+        self.write(&output.into(), val);
+        Ok(())
+    }
+
+    /// Treat the userop as a branch to the given destination, as is done for userops without a
+    /// registered handler
+    pub fn branch_to(&mut self, dest: &GeneralizedVarNode) {
+        self.branch = Some(dest.clone())
+    }
+
+    pub(crate) fn into_effects(self) -> CallOtherEffects<'ctx> {
+        CallOtherEffects {
+            reads: self.reads,
+            writes: self.writes,
+            branch: self.branch,
+        }
+    }
+}
+
+pub(crate) struct CallOtherEffects<'ctx> {
+    pub(crate) reads: Vec<GeneralizedVarNode>,
+    pub(crate) writes: Vec<(GeneralizedVarNode, BV<'ctx>)>,
+    pub(crate) branch: Option<GeneralizedVarNode>,
+}
+
+/// A table of [CallOtherHandler]s, keyed either by userop index or by userop name. A
+/// handler registered by index takes precedence over one registered by name.
+///
+/// `CALLOTHER`s without a handler fall back to `jingle`'s default modeling, which treats the
+/// userop as an opaque branch whose output is a hash of its inputs.
+#[derive(Clone, Default)]
+pub struct CallOtherRegistry<'ctx> {
+    by_index: HashMap<u64, Rc<dyn CallOtherHandler<'ctx> + 'ctx>>,
+    by_name: HashMap<String, Rc<dyn CallOtherHandler<'ctx> + 'ctx>>,
+    userop_names: HashMap<u64, String>,
+}
+
+impl<'ctx> Debug for CallOtherRegistry<'ctx> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallOtherRegistry")
+            .field("by_index", &self.by_index.keys().collect::<Vec<_>>())
+            .field("by_name", &self.by_name.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'ctx> CallOtherRegistry<'ctx> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Attach a handler to the userop with the given index
+    pub fn register_index<H: CallOtherHandler<'ctx> + 'ctx>(&mut self, index: u64, handler: H) {
+        self.by_index.insert(index, Rc::new(handler));
+    }
+
+    /// Attach a handler to the userop with the given name
+    pub fn register_name<H: CallOtherHandler<'ctx> + 'ctx>(&mut self, name: &str, handler: H) {
+        self.by_name.insert(name.to_string(), Rc::new(handler));
+    }
+
+    /// Record the name of a userop, allowing handlers registered by name to be found
+    pub fn set_userop_name(&mut self, index: u64, name: &str) {
+        self.userop_names.insert(index, name.to_string());
+    }
+
+    pub fn get_userop_name(&self, index: u64) -> Option<&str> {
+        self.userop_names.get(&index).map(|s| s.as_str())
+    }
+
+    pub fn get(&self, index: u64) -> Option<Rc<dyn CallOtherHandler<'ctx> + 'ctx>> {
+        self.by_index
+            .get(&index)
+            .or_else(|| {
+                self.userop_names
+                    .get(&index)
+                    .and_then(|name| self.by_name.get(name))
+            })
+            .cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_index.is_empty() && self.by_name.is_empty()
+    }
+}
@@ -0,0 +1,458 @@
+use crate::error::JingleError;
+use crate::error::JingleError::{FloatDeclParse, FloatEncoding, UnsupportedFloatSize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use z3::ast::{Ast, Bool, Dynamic, Float, BV};
+use z3::{Context, DeclKind, FuncDecl, Optimize};
+
+/// The IEEE-754 interchange format SLEIGH uses for a floating point varnode of a given size.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct FloatFormat {
+    ebits: u32,
+    sbits: u32,
+    /// x87 extended precision stores the leading significand bit explicitly, which z3's
+    /// floating point sorts do not
+    explicit_integer_bit: bool,
+}
+
+impl FloatFormat {
+    pub(crate) fn from_size(size: usize) -> Result<Self, JingleError> {
+        let (ebits, sbits, explicit_integer_bit) = match size {
+            4 => (8, 24, false),
+            8 => (11, 53, false),
+            10 => (15, 64, true),
+            16 => (15, 113, false),
+            _ => return Err(UnsupportedFloatSize),
+        };
+        Ok(Self {
+            ebits,
+            sbits,
+            explicit_integer_bit,
+        })
+    }
+
+    fn to_fp_func(self) -> String {
+        format!("(_ to_fp {} {})", self.ebits, self.sbits)
+    }
+
+    /// Reinterpret the raw bits of a varnode as a float of this format
+    pub(crate) fn decode<'ctx>(
+        &self,
+        decls: &FloatDecls<'ctx>,
+        bv: &BV<'ctx>,
+    ) -> Result<Float<'ctx>, JingleError> {
+        let ieee = if self.explicit_integer_bit {
+            let integer_bit = self.sbits - 1;
+            let sign_exp = bv.extract(bv.get_size() - 1, integer_bit + 1);
+            let fraction = bv.extract(integer_bit - 1, 0);
+            sign_exp.concat(&fraction)
+        } else {
+            bv.clone()
+        };
+        decls
+            .apply(
+                bv.get_ctx(),
+                &self.to_fp_func(),
+                DeclKind::FPA_TO_FP,
+                &[&ieee],
+            )?
+            .as_float()
+            .ok_or(FloatEncoding)
+    }
+
+    /// Produce the raw bits of a float of this format, suitable for writing into a varnode
+    pub(crate) fn encode<'ctx>(
+        &self,
+        decls: &FloatDecls<'ctx>,
+        float: &Float<'ctx>,
+    ) -> Result<BV<'ctx>, JingleError> {
+        let z3 = float.get_ctx();
+        let ieee = decls
+            .apply(z3, "fp.to_ieee_bv", DeclKind::FPA_TO_IEEE_BV, &[float])?
+            .as_bv()
+            .ok_or(FloatEncoding)?;
+        if self.explicit_integer_bit {
+            let fraction_bits = self.sbits - 1;
+            let sign_exp = ieee.extract(ieee.get_size() - 1, fraction_bits);
+            let fraction = ieee.extract(fraction_bits - 1, 0);
+            // the integer bit is set for everything but zeroes and denormals
+            let exp = sign_exp.extract(self.ebits - 1, 0);
+            let integer_bit = exp
+                ._eq(&BV::from_u64(z3, 0, self.ebits))
+                .ite(&BV::from_u64(z3, 0, 1), &BV::from_u64(z3, 1, 1));
+            Ok(sign_exp.concat(&integer_bit).concat(&fraction))
+        } else {
+            Ok(ieee)
+        }
+    }
+
+    /// Convert a signed integer into a float of this format
+    pub(crate) fn int_to_float<'ctx>(
+        &self,
+        decls: &FloatDecls<'ctx>,
+        bv: &BV<'ctx>,
+    ) -> Result<Float<'ctx>, JingleError> {
+        let z3 = bv.get_ctx();
+        let rm = RoundingMode::NearestEven.model(decls, z3)?;
+        decls
+            .apply(z3, &self.to_fp_func(), DeclKind::FPA_TO_FP, &[&rm, bv])?
+            .as_float()
+            .ok_or(FloatEncoding)
+    }
+
+    /// Convert a float of another format into this format
+    pub(crate) fn float_to_float<'ctx>(
+        &self,
+        decls: &FloatDecls<'ctx>,
+        float: &Float<'ctx>,
+    ) -> Result<Float<'ctx>, JingleError> {
+        let z3 = float.get_ctx();
+        let rm = RoundingMode::NearestEven.model(decls, z3)?;
+        decls
+            .apply(z3, &self.to_fp_func(), DeclKind::FPA_TO_FP, &[&rm, float])?
+            .as_float()
+            .ok_or(FloatEncoding)
+    }
+}
+
+/// IEEE-754 rounding modes. SLEIGH does not model rounding modes, so [NearestEven] is used
+/// wherever rounding is not implied by the operation itself.
+///
+/// [NearestEven]: RoundingMode::NearestEven
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum RoundingMode {
+    NearestEven,
+    TowardPositive,
+    TowardNegative,
+    TowardZero,
+}
+
+impl RoundingMode {
+    /// The rounding mode as a term. The `z3` crate types rounding mode terms as [Float]s, which
+    /// is what its rounded operations, e.g. [Float::add], expect.
+    fn model<'ctx>(
+        self,
+        decls: &FloatDecls<'ctx>,
+        z3: &'ctx Context,
+    ) -> Result<Float<'ctx>, JingleError> {
+        // the z3 crate only has constructors for the directed rounding modes
+        match self {
+            RoundingMode::NearestEven => {
+                let rne = decls.apply(z3, "RNE", DeclKind::FPA_RM_NEAREST_TIES_TO_EVEN, &[])?;
+                // SAFETY: the ast is a live rounding mode term of this context, wrapped the same
+                // way Float::round_towards_zero wraps the other rounding modes
+                Ok(unsafe { Float::wrap(z3, rne.get_z3_ast()) })
+            }
+            RoundingMode::TowardPositive => Ok(Float::round_towards_positive(z3)),
+            RoundingMode::TowardNegative => Ok(Float::round_towards_negative(z3)),
+            RoundingMode::TowardZero => Ok(Float::round_towards_zero(z3)),
+        }
+    }
+}
+
+/// Apply a rounded binary arithmetic operation of the `z3` crate (e.g. [Float::add], which takes
+/// the rounding mode as its receiver) to two floats of the same format
+pub(crate) fn fp_arith<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    op: fn(&Float<'ctx>, &Float<'ctx>, &Float<'ctx>) -> Float<'ctx>,
+    a: &Float<'ctx>,
+    b: &Float<'ctx>,
+) -> Result<Float<'ctx>, JingleError> {
+    let rm = RoundingMode::NearestEven.model(decls, a.get_ctx())?;
+    Ok(op(&rm, a, b))
+}
+
+pub(crate) fn fp_eq<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    a: &Float<'ctx>,
+    b: &Float<'ctx>,
+) -> Result<Bool<'ctx>, JingleError> {
+    decls
+        .apply(a.get_ctx(), "fp.eq", DeclKind::FPA_EQ, &[a, b])?
+        .as_bool()
+        .ok_or(FloatEncoding)
+}
+
+pub(crate) fn fp_is_nan<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    a: &Float<'ctx>,
+) -> Result<Bool<'ctx>, JingleError> {
+    decls
+        .apply(a.get_ctx(), "fp.isNaN", DeclKind::FPA_IS_NAN, &[a])?
+        .as_bool()
+        .ok_or(FloatEncoding)
+}
+
+pub(crate) fn fp_sqrt<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    a: &Float<'ctx>,
+) -> Result<Float<'ctx>, JingleError> {
+    let z3 = a.get_ctx();
+    let rm = RoundingMode::NearestEven.model(decls, z3)?;
+    decls
+        .apply(z3, "fp.sqrt", DeclKind::FPA_SQRT, &[&rm, a])?
+        .as_float()
+        .ok_or(FloatEncoding)
+}
+
+pub(crate) fn fp_round_to_integral<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    rounding_mode: RoundingMode,
+    a: &Float<'ctx>,
+) -> Result<Float<'ctx>, JingleError> {
+    let z3 = a.get_ctx();
+    let rm = rounding_mode.model(decls, z3)?;
+    decls
+        .apply(
+            z3,
+            "fp.roundToIntegral",
+            DeclKind::FPA_ROUND_TO_INTEGRAL,
+            &[&rm, a],
+        )?
+        .as_float()
+        .ok_or(FloatEncoding)
+}
+
+/// Convert a float to a signed integer of `bits` bits, truncating towards zero
+pub(crate) fn fp_to_sbv<'ctx>(
+    decls: &FloatDecls<'ctx>,
+    a: &Float<'ctx>,
+    bits: u32,
+) -> Result<BV<'ctx>, JingleError> {
+    let z3 = a.get_ctx();
+    let rm = RoundingMode::TowardZero.model(decls, z3)?;
+    decls
+        .apply(
+            z3,
+            &format!("(_ fp.to_sbv {})", bits),
+            DeclKind::FPA_TO_SBV,
+            &[&rm, a],
+        )?
+        .as_bv()
+        .ok_or(FloatEncoding)
+}
+
+/// The declarations of the floating point theory's functions that the `z3` crate does not
+/// expose. The crate does not hand out its raw context either, so they cannot be built through
+/// `z3-sys`. Instead, we have z3 parse a small SMT-LIB term applying the function to
+/// placeholder constants of the right sorts and keep that term's declaration, after checking
+/// that it is the function we asked for. Parsing is slow, so each declaration is parsed once
+/// per combination of argument sorts and then reused.
+///
+/// Clones share their declarations, so every clone of a [State](crate::modeling::State) only
+/// parses each declaration once.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FloatDecls<'ctx> {
+    decls: Rc<RefCell<HashMap<DeclKey, FuncDecl<'ctx>>>>,
+}
+
+/// A function's SMT-LIB name and the sorts of its arguments
+type DeclKey = (String, Vec<String>);
+
+impl<'ctx> FloatDecls<'ctx> {
+    /// Apply the SMT-LIB function `func`, which z3 knows as `kind`, to `args`
+    fn apply(
+        &self,
+        z3: &'ctx Context,
+        func: &str,
+        kind: DeclKind,
+        args: &[&dyn Ast<'ctx>],
+    ) -> Result<Dynamic<'ctx>, JingleError> {
+        let sorts: Vec<String> = args.iter().map(|a| a.get_sort().to_string()).collect();
+        let mut decls = self.decls.borrow_mut();
+        let key = (func.to_string(), sorts);
+        if !decls.contains_key(&key) {
+            let decl = Self::parse(z3, &key.0, kind, &key.1)?;
+            decls.insert(key.clone(), decl);
+        }
+        Ok(decls[&key].apply(args))
+    }
+
+    /// The term is parsed as an optimization objective because, unlike solver assertions, the
+    /// `z3` crate hands objectives back with the lifetime of the [Context]. The crate does not
+    /// report why z3 rejected a script, so a failure carries the script itself.
+    fn parse(
+        z3: &'ctx Context,
+        func: &str,
+        kind: DeclKind,
+        sorts: &[String],
+    ) -> Result<FuncDecl<'ctx>, JingleError> {
+        let mut script = String::new();
+        let mut names = vec![];
+        for (i, sort) in sorts.iter().enumerate() {
+            script.push_str(&format!("(declare-const a{} {})", i, sort));
+            names.push(format!("a{}", i));
+        }
+        let term = if names.is_empty() {
+            func.to_string()
+        } else {
+            format!("({} {})", func, names.join(" "))
+        };
+        script.push_str(&format!("(minimize (ite (= {} {}) 0 1))", term, term));
+        let opt = Optimize::new(z3);
+        opt.from_string(script.as_str());
+        opt.get_objectives()
+            .first()
+            .and_then(|ite| ite.nth_child(0))
+            .and_then(|eq| eq.nth_child(0))
+            .and_then(|term| term.safe_decl().ok())
+            .filter(|decl| decl.kind() == kind && decl.arity() == sorts.len())
+            .ok_or(FloatDeclParse(script))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::JingleError::FloatDeclParse;
+    use crate::modeling::float::{
+        fp_arith, fp_eq, fp_is_nan, fp_sqrt, fp_to_sbv, FloatDecls, FloatFormat, RoundingMode,
+    };
+    use z3::ast::{Ast, Float, BV};
+    use z3::{Config, Context, DeclKind};
+
+    fn eval_bv(bv: BV) -> u64 {
+        let bv = bv.simplify();
+        assert!(bv.is_const());
+        bv.as_u64().unwrap()
+    }
+
+    #[test]
+    fn test_float_round_trip() {
+        let z3 = Context::new(&Config::new());
+        let decls = FloatDecls::default();
+        let single = FloatFormat::from_size(4).unwrap();
+        let bits = BV::from_u64(&z3, 1.5f32.to_bits() as u64, 32);
+        let float = single.decode(&decls, &bits).unwrap();
+        assert_eq!(
+            eval_bv(single.encode(&decls, &float).unwrap()),
+            1.5f32.to_bits() as u64
+        );
+    }
+
+    #[test]
+    fn test_float_arithmetic() {
+        let z3 = Context::new(&Config::new());
+        let decls = FloatDecls::default();
+        let double = FloatFormat::from_size(8).unwrap();
+        let a = double
+            .decode(&decls, &BV::from_u64(&z3, 2.25f64.to_bits(), 64))
+            .unwrap();
+        let b = double
+            .decode(&decls, &BV::from_u64(&z3, 0.5f64.to_bits(), 64))
+            .unwrap();
+        let sum = fp_arith(&decls, Float::add, &a, &b).unwrap();
+        assert_eq!(
+            eval_bv(double.encode(&decls, &sum).unwrap()),
+            2.75f64.to_bits()
+        );
+        let root = fp_sqrt(&decls, &a).unwrap();
+        assert_eq!(
+            eval_bv(double.encode(&decls, &root).unwrap()),
+            1.5f64.to_bits()
+        );
+        assert_eq!(
+            fp_eq(&decls, &a, &a).unwrap().simplify().as_bool(),
+            Some(true)
+        );
+        assert_eq!(
+            fp_is_nan(&decls, &a).unwrap().simplify().as_bool(),
+            Some(false)
+        );
+        // clones share the declarations parsed so far
+        let cached = decls.decls.borrow().len();
+        fp_to_sbv(&decls.clone(), &b, 16).unwrap();
+        assert_eq!(decls.decls.borrow().len(), cached + 1);
+        fp_to_sbv(&decls.clone(), &a, 16).unwrap();
+        assert_eq!(decls.decls.borrow().len(), cached + 1);
+    }
+
+    #[test]
+    fn test_float_conversions() {
+        let z3 = Context::new(&Config::new());
+        let decls = FloatDecls::default();
+        let single = FloatFormat::from_size(4).unwrap();
+        let double = FloatFormat::from_size(8).unwrap();
+        let int = BV::from_i64(&z3, -3, 32);
+        let float = single.int_to_float(&decls, &int).unwrap();
+        assert_eq!(
+            eval_bv(single.encode(&decls, &float).unwrap()),
+            (-3.0f32).to_bits() as u64
+        );
+        let widened = double.float_to_float(&decls, &float).unwrap();
+        assert_eq!(
+            eval_bv(double.encode(&decls, &widened).unwrap()),
+            (-3.0f64).to_bits()
+        );
+        let truncated = fp_to_sbv(
+            &decls,
+            &double
+                .decode(&decls, &BV::from_u64(&z3, (-7.9f64).to_bits(), 64))
+                .unwrap(),
+            32,
+        )
+        .unwrap();
+        assert_eq!(eval_bv(truncated), (-7i32) as u32 as u64);
+    }
+
+    #[test]
+    fn test_extended_precision() {
+        let z3 = Context::new(&Config::new());
+        let decls = FloatDecls::default();
+        let extended = FloatFormat::from_size(10).unwrap();
+        // 1.0 in x87 extended precision: biased exponent 0x3fff, explicit integer bit set
+        let one = BV::from_u64(&z3, 0x3fff, 16).concat(&BV::from_u64(&z3, 1 << 63, 64));
+        let float = extended.decode(&decls, &one).unwrap();
+        let two = fp_arith(&decls, Float::add, &float, &float).unwrap();
+        let expected = BV::from_u64(&z3, 0x4000, 16).concat(&BV::from_u64(&z3, 1 << 63, 64));
+        let result = extended.encode(&decls, &two).unwrap();
+        assert_eq!(result._eq(&expected).simplify().as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_decl_shapes() {
+        let z3 = Context::new(&Config::new());
+        let decls = FloatDecls::default();
+        let rm = RoundingMode::NearestEven.model(&decls, &z3).unwrap();
+        let bv = BV::new_const(&z3, "bv", 32);
+        let float = Float::new_const(&z3, "float", 8, 24);
+        // every function jingle parses, with every combination of sorts it is applied to
+        let cases: [(&str, DeclKind, &[&dyn Ast]); 9] = [
+            ("(_ to_fp 8 24)", DeclKind::FPA_TO_FP, &[&bv]),
+            ("(_ to_fp 8 24)", DeclKind::FPA_TO_FP, &[&rm, &bv]),
+            ("(_ to_fp 11 53)", DeclKind::FPA_TO_FP, &[&rm, &float]),
+            ("fp.to_ieee_bv", DeclKind::FPA_TO_IEEE_BV, &[&float]),
+            ("fp.eq", DeclKind::FPA_EQ, &[&float, &float]),
+            ("fp.isNaN", DeclKind::FPA_IS_NAN, &[&float]),
+            ("fp.sqrt", DeclKind::FPA_SQRT, &[&rm, &float]),
+            (
+                "fp.roundToIntegral",
+                DeclKind::FPA_ROUND_TO_INTEGRAL,
+                &[&rm, &float],
+            ),
+            ("(_ fp.to_sbv 32)", DeclKind::FPA_TO_SBV, &[&rm, &float]),
+        ];
+        for (func, kind, args) in cases {
+            let term = decls.apply(&z3, func, kind, args).unwrap();
+            assert_eq!(term.safe_decl().unwrap().kind(), kind, "{}", func);
+        }
+        assert_eq!(
+            rm.safe_decl().unwrap().kind(),
+            DeclKind::FPA_RM_NEAREST_TIES_TO_EVEN
+        );
+
+        match decls.apply(&z3, "fp.sqrt", DeclKind::FPA_SQRT, &[&bv]) {
+            Err(FloatDeclParse(script)) => assert!(script.contains("(fp.sqrt a0)")),
+            r => panic!("unexpected result {:?}", r),
+        }
+        match decls.apply(&z3, "fp.abs", DeclKind::FPA_SQRT, &[&float]) {
+            Err(FloatDeclParse(script)) => assert!(script.contains("(fp.abs a0)")),
+            r => panic!("unexpected result {:?}", r),
+        };
+    }
+
+    #[test]
+    fn test_unsupported_size() {
+        assert!(FloatFormat::from_size(3).is_err());
+    }
+}
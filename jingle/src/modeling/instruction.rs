@@ -1,4 +1,4 @@
-use crate::modeling::{ModelingContext, TranslationContext};
+use crate::modeling::{CallOtherRegistry, ModelingContext, TranslationContext};
 use jingle_sleigh::Instruction;
 use jingle_sleigh::PcodeOperation;
 
@@ -22,6 +22,7 @@ pub struct ModeledInstruction<'ctx> {
     inputs: HashSet<ResolvedVarnode<'ctx>>,
     outputs: HashSet<ResolvedVarnode<'ctx>>,
     branch_builder: BranchConstraint,
+    callother: CallOtherRegistry<'ctx>,
 }
 
 impl<'ctx> ModeledInstruction<'ctx> {
@@ -29,6 +30,16 @@ impl<'ctx> ModeledInstruction<'ctx> {
         instr: Instruction,
         sleigh: &T,
         z3: &'ctx Context,
+    ) -> Result<Self, JingleError> {
+        Self::new_with_callother(instr, sleigh, z3, CallOtherRegistry::default())
+    }
+
+    /// Model an instruction, using the given handlers for any `CALLOTHER` operations
+    pub fn new_with_callother<T: SpaceManager>(
+        instr: Instruction,
+        sleigh: &T,
+        z3: &'ctx Context,
+        callother: CallOtherRegistry<'ctx>,
     ) -> Result<Self, JingleError> {
         let original_state = State::new(z3, sleigh);
         let state = original_state.clone();
@@ -44,6 +55,7 @@ impl<'ctx> ModeledInstruction<'ctx> {
             inputs: Default::default(),
             outputs: Default::default(),
             branch_builder: BranchConstraint::new(&next_vn),
+            callother,
         };
         for x in model.instr.clone().ops.iter() {
             model.model_pcode_op(x)?;
@@ -52,7 +64,12 @@ impl<'ctx> ModeledInstruction<'ctx> {
     }
 
     pub fn fresh(&self) -> Result<Self, JingleError> {
-        ModeledInstruction::new(self.instr.clone(), self, self.z3)
+        ModeledInstruction::new_with_callother(
+            self.instr.clone(),
+            self,
+            self.z3,
+            self.callother.clone(),
+        )
     }
 }
 
@@ -106,6 +123,10 @@ impl<'ctx> ModelingContext<'ctx> for ModeledInstruction<'ctx> {
     fn get_branch_constraint(&self) -> &BranchConstraint {
         &self.branch_builder
     }
+
+    fn get_callother_registry(&self) -> &CallOtherRegistry<'ctx> {
+        &self.callother
+    }
 }
 
 impl<'ctx> TranslationContext<'ctx> for ModeledInstruction<'ctx> {
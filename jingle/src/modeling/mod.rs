@@ -1,25 +1,30 @@
 use crate::error::JingleError;
+use crate::modeling::float::{
+    fp_arith, fp_eq, fp_is_nan, fp_round_to_integral, fp_sqrt, fp_to_sbv, FloatFormat, RoundingMode,
+};
 
 use crate::varnode::ResolvedVarnode::{Direct, Indirect};
 use crate::varnode::{ResolvedIndirectVarNode, ResolvedVarnode};
-use jingle_sleigh::{GeneralizedVarNode, PcodeOperation, SpaceManager, SpaceType};
+use jingle_sleigh::{GeneralizedVarNode, PcodeOperation, SpaceManager, SpaceType, VarNode};
 use std::cmp::{min, Ordering};
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::hash::{DefaultHasher, Hash, Hasher};
-use std::ops::{Add, Neg};
 use tracing::instrument;
-use z3::ast::{Ast, Bool, BV};
-use z3::Context;
+use z3::ast::{Ast, Bool, Float, BV};
+use z3::{Context, FuncDecl, Sort};
 
 mod block;
 mod branch;
+mod callother;
+mod float;
 mod instruction;
 mod slice;
 mod state;
 
 pub use block::ModeledBlock;
 pub use branch::*;
+pub use callother::{CallOtherHandler, CallOtherInvocation, CallOtherRegistry};
 pub use instruction::ModeledInstruction;
 pub use state::State;
 
@@ -63,6 +68,9 @@ pub trait ModelingContext<'ctx>: SpaceManager + Debug + Sized {
     /// encapsulating the possible end-of-block behaviors of this trace
     fn get_branch_constraint(&self) -> &BranchConstraint;
 
+    /// Get the handlers used to model `CALLOTHER` operations in this trace
+    fn get_callother_registry(&self) -> &CallOtherRegistry<'ctx>;
+
     /// SLEIGH models instructions using many address spaces, some of which do not map directly to
     /// architectural spaces. For instance, the `unique` space is used as an intra-instruction
     /// "scratch pad" for intermediate results and is explicitly cleared between each instruction.
@@ -241,6 +249,29 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
         }
     }
 
+    /// A helper function to read and track an input [VarNode], interpreting its contents as
+    /// an IEEE-754 float of the appropriate size
+    fn read_float_and_track(&mut self, vn: &VarNode) -> Result<Float<'ctx>, JingleError> {
+        let bv = self.read_and_track(vn.into())?;
+        FloatFormat::from_size(vn.size)?.decode(self.get_final_state().float_decls(), &bv)
+    }
+
+    fn write_float(&mut self, vn: &VarNode, val: &Float<'ctx>) -> Result<(), JingleError> {
+        let bv =
+            FloatFormat::from_size(vn.size)?.encode(self.get_final_state().float_decls(), val)?;
+        self.write(&vn.into(), bv)
+    }
+
+    /// Writes a boolean result into a [VarNode] as `1` or `0`
+    fn write_bool(&mut self, vn: &VarNode, val: &Bool<'ctx>) -> Result<(), JingleError> {
+        let size = vn.size as u32 * 8;
+        let bv = val.ite(
+            &BV::from_u64(self.get_z3(), 1, size),
+            &BV::from_u64(self.get_z3(), 0, size),
+        );
+        self.write(&vn.into(), bv)
+    }
+
     fn write<'a, 'b: 'ctx>(
         &'a mut self,
         gen: &GeneralizedVarNode,
@@ -356,7 +387,7 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
             }
             PcodeOperation::IntNegate { input, output } => {
                 let bv = self.read_and_track(input.into())?;
-                let neg = bv.neg();
+                let neg = bv.bvnot();
                 self.write(&output.into(), neg)
             }
             PcodeOperation::IntMult {
@@ -476,8 +507,14 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
             } => {
                 let in0 = self.read_and_track(input0.into())?;
                 let in1 = self.read_and_track(input1.into())?;
-                // bool arg seems to be for whether this check is signed
-                let carry_bool = in0.bvadd_no_overflow(&in1, true);
+                // signed addition can leave the representable range in either direction
+                let carry_bool = Bool::and(
+                    self.get_z3(),
+                    &[
+                        &in0.bvadd_no_overflow(&in1, true),
+                        &in0.bvadd_no_underflow(&in1),
+                    ],
+                );
                 let out_bv = carry_bool.ite(
                     &BV::from_i64(self.get_z3(), 0, 8),
                     &BV::from_i64(self.get_z3(), 1, 8),
@@ -491,9 +528,14 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
             } => {
                 let in0 = self.read_and_track(input0.into())?;
                 let in1 = self.read_and_track(input1.into())?;
-                // todo: need to do some experimentation as to what the intended
-                // meaning of "overflow" is in sleigh vs what it means in z3
-                let borrow_bool = in0.bvsub_no_underflow(&in1, true);
+                // sleigh's "borrow" is signed overflow of the subtraction, in either direction
+                let borrow_bool = Bool::and(
+                    self.get_z3(),
+                    &[
+                        &in0.bvsub_no_underflow(&in1, true),
+                        &in0.bvsub_no_overflow(&in1),
+                    ],
+                );
                 let out_bv = borrow_bool.ite(
                     &BV::from_i64(self.get_z3(), 0, 8),
                     &BV::from_i64(self.get_z3(), 1, 8),
@@ -502,10 +544,7 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
             }
             PcodeOperation::Int2Comp { input, output } => {
                 let in0 = self.read_and_track(input.into())?;
-                let flipped = in0
-                    .bvneg()
-                    .add(BV::from_u64(self.get_z3(), 1, in0.get_size()));
-                self.write(&output.into(), flipped)
+                self.write(&output.into(), in0.bvneg())
             }
             PcodeOperation::IntSignedLess {
                 input0,
@@ -580,7 +619,7 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
             PcodeOperation::BoolNegate { input, output } => {
                 let val = self.read_and_track(input.into())?;
                 let negated = val
-                    .bvneg()
+                    .bvnot()
                     .bvand(&BV::from_u64(self.get_z3(), 1, val.get_size()));
                 self.write(&output.into(), negated)
             }
@@ -612,7 +651,7 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
                 let size = output.size as u32;
                 let in0 = self.read_and_track(input.into())?;
                 let mut outbv = BV::from_i64(self.get_z3(), 0, output.size as u32 * 8);
-                for i in 0..size * 8 {
+                for i in 0..in0.get_size() {
                     let extract = in0.extract(i, i);
                     let extend = extract.zero_ext((size * 8) - 1);
                     outbv = outbv.bvadd(&extend);
@@ -620,6 +659,190 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
 
                 self.write(&output.into(), outbv)
             }
+            PcodeOperation::LzCount { input, output } => {
+                let in0 = self.read_and_track(input.into())?;
+                let out_size = output.size as u32 * 8;
+                let width = in0.get_size();
+                let mut outbv = BV::from_u64(self.get_z3(), width as u64, out_size);
+                // walk upwards so that the highest set bit determines the count
+                for i in 0..width {
+                    let bit_set = in0.extract(i, i)._eq(&BV::from_u64(self.get_z3(), 1, 1));
+                    let count = BV::from_u64(self.get_z3(), (width - 1 - i) as u64, out_size);
+                    outbv = bit_set.ite(&count, &outbv);
+                }
+                self.write(&output.into(), outbv)
+            }
+            PcodeOperation::IntLessEqual {
+                input0,
+                input1,
+                output,
+            } => {
+                let in0 = self.read_and_track(input0.into())?;
+                let in1 = self.read_and_track(input1.into())?;
+                self.write_bool(output, &in0.bvule(&in1))
+            }
+            PcodeOperation::IntSignedLessEqual {
+                input0,
+                input1,
+                output,
+            } => {
+                let in0 = self.read_and_track(input0.into())?;
+                let in1 = self.read_and_track(input1.into())?;
+                self.write_bool(output, &in0.bvsle(&in1))
+            }
+            PcodeOperation::Piece {
+                input0,
+                input1,
+                output,
+            } => {
+                let high = self.read_and_track(input0.into())?;
+                let low = self.read_and_track(input1.into())?;
+                let piece = resize(high.concat(&low), output.size as u32 * 8, false);
+                self.write(&output.into(), piece)
+            }
+            PcodeOperation::Cast { input, output } => {
+                let val = self.read_and_track(input.into())?;
+                self.write(&output.into(), val)
+            }
+            PcodeOperation::Insert {
+                input0,
+                input1,
+                position,
+                size,
+                output,
+            } => {
+                let in0 = self.read_and_track(input0.into())?;
+                let in1 = self.read_and_track(input1.into())?;
+                // sleigh asserts that position and size are constants
+                let width = in0.get_size();
+                let field_mask = low_bits_mask(self.get_z3(), size.offset as u32, width);
+                let shift = BV::from_u64(self.get_z3(), position.offset, width);
+                let field = resize(in1, width, false).bvand(&field_mask).bvshl(&shift);
+                let cleared = in0.bvand(&field_mask.bvshl(&shift).bvnot());
+                let result = resize(cleared.bvor(&field), output.size as u32 * 8, false);
+                self.write(&output.into(), result)
+            }
+            PcodeOperation::Extract {
+                input0,
+                position,
+                size,
+                output,
+            } => {
+                let in0 = self.read_and_track(input0.into())?;
+                // sleigh asserts that position and size are constants
+                let width = in0.get_size();
+                let shift = BV::from_u64(self.get_z3(), position.offset, width);
+                let field = in0.bvlshr(&shift).bvand(&low_bits_mask(
+                    self.get_z3(),
+                    size.offset as u32,
+                    width,
+                ));
+                self.write(&output.into(), resize(field, output.size as u32 * 8, false))
+            }
+            PcodeOperation::PtrAdd {
+                input0,
+                input1,
+                input2,
+                output,
+            } => {
+                let base = self.read_and_track(input0.into())?;
+                let index = self.read_and_track(input1.into())?;
+                // sleigh asserts that the element size is a constant
+                let width = base.get_size();
+                let element_size = BV::from_u64(self.get_z3(), input2.offset, width);
+                let offset = resize(index, width, true).bvmul(&element_size);
+                self.write(&output.into(), base.bvadd(&offset))
+            }
+            PcodeOperation::PtrSub {
+                input0,
+                input1,
+                output,
+            } => {
+                let base = self.read_and_track(input0.into())?;
+                let offset = self.read_and_track(input1.into())?;
+                let width = base.get_size();
+                self.write(&output.into(), base.bvadd(&resize(offset, width, false)))
+            }
+            PcodeOperation::Indirect { input0, output, .. } => {
+                // input1 only identifies the operation that may indirectly affect input0; in a
+                // straight-line trace the value simply flows through
+                let val = self.read_and_track(input0.into())?;
+                self.write(&output.into(), val)
+            }
+            PcodeOperation::MultiEqual {
+                input0,
+                input1,
+                inputs,
+                output,
+            } => {
+                // a straight-line trace has no record of which predecessor was taken, so the
+                // choice of input is left to a selector named after the operation. As with
+                // CALLOTHER, identical operations share the same selector.
+                let mut hasher = DefaultHasher::new();
+                for vn in [output, input0, input1].into_iter().chain(inputs.iter()) {
+                    vn.hash(&mut hasher);
+                }
+                let selector = BV::new_const(
+                    self.get_z3(),
+                    format!("multiequal_{:x}", hasher.finish()),
+                    32,
+                );
+                let mut values = vec![];
+                for vn in [input0, input1].into_iter().chain(inputs.iter()) {
+                    values.push(self.read_and_track(vn.into())?);
+                }
+                let (last, rest) = values.split_at(values.len() - 1);
+                let mut result = last[0].clone();
+                for (i, val) in rest.iter().enumerate().rev() {
+                    let chosen = selector._eq(&BV::from_u64(self.get_z3(), i as u64, 32));
+                    result = chosen.ite(val, &result);
+                }
+                self.write(&output.into(), result)
+            }
+            PcodeOperation::SegmentOp {
+                input1,
+                input2,
+                output,
+                ..
+            } => {
+                // the segmented address calculation is defined per-processor in its pspec, so it
+                // is modeled as an uninterpreted function of the segment and offset.
+                // input0 only identifies the space of the result.
+                let segment = self.read_and_track(input1.into())?;
+                let offset = self.read_and_track(input2.into())?;
+                let result =
+                    uninterpreted(self.get_z3(), "segment_op", &[segment, offset], output.size)?;
+                self.write(&output.into(), result)
+            }
+            PcodeOperation::CPoolRef {
+                input0,
+                input1,
+                inputs,
+                output,
+            } => {
+                // constant pool contents are not visible to sleigh, so a reference is modeled as
+                // an uninterpreted function of the pool, the index, and any extra arguments
+                let mut args = vec![];
+                for vn in [input0, input1].into_iter().chain(inputs.iter()) {
+                    args.push(self.read_and_track(vn.into())?);
+                }
+                let result = uninterpreted(self.get_z3(), "cpool_ref", &args, output.size)?;
+                self.write(&output.into(), result)
+            }
+            PcodeOperation::New {
+                input,
+                size,
+                output,
+            } => {
+                // allocation is modeled as an uninterpreted function of the type (and size, for
+                // arrays) being allocated
+                let mut args = vec![self.read_and_track(input.into())?];
+                if let Some(size) = size {
+                    args.push(self.read_and_track(size.into())?);
+                }
+                let result = uninterpreted(self.get_z3(), "new", &args, output.size)?;
+                self.write(&output.into(), result)
+            }
             PcodeOperation::Branch { input } => {
                 self.get_branch_builder()
                     .set_last(&GeneralizedVarNode::from(input));
@@ -669,6 +892,32 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
                 Ok(())
             }
             PcodeOperation::CallOther { inputs, output } => {
+                // input0 is always the constant index of the userop
+                if let Some(index) = inputs.first().map(|vn| vn.offset) {
+                    if let Some(handler) = self.get_callother_registry().get(index) {
+                        let registry = self.get_callother_registry();
+                        let mut call = CallOtherInvocation::new(
+                            self.get_z3(),
+                            self.get_final_state(),
+                            index,
+                            registry.get_userop_name(index),
+                            &inputs[1..],
+                            output.as_ref(),
+                        );
+                        handler.model(&mut call)?;
+                        let effects = call.into_effects();
+                        for vn in effects.reads {
+                            self.read_and_track(vn)?;
+                        }
+                        for (vn, val) in effects.writes {
+                            self.write(&vn, val)?;
+                        }
+                        if let Some(dest) = effects.branch {
+                            self.get_branch_builder().set_last(&dest);
+                        }
+                        return Ok(());
+                    }
+                }
                 let mut hasher = DefaultHasher::new();
                 for vn in inputs {
                     vn.hash(&mut hasher);
@@ -714,11 +963,202 @@ pub(crate) trait TranslationContext<'ctx>: ModelingContext<'ctx> {
                 self.read_and_track(GeneralizedVarNode::from(&input.pointer_location))?;
                 Ok(())
             }
-            v => Err(JingleError::UnmodeledInstruction(Box::new(v.clone()))),
+            PcodeOperation::FloatEqual {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let eq = fp_eq(self.get_final_state().float_decls(), &f0, &f1)?;
+                self.write_bool(output, &eq)
+            }
+            PcodeOperation::FloatNotEqual {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let eq = fp_eq(self.get_final_state().float_decls(), &f0, &f1)?;
+                self.write_bool(output, &eq.not())
+            }
+            PcodeOperation::FloatLess {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                self.write_bool(output, &f0.lt(&f1))
+            }
+            PcodeOperation::FloatLessEqual {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                self.write_bool(output, &f0.le(&f1))
+            }
+            PcodeOperation::FloatNaN { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let nan = fp_is_nan(self.get_final_state().float_decls(), &f)?;
+                self.write_bool(output, &nan)
+            }
+            PcodeOperation::FloatAdd {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let sum = fp_arith(self.get_final_state().float_decls(), Float::add, &f0, &f1)?;
+                self.write_float(output, &sum)
+            }
+            PcodeOperation::FloatSub {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let diff = fp_arith(self.get_final_state().float_decls(), Float::sub, &f0, &f1)?;
+                self.write_float(output, &diff)
+            }
+            PcodeOperation::FloatMult {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let product = fp_arith(self.get_final_state().float_decls(), Float::mul, &f0, &f1)?;
+                self.write_float(output, &product)
+            }
+            PcodeOperation::FloatDiv {
+                input0,
+                input1,
+                output,
+            } => {
+                let f0 = self.read_float_and_track(input0)?;
+                let f1 = self.read_float_and_track(input1)?;
+                let quotient =
+                    fp_arith(self.get_final_state().float_decls(), Float::div, &f0, &f1)?;
+                self.write_float(output, &quotient)
+            }
+            PcodeOperation::FloatNeg { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                self.write_float(output, &f.unary_neg())
+            }
+            PcodeOperation::FloatAbs { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                self.write_float(output, &f.unary_abs())
+            }
+            PcodeOperation::FloatSqrt { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let root = fp_sqrt(self.get_final_state().float_decls(), &f)?;
+                self.write_float(output, &root)
+            }
+            PcodeOperation::FloatIntToFloat { input, output } => {
+                // sleigh treats the integer input as signed
+                let bv = self.read_and_track(input.into())?;
+                let f = FloatFormat::from_size(output.size)?
+                    .int_to_float(self.get_final_state().float_decls(), &bv)?;
+                self.write_float(output, &f)
+            }
+            PcodeOperation::FloatFloatToFloat { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let converted = FloatFormat::from_size(output.size)?
+                    .float_to_float(self.get_final_state().float_decls(), &f)?;
+                self.write_float(output, &converted)
+            }
+            PcodeOperation::FloatTrunc { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let int = fp_to_sbv(
+                    self.get_final_state().float_decls(),
+                    &f,
+                    output.size as u32 * 8,
+                )?;
+                self.write(&output.into(), int)
+            }
+            PcodeOperation::FloatCeil { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let ceil = fp_round_to_integral(
+                    self.get_final_state().float_decls(),
+                    RoundingMode::TowardPositive,
+                    &f,
+                )?;
+                self.write_float(output, &ceil)
+            }
+            PcodeOperation::FloatFloor { input, output } => {
+                let f = self.read_float_and_track(input)?;
+                let floor = fp_round_to_integral(
+                    self.get_final_state().float_decls(),
+                    RoundingMode::TowardNegative,
+                    &f,
+                )?;
+                self.write_float(output, &floor)
+            }
+            PcodeOperation::FloatRound { input, output } => {
+                // SLEIGH rounds halfway cases up, not away from zero, so this is floor(x + 0.5)
+                let f = self.read_float_and_track(input)?;
+                let decls = self.get_final_state().float_decls();
+                let half = FloatFormat::from_size(input.size)?
+                    .float_to_float(decls, &Float::from_f64(f.get_ctx(), 0.5))?;
+                let shifted = fp_arith(decls, Float::add, &f, &half)?;
+                let rounded = fp_round_to_integral(decls, RoundingMode::TowardNegative, &shifted)?;
+                self.write_float(output, &rounded)
+            }
         }
     }
 }
 
+/// Truncate or extend a bitvector to exactly `bits` bits
+fn resize(bv: BV<'_>, bits: u32, signed: bool) -> BV<'_> {
+    match bv.get_size().cmp(&bits) {
+        Ordering::Less if signed => bv.sign_ext(bits - bv.get_size()),
+        Ordering::Less => bv.zero_ext(bits - bv.get_size()),
+        Ordering::Greater => bv.extract(bits - 1, 0),
+        Ordering::Equal => bv,
+    }
+}
+
+/// A `width`-bit mask with the lowest `count` bits set
+fn low_bits_mask(z3: &Context, count: u32, width: u32) -> BV<'_> {
+    let ones = BV::from_i64(z3, -1, width);
+    if count >= width {
+        ones
+    } else {
+        ones.bvlshr(&BV::from_u64(z3, (width - count) as u64, width))
+    }
+}
+
+/// Apply an uninterpreted function, returning a bitvector of `size` bytes. Used for
+/// operations whose results depend on information that SLEIGH does not expose.
+fn uninterpreted<'ctx>(
+    z3: &'ctx Context,
+    name: &str,
+    args: &[BV<'ctx>],
+    size: usize,
+) -> Result<BV<'ctx>, JingleError> {
+    let domain: Vec<Sort> = args
+        .iter()
+        .map(|a| Sort::bitvector(z3, a.get_size()))
+        .collect();
+    let domain: Vec<&Sort> = domain.iter().collect();
+    let func = FuncDecl::new(
+        z3,
+        name,
+        domain.as_slice(),
+        &Sort::bitvector(z3, size as u32 * 8),
+    );
+    let args: Vec<&dyn Ast<'ctx>> = args.iter().map(|a| a as &dyn Ast<'ctx>).collect();
+    func.apply(args.as_slice())
+        .as_bv()
+        .ok_or(JingleError::UninterpretedSort)
+}
+
 fn zext_to_match<'ctx>(bv1: BV<'ctx>, bv2: &BV<'ctx>) -> BV<'ctx> {
     if bv1.get_size() < bv2.get_size() {
         bv1.zero_ext(bv2.get_size() - bv1.get_size())
@@ -726,3 +1166,744 @@ fn zext_to_match<'ctx>(bv1: BV<'ctx>, bv2: &BV<'ctx>) -> BV<'ctx> {
         bv1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::modeling::{
+        CallOtherInvocation, CallOtherRegistry, ModeledBlock, ModeledInstruction, ModelingContext,
+    };
+    use crate::JingleError;
+    use jingle_sleigh::tests::{TestContext, CONST, RAM, REGISTER};
+    use jingle_sleigh::PcodeOperation::*;
+    use jingle_sleigh::{
+        Disassembly, IndirectVarNode, Instruction, JingleSleighError, PcodeOperation,
+        SleighErrorClass, VarNode,
+    };
+    use z3::ast::{Ast, BV};
+    use z3::{Config, Context};
+
+    fn constant(offset: u64, size: usize) -> VarNode {
+        VarNode {
+            space_index: CONST,
+            offset,
+            size,
+        }
+    }
+
+    fn reg(offset: u64, size: usize) -> VarNode {
+        VarNode {
+            space_index: REGISTER,
+            offset,
+            size,
+        }
+    }
+
+    fn out(size: usize) -> VarNode {
+        reg(0x100, size)
+    }
+
+    fn model<'ctx>(z3: &'ctx Context, ops: Vec<PcodeOperation>) -> ModeledInstruction<'ctx> {
+        model_with_callother(z3, ops, CallOtherRegistry::default())
+    }
+
+    fn model_with_callother<'ctx>(
+        z3: &'ctx Context,
+        ops: Vec<PcodeOperation>,
+        callother: CallOtherRegistry<'ctx>,
+    ) -> ModeledInstruction<'ctx> {
+        ModeledInstruction::new_with_callother(
+            instruction(0x1000, 4, ops),
+            &TestContext::new(),
+            z3,
+            callother,
+        )
+        .unwrap()
+    }
+
+    fn instruction(address: u64, length: usize, ops: Vec<PcodeOperation>) -> Instruction {
+        Instruction {
+            disassembly: Disassembly {
+                mnemonic: "test".to_string(),
+                args: "".to_string(),
+                operands: vec![],
+            },
+            delay_slots: vec![],
+            ops,
+            length,
+            address,
+        }
+    }
+
+    fn concrete(bv: BV) -> u64 {
+        let bv = bv.simplify();
+        assert!(bv.is_const());
+        bv.as_u64().unwrap()
+    }
+
+    /// Model the given operations and concretely evaluate the given varnode afterward
+    fn eval_ops(ops: Vec<PcodeOperation>, vn: &VarNode) -> u64 {
+        let z3 = Context::new(&Config::new());
+        let model = model(&z3, ops);
+        concrete(model.get_final_state().read_varnode(vn).unwrap())
+    }
+
+    fn eval(op: PcodeOperation) -> u64 {
+        let output = op.output().unwrap();
+        match output {
+            jingle_sleigh::GeneralizedVarNode::Direct(d) => eval_ops(vec![op], &d),
+            jingle_sleigh::GeneralizedVarNode::Indirect(_) => unreachable!(),
+        }
+    }
+
+    fn binary(
+        f: fn(VarNode, VarNode, VarNode) -> PcodeOperation,
+        a: u64,
+        b: u64,
+        in_size: usize,
+        out_size: usize,
+    ) -> u64 {
+        eval(f(constant(a, in_size), constant(b, in_size), out(out_size)))
+    }
+
+    fn branch_dest(ops: Vec<PcodeOperation>) -> u64 {
+        let z3 = Context::new(&Config::new());
+        let model = model(&z3, ops);
+        concrete(model.get_branch_constraint().build_bv(&model).unwrap())
+    }
+
+    macro_rules! two_in {
+        ($name:ident) => {
+            |input0, input1, output| $name {
+                input0,
+                input1,
+                output,
+            }
+        };
+    }
+
+    #[test]
+    fn test_copy() {
+        let op = Copy {
+            input: constant(0xdead_beef, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xdead_beef);
+    }
+
+    #[test]
+    fn test_float_round() {
+        let round = |x: f64| {
+            let op = FloatRound {
+                input: constant(x.to_bits(), 8),
+                output: out(8),
+            };
+            f64::from_bits(eval(op))
+        };
+        assert_eq!(round(2.5), 3.0);
+        assert_eq!(round(2.4), 2.0);
+        assert_eq!(round(-2.5), -2.0);
+        assert_eq!(round(-2.6), -3.0);
+    }
+
+    #[test]
+    fn test_store_load() {
+        let ptr = IndirectVarNode {
+            pointer_space_index: RAM,
+            pointer_location: constant(0x2000, 8),
+            access_size_bytes: 4,
+        };
+        let ops = vec![
+            Store {
+                output: ptr.clone(),
+                input: constant(0x1234_5678, 4),
+            },
+            Load {
+                input: ptr,
+                output: out(4),
+            },
+        ];
+        assert_eq!(eval_ops(ops, &out(4)), 0x1234_5678);
+    }
+
+    #[test]
+    fn test_int_equal() {
+        assert_eq!(binary(two_in!(IntEqual), 5, 5, 4, 1), 1);
+        assert_eq!(binary(two_in!(IntEqual), 5, 6, 4, 1), 0);
+    }
+
+    #[test]
+    fn test_int_not_equal() {
+        assert_eq!(binary(two_in!(IntNotEqual), 5, 5, 4, 1), 0);
+        assert_eq!(binary(two_in!(IntNotEqual), 5, 6, 4, 1), 1);
+    }
+
+    #[test]
+    fn test_int_less() {
+        assert_eq!(binary(two_in!(IntLess), 1, 0xff, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntLess), 0xff, 1, 1, 1), 0);
+        assert_eq!(binary(two_in!(IntLess), 1, 1, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_int_signed_less() {
+        assert_eq!(binary(two_in!(IntSignedLess), 1, 0xff, 1, 1), 0);
+        assert_eq!(binary(two_in!(IntSignedLess), 0xff, 1, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_int_less_equal() {
+        assert_eq!(binary(two_in!(IntLessEqual), 1, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntLessEqual), 1, 0xff, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntLessEqual), 0xff, 1, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_int_signed_less_equal() {
+        assert_eq!(binary(two_in!(IntSignedLessEqual), 1, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedLessEqual), 0xff, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedLessEqual), 1, 0xff, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_int_zext() {
+        let op = IntZExt {
+            input: constant(0x80, 1),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0x80);
+    }
+
+    #[test]
+    fn test_int_sext() {
+        let op = IntSExt {
+            input: constant(0x80, 1),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xffff_ff80);
+    }
+
+    #[test]
+    fn test_int_add() {
+        assert_eq!(binary(two_in!(IntAdd), 0xffff_ffff, 2, 4, 4), 1);
+    }
+
+    #[test]
+    fn test_int_sub() {
+        assert_eq!(binary(two_in!(IntSub), 1, 2, 4, 4), 0xffff_ffff);
+    }
+
+    #[test]
+    fn test_int_carry() {
+        assert_eq!(binary(two_in!(IntCarry), 0xff, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntCarry), 0xfe, 1, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_int_signed_carry() {
+        assert_eq!(binary(two_in!(IntSignedCarry), 0x7f, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedCarry), 0x80, 0xff, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedCarry), 0xff, 1, 1, 1), 0);
+        assert_eq!(
+            binary(two_in!(IntSignedCarry), 0x8000_0000, 0xffff_ffff, 4, 1),
+            1
+        );
+    }
+
+    #[test]
+    fn test_int_signed_borrow() {
+        assert_eq!(binary(two_in!(IntSignedBorrow), 0x80, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedBorrow), 0x7f, 0xff, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntSignedBorrow), 0, 1, 1, 1), 0);
+        assert_eq!(
+            binary(two_in!(IntSignedBorrow), 0x7fff_ffff, 0xffff_ffff, 4, 1),
+            1
+        );
+    }
+
+    #[test]
+    fn test_int_2comp() {
+        let op = Int2Comp {
+            input: constant(1, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xffff_ffff);
+        let op = Int2Comp {
+            input: constant(0, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0);
+    }
+
+    #[test]
+    fn test_int_negate() {
+        let op = IntNegate {
+            input: constant(0xf0f0_0000, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0x0f0f_ffff);
+        let op = IntNegate {
+            input: constant(0, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xffff_ffff);
+    }
+
+    #[test]
+    fn test_int_xor() {
+        assert_eq!(binary(two_in!(IntXor), 0b1100, 0b1010, 1, 1), 0b0110);
+    }
+
+    #[test]
+    fn test_int_and() {
+        assert_eq!(binary(two_in!(IntAnd), 0b1100, 0b1010, 1, 1), 0b1000);
+    }
+
+    #[test]
+    fn test_int_or() {
+        assert_eq!(binary(two_in!(IntOr), 0b1100, 0b1010, 1, 1), 0b1110);
+    }
+
+    #[test]
+    fn test_int_left_shift() {
+        assert_eq!(binary(two_in!(IntLeftShift), 0x81, 1, 1, 1), 0x02);
+        let op = IntLeftShift {
+            input0: constant(1, 4),
+            input1: constant(4, 1),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0x10);
+    }
+
+    #[test]
+    fn test_int_right_shift() {
+        assert_eq!(binary(two_in!(IntRightShift), 0x80, 7, 1, 1), 1);
+        assert_eq!(binary(two_in!(IntRightShift), 0x80, 8, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_int_signed_right_shift() {
+        assert_eq!(binary(two_in!(IntSignedRightShift), 0x80, 7, 1, 1), 0xff);
+        assert_eq!(binary(two_in!(IntSignedRightShift), 0x40, 6, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_int_mult() {
+        assert_eq!(binary(two_in!(IntMult), 0x10, 0x11, 1, 1), 0x10);
+    }
+
+    #[test]
+    fn test_int_div() {
+        assert_eq!(binary(two_in!(IntDiv), 0xfe, 2, 1, 1), 0x7f);
+    }
+
+    #[test]
+    fn test_int_signed_div() {
+        assert_eq!(binary(two_in!(IntSignedDiv), 0xfe, 2, 1, 1), 0xff);
+    }
+
+    #[test]
+    fn test_int_rem() {
+        assert_eq!(binary(two_in!(IntRem), 0xff, 0x10, 1, 1), 0x0f);
+    }
+
+    #[test]
+    fn test_int_signed_rem() {
+        // -7 % 2 == -1
+        assert_eq!(binary(two_in!(IntSignedRem), 0xf9, 2, 1, 1), 0xff);
+    }
+
+    #[test]
+    fn test_bool_negate() {
+        let negate = |v| {
+            eval(BoolNegate {
+                input: constant(v, 1),
+                output: out(1),
+            })
+        };
+        assert_eq!(negate(0), 1);
+        assert_eq!(negate(1), 0);
+    }
+
+    #[test]
+    fn test_bool_xor() {
+        assert_eq!(binary(two_in!(BoolXor), 1, 1, 1, 1), 0);
+        assert_eq!(binary(two_in!(BoolXor), 1, 0, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_bool_and() {
+        assert_eq!(binary(two_in!(BoolAnd), 1, 1, 1, 1), 1);
+        assert_eq!(binary(two_in!(BoolAnd), 1, 0, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_bool_or() {
+        assert_eq!(binary(two_in!(BoolOr), 0, 0, 1, 1), 0);
+        assert_eq!(binary(two_in!(BoolOr), 1, 0, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_piece() {
+        let op = Piece {
+            input0: constant(0xdead, 2),
+            input1: constant(0xbeef, 2),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xdead_beef);
+    }
+
+    #[test]
+    fn test_subpiece() {
+        let op = SubPiece {
+            input0: constant(0xdead_beef, 4),
+            input1: constant(2, 4),
+            output: out(2),
+        };
+        assert_eq!(eval(op), 0xdead);
+    }
+
+    #[test]
+    fn test_cast() {
+        let op = Cast {
+            input: constant(0x1234, 2),
+            output: out(2),
+        };
+        assert_eq!(eval(op), 0x1234);
+    }
+
+    #[test]
+    fn test_ptr_add() {
+        let op = PtrAdd {
+            input0: constant(0x1000, 8),
+            input1: constant(3, 8),
+            input2: constant(4, 8),
+            output: out(8),
+        };
+        assert_eq!(eval(op), 0x100c);
+    }
+
+    #[test]
+    fn test_ptr_sub() {
+        assert_eq!(binary(two_in!(PtrSub), 0x1000, 0x10, 8, 8), 0x1010);
+    }
+
+    #[test]
+    fn test_insert() {
+        let op = Insert {
+            input0: constant(0xffff_ffff, 4),
+            input1: constant(0x5, 4),
+            position: constant(8, 4),
+            size: constant(4, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xffff_f5ff);
+    }
+
+    #[test]
+    fn test_extract() {
+        let op = Extract {
+            input0: constant(0xdead_beef, 4),
+            position: constant(8, 4),
+            size: constant(12, 4),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0xdbe);
+    }
+
+    #[test]
+    fn test_popcount() {
+        let op = PopCount {
+            input: constant(0xf0f0_0001, 4),
+            output: out(1),
+        };
+        assert_eq!(eval(op), 9);
+        // the output may be wider than the input
+        let op = PopCount {
+            input: constant(0xff, 1),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 8);
+    }
+
+    #[test]
+    fn test_lzcount() {
+        let lzcount = |v| {
+            eval(LzCount {
+                input: constant(v, 4),
+                output: out(1),
+            })
+        };
+        assert_eq!(lzcount(0x0001_0000), 15);
+        assert_eq!(lzcount(0x8000_0000), 0);
+        assert_eq!(lzcount(0), 32);
+    }
+
+    #[test]
+    fn test_multiequal() {
+        let op = MultiEqual {
+            input0: constant(7, 4),
+            input1: constant(7, 4),
+            inputs: vec![constant(7, 4)],
+            output: out(4),
+        };
+        assert_eq!(eval(op), 7);
+    }
+
+    #[test]
+    fn test_indirect() {
+        let op = Indirect {
+            input0: constant(0x42, 4),
+            input1: constant(0x1000, 8),
+            output: out(4),
+        };
+        assert_eq!(eval(op), 0x42);
+    }
+
+    /// Operations relying on information SLEIGH does not expose must at least be deterministic
+    fn assert_deterministic(op: PcodeOperation) {
+        let z3 = Context::new(&Config::new());
+        let output = out(8);
+        let first = model(&z3, vec![op.clone()]);
+        let second = model(&z3, vec![op]);
+        let a = first.get_final_state().read_varnode(&output).unwrap();
+        let b = second.get_final_state().read_varnode(&output).unwrap();
+        assert_eq!(a._eq(&b).simplify().as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_segment_op() {
+        assert_deterministic(SegmentOp {
+            input0: constant(1, 8),
+            input1: constant(0x10, 2),
+            input2: constant(0x20, 2),
+            output: out(8),
+        });
+    }
+
+    #[test]
+    fn test_cpool_ref() {
+        assert_deterministic(CPoolRef {
+            input0: constant(0x10, 8),
+            input1: constant(2, 8),
+            inputs: vec![],
+            output: out(8),
+        });
+    }
+
+    #[test]
+    fn test_new() {
+        assert_deterministic(New {
+            input: constant(0x10, 8),
+            size: Some(constant(4, 8)),
+            output: out(8),
+        });
+    }
+
+    #[test]
+    fn test_call_other() {
+        assert_deterministic(CallOther {
+            inputs: vec![constant(0, 4), constant(0x10, 8)],
+            output: Some(out(8)),
+        });
+    }
+
+    #[test]
+    fn test_branch() {
+        let dest = VarNode {
+            space_index: RAM,
+            offset: 0x4000,
+            size: 8,
+        };
+        assert_eq!(
+            branch_dest(vec![Branch {
+                input: dest.clone()
+            }]),
+            0x4000
+        );
+        assert_eq!(branch_dest(vec![Call { input: dest }]), 0x4000);
+    }
+
+    #[test]
+    fn test_cbranch() {
+        let dest = VarNode {
+            space_index: RAM,
+            offset: 0x4000,
+            size: 8,
+        };
+        let cbranch = |cond| {
+            branch_dest(vec![CBranch {
+                input0: dest.clone(),
+                input1: constant(cond, 1),
+            }])
+        };
+        assert_eq!(cbranch(1), 0x4000);
+        // falls through to the next instruction
+        assert_eq!(cbranch(0), 0x1004);
+    }
+
+    #[test]
+    fn test_indirect_branches() {
+        let target = IndirectVarNode {
+            pointer_space_index: RAM,
+            pointer_location: reg(0, 8),
+            access_size_bytes: 8,
+        };
+        let set_target = Copy {
+            input: constant(0x5000, 8),
+            output: reg(0, 8),
+        };
+        for op in [
+            BranchInd {
+                input: target.clone(),
+            },
+            CallInd {
+                input: target.clone(),
+            },
+            Return {
+                input: target.clone(),
+            },
+        ] {
+            assert_eq!(branch_dest(vec![set_target.clone(), op]), 0x5000);
+        }
+    }
+
+    fn userop(index: u64) -> PcodeOperation {
+        CallOther {
+            inputs: vec![constant(index, 4), constant(0x41, 8)],
+            output: Some(out(8)),
+        }
+    }
+
+    #[test]
+    fn test_callother_by_index() {
+        let z3 = Context::new(&Config::new());
+        let mut registry = CallOtherRegistry::new();
+        registry.register_index(3, |call: &mut CallOtherInvocation| {
+            let arg = call.arg(0)?;
+            call.write_output(arg.bvadd(&BV::from_u64(call.get_z3(), 1, 64)))
+        });
+        let model = model_with_callother(&z3, vec![userop(3)], registry);
+        let result = model.get_final_state().read_varnode(&out(8)).unwrap();
+        assert_eq!(concrete(result), 0x42);
+        // a handler that does not branch leaves the instruction falling through
+        assert!(!model.get_branch_constraint().has_branch());
+    }
+
+    #[test]
+    fn test_callother_by_name() {
+        let z3 = Context::new(&Config::new());
+        let mut registry = CallOtherRegistry::new();
+        registry.set_userop_name(5, "syscall");
+        registry.register_name("syscall", |call: &mut CallOtherInvocation| {
+            assert_eq!(call.name, Some("syscall"));
+            let rax = call.read(&reg(0, 8).into())?;
+            call.write(&reg(0, 8).into(), rax.bvnot());
+            Ok(())
+        });
+        let ops = vec![
+            Copy {
+                input: constant(0, 8),
+                output: reg(0, 8),
+            },
+            userop(5),
+        ];
+        let model = model_with_callother(&z3, ops, registry);
+        let result = model.get_final_state().read_varnode(&reg(0, 8)).unwrap();
+        assert_eq!(concrete(result), u64::MAX);
+    }
+
+    #[test]
+    fn test_callother_fallback() {
+        let z3 = Context::new(&Config::new());
+        let mut registry = CallOtherRegistry::new();
+        registry.register_index(3, |_: &mut CallOtherInvocation| {
+            Err(JingleError::MissingCallOtherOutput)
+        });
+        // unregistered userops are still modeled as an opaque branch
+        let model = model_with_callother(&z3, vec![userop(4)], registry.clone());
+        assert!(model.get_branch_constraint().has_branch());
+        // and handler errors are surfaced
+        let instr = instruction(0x1000, 4, vec![userop(3)]);
+        assert!(
+            ModeledInstruction::new_with_callother(instr, &TestContext::new(), &z3, registry)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_delay_slot_block() {
+        let z3 = Context::new(&Config::new());
+        let dest = VarNode {
+            space_index: RAM,
+            offset: 0x4000,
+            size: 8,
+        };
+        // a branch bundled with its delay slot, whose semantics come first
+        let mut bundle = instruction(
+            0x1000,
+            8,
+            vec![
+                Copy {
+                    input: constant(5, 4),
+                    output: out(4),
+                },
+                CBranch {
+                    input0: dest,
+                    input1: constant(0, 1),
+                },
+            ],
+        );
+        bundle.delay_slots.push(bundle.disassembly.clone());
+        let next = instruction(0x1008, 4, vec![]);
+        let block = ModeledBlock::read(
+            &z3,
+            &TestContext::new(),
+            vec![bundle, next].into_iter().map(Ok),
+        )
+        .unwrap();
+        assert_eq!(block.instructions.len(), 1);
+        assert_eq!(
+            concrete(block.get_branch_constraint().build_bv(&block).unwrap()),
+            0x1008
+        );
+        assert_eq!(
+            concrete(block.get_final_state().read_varnode(&out(4)).unwrap()),
+            5
+        );
+    }
+
+    #[test]
+    fn test_block_stop_reasons() {
+        let z3 = Context::new(&Config::new());
+        let straight_line = || {
+            Ok(instruction(
+                0x1000,
+                4,
+                vec![Copy {
+                    input: constant(5, 4),
+                    output: out(4),
+                }],
+            ))
+        };
+        let bad_opcode = Err(JingleSleighError::InstructionDecode {
+            address: 0x1004,
+            class: SleighErrorClass::BadData,
+            message: "Unable to resolve constructor".to_string(),
+        });
+        assert!(matches!(
+            ModeledBlock::read(&z3, &TestContext::new(), vec![straight_line()].into_iter()),
+            Err(JingleError::DisassemblyLengthBound)
+        ));
+        assert!(matches!(
+            ModeledBlock::read(
+                &z3,
+                &TestContext::new(),
+                vec![straight_line(), bad_opcode].into_iter()
+            ),
+            Err(JingleError::Sleigh(JingleSleighError::InstructionDecode {
+                address: 0x1004,
+                ..
+            }))
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::modeling::{BranchConstraint, ModelingContext, State};
+use crate::modeling::{BranchConstraint, CallOtherRegistry, ModelingContext, State};
 use crate::varnode::ResolvedVarnode;
 use jingle_sleigh::PcodeOperation;
 use std::collections::HashSet;
@@ -49,4 +49,8 @@ impl<'ctx, T: ModelingContext<'ctx>> ModelingContext<'ctx> for &[T] {
     fn get_branch_constraint(&self) -> &BranchConstraint {
         self.last().unwrap().get_branch_constraint()
     }
+
+    fn get_callother_registry(&self) -> &CallOtherRegistry<'ctx> {
+        self[0].get_callother_registry()
+    }
 }
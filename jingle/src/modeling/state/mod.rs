@@ -6,6 +6,7 @@ use crate::error::JingleError::{
     ZeroSizedVarnode,
 };
 
+use crate::modeling::float::FloatDecls;
 use crate::modeling::state::space::ModeledSpace;
 use crate::varnode::ResolvedVarnode;
 use jingle_sleigh::{
@@ -24,6 +25,7 @@ pub struct State<'ctx> {
     space_info: Vec<SpaceInfo>,
     spaces: Vec<ModeledSpace<'ctx>>,
     default_code_space_index: usize,
+    floats: FloatDecls<'ctx>,
 }
 
 impl<'ctx> SpaceManager for State<'ctx> {
@@ -47,6 +49,7 @@ impl<'ctx> State<'ctx> {
             space_info: other.get_all_space_info().to_vec(),
             spaces: Default::default(),
             default_code_space_index: other.get_code_space_idx(),
+            floats: Default::default(),
         };
         for space_info in other.get_all_space_info() {
             s.spaces.push(ModeledSpace::new(s.z3, space_info));
@@ -54,6 +57,11 @@ impl<'ctx> State<'ctx> {
         s
     }
 
+    /// The floating point declarations shared by this state and its clones
+    pub(crate) fn float_decls(&self) -> &FloatDecls<'ctx> {
+        &self.floats
+    }
+
     pub fn get_space(&self, idx: usize) -> Result<&Array<'ctx>, JingleError> {
         self.spaces
             .get(idx)
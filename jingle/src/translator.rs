@@ -1,9 +1,8 @@
 use crate::error::JingleError;
 use jingle_sleigh::context::SleighContext;
-use jingle_sleigh::{Instruction, RegisterManager, SpaceInfo, VarNode};
+use jingle_sleigh::{Instruction, RegisterManager, SpaceInfo, UserOpManager, VarNode};
 
-use crate::modeling::ModeledInstruction;
-use jingle_sleigh::JingleSleighError::InstructionDecode;
+use crate::modeling::{CallOtherRegistry, ModeledInstruction};
 use jingle_sleigh::SpaceManager;
 use z3::Context;
 
@@ -14,26 +13,42 @@ use z3::Context;
 pub struct SleighTranslator<'ctx> {
     z3_ctx: &'ctx Context,
     sleigh: &'ctx SleighContext,
+    callother: CallOtherRegistry<'ctx>,
 }
 
 impl<'ctx> SleighTranslator<'ctx> {
     /// Make a new sleigh translator
     pub fn new(sleigh: &'ctx SleighContext, z3_ctx: &'ctx Context) -> Self {
-        Self { z3_ctx, sleigh }
+        let mut callother = CallOtherRegistry::new();
+        for (index, name) in sleigh.get_userops() {
+            callother.set_userop_name(index, &name);
+        }
+        Self {
+            z3_ctx,
+            sleigh,
+            callother,
+        }
+    }
+
+    /// The handlers used to model `CALLOTHER` operations in instructions produced by
+    /// this translator
+    pub fn callother_registry(&self) -> &CallOtherRegistry<'ctx> {
+        &self.callother
+    }
+
+    /// Mutable access to the `CALLOTHER` handlers, for registering custom userop semantics
+    pub fn callother_registry_mut(&mut self) -> &mut CallOtherRegistry<'ctx> {
+        &mut self.callother
     }
 
     /// Ask sleigh to read one instruction from the given offset and attempt
-    /// to model it
-    /// todo: this approach might not work with MIPS delayslots
+    /// to model it. Instructions with delay slots are modeled together with
+    /// the instructions in their delay slot
     pub fn model_instruction_at(
         &self,
         offset: u64,
     ) -> Result<ModeledInstruction<'ctx>, JingleError> {
-        let op = self
-            .sleigh
-            .read(offset, 1)
-            .next()
-            .ok_or(InstructionDecode)?;
+        let op = self.sleigh.instruction_at(offset)?;
         self.model_instruction(op)
     }
 
@@ -42,7 +57,12 @@ impl<'ctx> SleighTranslator<'ctx> {
         &self,
         instr: Instruction,
     ) -> Result<ModeledInstruction<'ctx>, JingleError> {
-        ModeledInstruction::new(instr, self.sleigh, self.z3_ctx)
+        ModeledInstruction::new_with_callother(
+            instr,
+            self.sleigh,
+            self.z3_ctx,
+            self.callother.clone(),
+        )
     }
 }
 
@@ -58,6 +78,10 @@ impl<'ctx> SpaceManager for SleighTranslator<'ctx> {
     fn get_code_space_idx(&self) -> usize {
         self.sleigh.get_code_space_idx()
     }
+
+    fn as_userop_manager(&self) -> Option<&dyn UserOpManager> {
+        Some(self)
+    }
 }
 
 impl<'ctx> RegisterManager for SleighTranslator<'ctx> {
@@ -72,4 +96,38 @@ impl<'ctx> RegisterManager for SleighTranslator<'ctx> {
     fn get_registers(&self) -> Vec<(VarNode, String)> {
         self.sleigh.get_registers()
     }
+
+    fn get_parent_register(&self, name: &str) -> Option<&str> {
+        self.sleigh.get_parent_register(name)
+    }
+
+    fn get_child_registers(&self, name: &str) -> Vec<&str> {
+        self.sleigh.get_child_registers(name)
+    }
+
+    fn get_covering_register(&self, location: &VarNode) -> Option<&str> {
+        self.sleigh.get_covering_register(location)
+    }
+
+    fn get_overlapping_registers(&self, location: &VarNode) -> Vec<&str> {
+        self.sleigh.get_overlapping_registers(location)
+    }
+
+    fn get_register_space_idx(&self) -> Option<usize> {
+        self.sleigh.get_register_space_idx()
+    }
+}
+
+impl<'ctx> UserOpManager for SleighTranslator<'ctx> {
+    fn get_userop_name(&self, index: u64) -> Option<&str> {
+        self.sleigh.get_userop_name(index)
+    }
+
+    fn get_userop_index(&self, name: &str) -> Option<u64> {
+        self.sleigh.get_userop_index(name)
+    }
+
+    fn get_userops(&self) -> Vec<(u64, String)> {
+        self.sleigh.get_userops()
+    }
 }
@@ -22,7 +22,6 @@ fn main() {
         "src/ffi/context_ffi.rs",
         "src/ffi/instruction.rs",
         "src/ffi/opcode.rs",
-        "src/ffi/image.rs",
     ];
 
     let mut cpp_sources = vec![
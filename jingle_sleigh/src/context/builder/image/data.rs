@@ -0,0 +1,119 @@
+#[cfg(feature = "mmap")]
+use crate::error::JingleSleighError;
+#[cfg(feature = "mmap")]
+use crate::error::JingleSleighError::ImageFileRead;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+#[cfg(feature = "mmap")]
+use std::ops::Range;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use std::sync::Arc;
+
+/// The bytes of an [ImageSection](super::ImageSection).
+///
+/// Section bytes are reference counted, so cloning an [Image](super::Image) (as is done to
+/// hand it to `SLEIGH`) does not copy them. Bytes borrowed from a [MappedFile] are read
+/// straight out of the mapping; they are only copied if the section is modified.
+#[derive(Clone)]
+pub struct SectionData(Backing);
+
+#[derive(Clone)]
+enum Backing {
+    Owned(Arc<Vec<u8>>),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedFile, Range<usize>),
+}
+
+impl SectionData {
+    /// Get mutable access to the bytes, copying them first if they are shared with another
+    /// image or borrowed from a file
+    pub fn to_mut(&mut self) -> &mut Vec<u8> {
+        #[cfg(feature = "mmap")]
+        if let Backing::Mapped(file, range) = &self.0 {
+            self.0 = Backing::Owned(Arc::new(file.bytes()[range.clone()].to_vec()));
+        }
+        match &mut self.0 {
+            Backing::Owned(bytes) => Arc::make_mut(bytes),
+            #[cfg(feature = "mmap")]
+            Backing::Mapped(..) => unreachable!(),
+        }
+    }
+
+    /// Whether the bytes are borrowed from a [MappedFile]
+    pub fn is_mapped(&self) -> bool {
+        match self.0 {
+            Backing::Owned(_) => false,
+            #[cfg(feature = "mmap")]
+            Backing::Mapped(..) => true,
+        }
+    }
+}
+
+impl Deref for SectionData {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Backing::Owned(bytes) => bytes.as_slice(),
+            #[cfg(feature = "mmap")]
+            Backing::Mapped(file, range) => &file.bytes()[range.clone()],
+        }
+    }
+}
+
+impl From<Vec<u8>> for SectionData {
+    fn from(value: Vec<u8>) -> Self {
+        Self(Backing::Owned(Arc::new(value)))
+    }
+}
+
+impl PartialEq<Vec<u8>> for SectionData {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.deref() == other.as_slice()
+    }
+}
+
+impl Debug for SectionData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SectionData")
+            .field("len", &self.len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+/// A read-only memory mapping of a file, out of which [SectionData] can be borrowed without
+/// copying. The mapping is kept alive for as long as any section borrows from it.
+///
+/// The file must not be modified while it is mapped.
+#[cfg(feature = "mmap")]
+#[derive(Clone)]
+pub struct MappedFile(Arc<Mmap>);
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, JingleSleighError> {
+        let error = |source| ImageFileRead {
+            path: path.as_ref().to_path_buf(),
+            source,
+        };
+        let file = std::fs::File::open(path.as_ref()).map_err(error)?;
+        // SAFETY: the mapping is read-only, and we document that the file must not be
+        // modified while mapped
+        let map = unsafe { Mmap::map(&file) }.map_err(error)?;
+        Ok(Self(Arc::new(map)))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Borrow the given range of the file as section bytes, if it lies within the file
+    pub fn slice(&self, range: Range<usize>) -> Option<SectionData> {
+        self.bytes().get(range.clone())?;
+        Some(SectionData(Backing::Mapped(self.clone(), range)))
+    }
+}
@@ -1,9 +1,27 @@
+#[cfg(feature = "mmap")]
+use crate::context::builder::image::data::MappedFile;
+use crate::context::builder::image::data::SectionData;
 use crate::context::builder::image::Perms;
+use crate::context::builder::image::{Image, ImageSection, ImageSymbol, SymbolBinding, SymbolKind};
 use crate::error::JingleSleighError;
 use crate::error::JingleSleighError::ImageLoadError;
-use crate::ffi::image::bridge::{Image, ImageSection};
-use elf::abi::{PF_R, PF_W, PF_X, PT_LOAD};
+use elf::abi::{
+    DT_JMPREL, DT_PLTREL, DT_PLTRELSZ, DT_REL, DT_RELA, DT_RELASZ, DT_RELSZ, DT_STRSZ, DT_STRTAB,
+    DT_SYMTAB, EM_386, EM_AARCH64, EM_ARM, EM_RISCV, EM_X86_64, ET_DYN, PF_R, PF_W, PF_X, PT_LOAD,
+    R_AARCH64_ABS64, R_AARCH64_GLOB_DAT, R_AARCH64_JUMP_SLOT, R_AARCH64_RELATIVE, R_ARM_ABS32,
+    R_ARM_GLOB_DAT, R_ARM_JUMP_SLOT, R_ARM_RELATIVE, R_RISCV_32, R_RISCV_64, R_RISCV_JUMP_SLOT,
+    R_RISCV_RELATIVE, R_X86_64_64, R_X86_64_GLOB_DAT, R_X86_64_JUMP_SLOT, R_X86_64_RELATIVE,
+    SHF_ALLOC, SHN_ABS, SHT_REL, SHT_RELA, STB_GLOBAL, STB_WEAK, STT_FILE, STT_FUNC, STT_GNU_IFUNC,
+    STT_OBJECT, STT_SECTION, STT_TLS,
+};
+#[cfg(feature = "mmap")]
+use elf::endian::AnyEndian;
 use elf::endian::EndianParse;
+use elf::file::Class;
+use elf::relocation::{Rel, RelIterator, Rela, RelaIterator};
+use elf::segment::ProgramHeader;
+use elf::string_table::StringTable;
+use elf::symbol::{Symbol, SymbolTable};
 use elf::ElfBytes;
 use std::cmp::min;
 
@@ -11,37 +29,438 @@ impl<'data, E: EndianParse> TryFrom<ElfBytes<'data, E>> for Image {
     type Error = JingleSleighError;
 
     fn try_from(value: ElfBytes<E>) -> Result<Self, Self::Error> {
-        let mut img: Image = Image { sections: vec![] };
-        let segments = value.segments().ok_or(ImageLoadError)?;
+        Image::from_elf(&value, 0)
+    }
+}
+
+impl Image {
+    /// Load the `PT_LOAD` segments of an ELF. Position-independent files (`ET_DYN`) are loaded
+    /// `load_base` bytes above their link addresses; all other files are loaded at their link
+    /// addresses.
+    ///
+    /// Dynamic relocations are applied for x86, x86-64, ARM, AArch64 and RISC-V; relocations of
+    /// other architectures or types are left as they are in the file. They are found through the
+    /// section headers, or through the `PT_DYNAMIC` segment if the file has none. Imported
+    /// symbols are assigned stub addresses in a zero-filled section placed after the rest of the
+    /// image, and recorded with [SymbolKind::Import], so that pointers into the GOT lead
+    /// somewhere recognizable.
+    pub fn from_elf<E: EndianParse>(
+        elf: &ElfBytes<E>,
+        load_base: u64,
+    ) -> Result<Self, JingleSleighError> {
+        load_elf(elf, load_base, |_| None)
+    }
+
+    /// Load an ELF out of a [MappedFile], as with [Image::from_elf]. Segments that are fully
+    /// backed by the file are borrowed from the mapping rather than copied, until relocations
+    /// are applied to them.
+    #[cfg(feature = "mmap")]
+    pub fn from_mapped_elf(file: &MappedFile, load_base: u64) -> Result<Self, JingleSleighError> {
+        let elf = ElfBytes::<AnyEndian>::minimal_parse(file.bytes())?;
+        load_elf(&elf, load_base, |hdr| {
+            let start = hdr.p_offset as usize;
+            (hdr.p_filesz >= hdr.p_memsz)
+                .then(|| file.slice(start..start + hdr.p_memsz as usize))
+                .flatten()
+        })
+    }
+}
+
+/// Load an ELF, using `borrow` to avoid copying the contents of a segment where possible
+fn load_elf<E: EndianParse, F: Fn(&ProgramHeader) -> Option<SectionData>>(
+    elf: &ElfBytes<E>,
+    load_base: u64,
+    borrow: F,
+) -> Result<Image, JingleSleighError> {
+    let bias = match elf.ehdr.e_type {
+        ET_DYN => load_base,
+        _ => 0,
+    };
+    let mut img: Image = Image {
+        sections: vec![],
+        symbols: Default::default(),
+    };
+    let segments = elf.segments().ok_or(ImageLoadError)?;
+    for hdr in segments.iter().filter(|seg| seg.p_type == PT_LOAD) {
+        let addr = hdr.p_vaddr + bias;
+        let mem_size = hdr.p_memsz;
+        let flags = hdr.p_flags;
+
+        let perms = Perms {
+            exec: (flags & PF_X) == PF_X,
+            write: (flags & PF_W) == PF_W,
+            read: (flags & PF_R) == PF_R,
+        };
+        let data = match borrow(&hdr) {
+            Some(data) => data,
+            None => {
+                let file_data = elf.segment_data(&hdr)?;
+                let mut data = vec![0; mem_size as usize];
+                let len = min(mem_size as usize, file_data.len());
+                data[0..len].copy_from_slice(&file_data[0..len]);
+                data.into()
+            }
+        };
+        img.sections.push(ImageSection {
+            perms,
+            base_address: addr as usize,
+            data,
+            module: String::new(),
+        })
+    }
+    if let Some((symbols, strings)) = elf.symbol_table()? {
+        read_symbols(&mut img, symbols, strings, bias)?;
+    }
+    if let Some((symbols, strings)) = elf.dynamic_symbol_table()? {
+        read_symbols(&mut img, symbols, strings, bias)?;
+    }
+    apply_relocations(elf, &mut img, bias)?;
+    Ok(img)
+}
+
+/// Add the defined, named symbols of a symbol table to the image. A symbol appearing in both
+/// the static and dynamic tables is only added once.
+fn read_symbols<E: EndianParse>(
+    img: &mut Image,
+    symbols: SymbolTable<E>,
+    strings: StringTable,
+    bias: u64,
+) -> Result<(), JingleSleighError> {
+    for sym in symbols.iter().filter(|s| !s.is_undefined()) {
+        let name = strings.get(sym.st_name as usize)?;
+        let kind = match sym.st_symtype() {
+            STT_FUNC | STT_GNU_IFUNC => SymbolKind::Function,
+            STT_OBJECT => SymbolKind::Data,
+            STT_SECTION => SymbolKind::Section,
+            STT_FILE => SymbolKind::File,
+            STT_TLS => SymbolKind::Tls,
+            _ => SymbolKind::Unknown,
+        };
+        let binding = match sym.st_bind() {
+            STB_GLOBAL => SymbolBinding::Global,
+            STB_WEAK => SymbolBinding::Weak,
+            _ => SymbolBinding::Local,
+        };
+        img.add_symbol(ImageSymbol {
+            name: name.to_string(),
+            address: symbol_address(&sym, bias),
+            size: sym.st_size,
+            kind,
+            binding,
+        })
+    }
+    Ok(())
+}
+
+/// The address a defined symbol ends up at once the image is loaded. Absolute symbols are
+/// plain values rather than addresses in the file, so the load bias does not apply to them.
+fn symbol_address(sym: &Symbol, bias: u64) -> u64 {
+    match sym.st_shndx {
+        SHN_ABS => sym.st_value,
+        _ => sym.st_value + bias,
+    }
+}
+
+// the i386 relocation types share their numbering with x86-64, but the `elf` crate does not
+// define them
+const R_386_32: u32 = 1;
+const R_386_GLOB_DAT: u32 = 6;
+const R_386_JMP_SLOT: u32 = 7;
+const R_386_RELATIVE: u32 = 8;
+
+/// How the value of a relocation is computed, in terms of the symbol value `S`, the addend `A`
+/// and the load bias `B`
+enum RelocationKind {
+    /// `S + A`
+    Absolute,
+    /// `S`, plus an explicit addend if there is one; implicit addends of these relocations
+    /// refer to the lazy binding machinery and are discarded
+    Symbol,
+    /// `B + A`
+    Relative,
+}
+
+fn classify_relocation(machine: u16, r_type: u32) -> Option<RelocationKind> {
+    match (machine, r_type) {
+        (EM_X86_64, R_X86_64_64)
+        | (EM_386, R_386_32)
+        | (EM_AARCH64, R_AARCH64_ABS64)
+        | (EM_ARM, R_ARM_ABS32)
+        | (EM_RISCV, R_RISCV_32 | R_RISCV_64) => Some(RelocationKind::Absolute),
+        (EM_X86_64, R_X86_64_GLOB_DAT | R_X86_64_JUMP_SLOT)
+        | (EM_386, R_386_GLOB_DAT | R_386_JMP_SLOT)
+        | (EM_AARCH64, R_AARCH64_GLOB_DAT | R_AARCH64_JUMP_SLOT)
+        | (EM_ARM, R_ARM_GLOB_DAT | R_ARM_JUMP_SLOT)
+        | (EM_RISCV, R_RISCV_JUMP_SLOT) => Some(RelocationKind::Symbol),
+        (EM_X86_64, R_X86_64_RELATIVE)
+        | (EM_386, R_386_RELATIVE)
+        | (EM_AARCH64, R_AARCH64_RELATIVE)
+        | (EM_ARM, R_ARM_RELATIVE)
+        | (EM_RISCV, R_RISCV_RELATIVE) => Some(RelocationKind::Relative),
+        _ => None,
+    }
+}
+
+/// A relocation entry, with the addend made explicit for `REL`-style relocations
+struct Relocation {
+    offset: u64,
+    sym: u32,
+    r_type: u32,
+    addend: Option<i64>,
+}
+
+impl From<Rela> for Relocation {
+    fn from(r: Rela) -> Self {
+        Relocation {
+            offset: r.r_offset,
+            sym: r.r_sym,
+            r_type: r.r_type,
+            addend: Some(r.r_addend),
+        }
+    }
+}
+
+impl From<Rel> for Relocation {
+    fn from(r: Rel) -> Self {
+        Relocation {
+            offset: r.r_offset,
+            sym: r.r_sym,
+            r_type: r.r_type,
+            addend: None,
+        }
+    }
+}
+
+/// The dynamic relocations of a file, along with the symbol and string tables they refer to
+type DynamicRelocations<'data, E> = (Vec<Relocation>, SymbolTable<'data, E>, StringTable<'data>);
+
+/// Find the dynamic relocations through the allocated `REL`/`RELA` sections; relocations in
+/// non-allocated sections are for the static linker
+fn section_relocations<'data, E: EndianParse>(
+    elf: &ElfBytes<'data, E>,
+) -> Result<Option<DynamicRelocations<'data, E>>, JingleSleighError> {
+    let (Some(shdrs), Some((dynsyms, dynstrs))) =
+        (elf.section_headers(), elf.dynamic_symbol_table()?)
+    else {
+        return Ok(None);
+    };
+    let mut relocations = vec![];
+    for shdr in shdrs.iter().filter(|s| s.sh_flags & SHF_ALLOC as u64 != 0) {
+        match shdr.sh_type {
+            SHT_RELA => {
+                let relas = elf.section_data_as_relas(&shdr)?;
+                relocations.extend(relas.map(Relocation::from))
+            }
+            SHT_REL => {
+                let rels = elf.section_data_as_rels(&shdr)?;
+                relocations.extend(rels.map(Relocation::from))
+            }
+            _ => {}
+        }
+    }
+    Ok(Some((relocations, dynsyms, dynstrs)))
+}
+
+/// Find the dynamic relocations through the `PT_DYNAMIC` segment, for files whose section
+/// headers have been stripped
+fn segment_relocations<'data, E: EndianParse>(
+    elf: &ElfBytes<'data, E>,
+) -> Result<Option<DynamicRelocations<'data, E>>, JingleSleighError> {
+    let (Some(dynamic), Some(segments)) = (elf.dynamic()?, elf.segments()) else {
+        return Ok(None);
+    };
+    let tag = |tag: i64| dynamic.iter().find(|d| d.d_tag == tag).map(|d| d.d_val());
+    // the dynamic table holds link addresses, which have to be mapped back to file data
+    let data_at = |addr: u64| -> Result<Option<&'data [u8]>, JingleSleighError> {
         for hdr in segments.iter().filter(|seg| seg.p_type == PT_LOAD) {
-            let addr = hdr.p_vaddr;
-            let mem_size = hdr.p_memsz;
-            let flags = hdr.p_flags;
-
-            let file_data = value.segment_data(&hdr)?;
-
-            let perms = Perms {
-                exec: (flags & PF_X) == PF_X,
-                write: (flags & PF_W) == PF_W,
-                read: (flags & PF_R) == PF_R,
-            };
-            let mut data = vec![0; mem_size as usize];
-            let len = min(mem_size as usize, file_data.len());
-            data[0..len].copy_from_slice(&file_data[0..len]);
-            img.sections.push(ImageSection {
-                perms,
-                base_address: addr as usize,
-                data,
+            if let Some(start) = addr.checked_sub(hdr.p_vaddr) {
+                if start < hdr.p_filesz {
+                    return Ok(elf.segment_data(&hdr)?.get(start as usize..));
+                }
+            }
+        }
+        Ok(None)
+    };
+    let table = |addr_tag: i64, size_tag: i64| -> Result<Option<&'data [u8]>, JingleSleighError> {
+        let (Some(addr), Some(size)) = (tag(addr_tag), tag(size_tag)) else {
+            return Ok(None);
+        };
+        Ok(data_at(addr)?.and_then(|data| data.get(..size as usize)))
+    };
+
+    let (endian, class) = (elf.ehdr.endianness, elf.ehdr.class);
+    // the number of dynamic symbols is only recorded in the hash tables, but relocations
+    // only ever look symbols up by index, so the table can run to the end of its segment
+    let (Some(symbols), Some(strings)) = (
+        tag(DT_SYMTAB).map(data_at).transpose()?.flatten(),
+        table(DT_STRTAB, DT_STRSZ)?,
+    ) else {
+        return Ok(None);
+    };
+    let mut relocations: Vec<Relocation> = vec![];
+    if let Some(data) = table(DT_RELA, DT_RELASZ)? {
+        relocations.extend(RelaIterator::new(endian, class, data).map(Relocation::from));
+    }
+    if let Some(data) = table(DT_REL, DT_RELSZ)? {
+        relocations.extend(RelIterator::new(endian, class, data).map(Relocation::from));
+    }
+    // some linkers count the PLT relocations in DT_RELASZ/DT_RELSZ as well; applying an
+    // implicit-addend relocation twice would corrupt it
+    let jmprel = tag(DT_JMPREL);
+    let covers_jmprel = |addr_tag: i64, size_tag: i64| {
+        let (Some(addr), Some(size), Some(jmprel)) = (tag(addr_tag), tag(size_tag), jmprel) else {
+            return false;
+        };
+        (addr..addr.saturating_add(size)).contains(&jmprel)
+    };
+    let counted = covers_jmprel(DT_RELA, DT_RELASZ) || covers_jmprel(DT_REL, DT_RELSZ);
+    if let (false, Some(data)) = (counted, table(DT_JMPREL, DT_PLTRELSZ)?) {
+        if tag(DT_PLTREL) == Some(DT_RELA as u64) {
+            relocations.extend(RelaIterator::new(endian, class, data).map(Relocation::from));
+        } else {
+            relocations.extend(RelIterator::new(endian, class, data).map(Relocation::from));
+        }
+    }
+    Ok(Some((
+        relocations,
+        SymbolTable::new(endian, class, symbols),
+        StringTable::new(strings),
+    )))
+}
+
+/// Reads and writes pointer-sized values in the loaded image
+struct PointerWriter {
+    size: usize,
+    little_endian: bool,
+}
+
+impl PointerWriter {
+    fn read(&self, img: &Image, addr: u64) -> Option<u64> {
+        let bytes = img.sections.iter().find_map(|s| {
+            let start = (addr as usize).checked_sub(s.base_address)?;
+            s.data.get(start..start + self.size)
+        })?;
+        let mut buf = [0u8; 8];
+        if self.little_endian {
+            buf[0..self.size].copy_from_slice(bytes);
+            Some(u64::from_le_bytes(buf))
+        } else {
+            buf[8 - self.size..].copy_from_slice(bytes);
+            Some(u64::from_be_bytes(buf))
+        }
+    }
+
+    fn write(&self, img: &mut Image, addr: u64, value: u64) {
+        let size = self.size;
+        let section = img.sections.iter_mut().find(|s| {
+            (addr as usize)
+                .checked_sub(s.base_address)
+                .is_some_and(|start| start + size <= s.data.len())
+        });
+        if let Some(section) = section {
+            let start = addr as usize - section.base_address;
+            let bytes = &mut section.data.to_mut()[start..start + size];
+            if self.little_endian {
+                bytes.copy_from_slice(&value.to_le_bytes()[0..size]);
+            } else {
+                bytes.copy_from_slice(&value.to_be_bytes()[8 - size..]);
+            }
+        }
+    }
+}
+
+fn apply_relocations<E: EndianParse>(
+    elf: &ElfBytes<E>,
+    img: &mut Image,
+    bias: u64,
+) -> Result<(), JingleSleighError> {
+    let found = match elf.section_headers() {
+        Some(_) => section_relocations(elf)?,
+        None => segment_relocations(elf)?,
+    };
+    let Some((relocations, dynsyms, dynstrs)) = found else {
+        return Ok(());
+    };
+    let pointer = PointerWriter {
+        size: match elf.ehdr.class {
+            Class::ELF64 => 8,
+            Class::ELF32 => 4,
+        },
+        little_endian: elf.ehdr.endianness.is_little(),
+    };
+    let stub_base = img
+        .get_range()
+        .map(|r| (r.end as u64).next_multiple_of(0x1000))
+        .unwrap_or(0);
+    let mut imports: Vec<String> = vec![];
+
+    for reloc in relocations {
+        let Some(kind) = classify_relocation(elf.ehdr.e_machine, reloc.r_type) else {
+            continue;
+        };
+        let location = reloc.offset + bias;
+        let symbol = match reloc.sym {
+            0 => 0,
+            idx => {
+                let sym = dynsyms.get(idx as usize)?;
+                if sym.is_undefined() {
+                    let name = dynstrs.get(sym.st_name as usize)?;
+                    let slot = match imports.iter().position(|i| i == name) {
+                        Some(slot) => slot,
+                        None => {
+                            imports.push(name.to_string());
+                            imports.len() - 1
+                        }
+                    };
+                    stub_base + (slot * pointer.size) as u64
+                } else {
+                    symbol_address(&sym, bias)
+                }
+            }
+        };
+        let value = match (kind, reloc.addend) {
+            (RelocationKind::Absolute, Some(addend)) => symbol.wrapping_add_signed(addend),
+            (RelocationKind::Absolute, None) => {
+                symbol.wrapping_add(pointer.read(img, location).unwrap_or(0))
+            }
+            (RelocationKind::Symbol, addend) => symbol.wrapping_add_signed(addend.unwrap_or(0)),
+            (RelocationKind::Relative, Some(addend)) => bias.wrapping_add_signed(addend),
+            (RelocationKind::Relative, None) => {
+                bias.wrapping_add(pointer.read(img, location).unwrap_or(0))
+            }
+        };
+        pointer.write(img, location, value);
+    }
+
+    if !imports.is_empty() {
+        img.sections.push(ImageSection {
+            data: vec![0; imports.len() * pointer.size].into(),
+            base_address: stub_base as usize,
+            perms: Perms {
+                read: true,
+                write: false,
+                exec: true,
+            },
+            module: String::new(),
+        });
+        for (slot, name) in imports.into_iter().enumerate() {
+            img.add_symbol(ImageSymbol {
+                name,
+                address: stub_base + (slot * pointer.size) as u64,
+                size: pointer.size as u64,
+                kind: SymbolKind::Import,
+                binding: SymbolBinding::Global,
             })
         }
-        Ok(img)
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ffi::image::bridge::Image;
+    use crate::context::{Image, SymbolKind};
     use elf::endian::AnyEndian;
+    use elf::endian::LittleEndian;
     use elf::ElfBytes;
 
     #[test]
@@ -50,6 +469,163 @@ mod tests {
         let file_data = std::fs::read(path).unwrap();
         let slice = file_data.as_slice();
         let file = ElfBytes::<AnyEndian>::minimal_parse(slice).unwrap();
-        let _img = Image::try_from(file).unwrap();
+        let img = Image::try_from(file).unwrap();
+        let main = img.get_symbol("main").unwrap();
+        assert_eq!(main.kind, SymbolKind::Function);
+        let (nearest, offset) = img.get_nearest_symbol(main.address + 1).unwrap();
+        assert_eq!(nearest.name, "main");
+        assert_eq!(offset, 1);
+    }
+
+    /// A position-independent x86-64 ELF with a single writable segment, importing `puts`
+    /// through a jump slot and holding one relative pointer
+    fn make_elf() -> Vec<u8> {
+        let mut elf = vec![0u8; 0x300];
+        let put = |elf: &mut Vec<u8>, offset: usize, bytes: &[u8]| {
+            elf[offset..offset + bytes.len()].copy_from_slice(bytes)
+        };
+        put(&mut elf, 0, &[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+        // type, machine, version, header offsets and sizes
+        put(&mut elf, 0x10, &3u16.to_le_bytes());
+        put(&mut elf, 0x12, &62u16.to_le_bytes());
+        put(&mut elf, 0x14, &1u32.to_le_bytes());
+        put(&mut elf, 0x20, &0x40u64.to_le_bytes());
+        put(&mut elf, 0x28, &0x200u64.to_le_bytes());
+        put(&mut elf, 0x34, &64u16.to_le_bytes());
+        put(&mut elf, 0x36, &56u16.to_le_bytes());
+        put(&mut elf, 0x38, &1u16.to_le_bytes());
+        put(&mut elf, 0x3a, &64u16.to_le_bytes());
+        put(&mut elf, 0x3c, &4u16.to_le_bytes());
+        // PT_LOAD covering the whole file
+        put(&mut elf, 0x40, &1u32.to_le_bytes());
+        put(&mut elf, 0x44, &6u32.to_le_bytes());
+        put(&mut elf, 0x60, &0x200u64.to_le_bytes());
+        put(&mut elf, 0x68, &0x200u64.to_le_bytes());
+        // .dynsym: the null symbol, then an undefined global function
+        put(&mut elf, 0x118, &1u32.to_le_bytes());
+        put(&mut elf, 0x11c, &[0x12]);
+        // .dynstr
+        put(&mut elf, 0x140, b"\0puts\0");
+        // .rela.dyn: R_X86_64_JUMP_SLOT puts, then R_X86_64_RELATIVE 0x40
+        put(&mut elf, 0x150, &0x180u64.to_le_bytes());
+        put(&mut elf, 0x158, &((1u64 << 32) | 7).to_le_bytes());
+        put(&mut elf, 0x168, &0x188u64.to_le_bytes());
+        put(&mut elf, 0x170, &8u64.to_le_bytes());
+        put(&mut elf, 0x178, &0x40u64.to_le_bytes());
+        // section headers
+        let mut section = |idx: usize, kind: u32, offset: u64, size: u64, link: u32, ent: u64| {
+            let hdr = 0x200 + idx * 64;
+            put(&mut elf, hdr + 4, &kind.to_le_bytes());
+            put(&mut elf, hdr + 8, &2u64.to_le_bytes());
+            put(&mut elf, hdr + 0x10, &offset.to_le_bytes());
+            put(&mut elf, hdr + 0x18, &offset.to_le_bytes());
+            put(&mut elf, hdr + 0x20, &size.to_le_bytes());
+            put(&mut elf, hdr + 0x28, &link.to_le_bytes());
+            put(&mut elf, hdr + 0x38, &ent.to_le_bytes());
+        };
+        section(1, 11, 0x100, 48, 2, 24);
+        section(2, 3, 0x140, 6, 0, 0);
+        section(3, 4, 0x150, 48, 1, 24);
+        elf
+    }
+
+    /// [make_elf], with its section headers stripped and its relocations described by a
+    /// `PT_DYNAMIC` segment instead
+    fn make_sectionless_elf() -> Vec<u8> {
+        let mut elf = make_elf();
+        let put = |elf: &mut Vec<u8>, offset: usize, bytes: &[u8]| {
+            elf[offset..offset + bytes.len()].copy_from_slice(bytes)
+        };
+        elf[0x200..].fill(0);
+        put(&mut elf, 0x28, &0u64.to_le_bytes());
+        put(&mut elf, 0x38, &2u16.to_le_bytes());
+        put(&mut elf, 0x3c, &0u16.to_le_bytes());
+        put(&mut elf, 0x60, &0x300u64.to_le_bytes());
+        put(&mut elf, 0x68, &0x300u64.to_le_bytes());
+        // PT_DYNAMIC
+        put(&mut elf, 0x78, &2u32.to_le_bytes());
+        put(&mut elf, 0x7c, &6u32.to_le_bytes());
+        put(&mut elf, 0x80, &0x200u64.to_le_bytes());
+        put(&mut elf, 0x88, &0x200u64.to_le_bytes());
+        put(&mut elf, 0x98, &0x90u64.to_le_bytes());
+        put(&mut elf, 0xa0, &0x90u64.to_le_bytes());
+        // the jump slot is found through DT_JMPREL, the relative pointer through DT_RELA
+        let entries: [(u64, u64); 8] = [
+            (6, 0x100),
+            (5, 0x140),
+            (10, 6),
+            (23, 0x150),
+            (2, 24),
+            (20, 7),
+            (7, 0x168),
+            (8, 24),
+        ];
+        for (i, (tag, val)) in entries.into_iter().enumerate() {
+            put(&mut elf, 0x200 + i * 16, &tag.to_le_bytes());
+            put(&mut elf, 0x208 + i * 16, &val.to_le_bytes());
+        }
+        elf
+    }
+
+    #[test]
+    fn test_elf_absolute_symbol() {
+        let mut elf = make_elf();
+        elf.resize(0x380, 0);
+        let put = |elf: &mut Vec<u8>, offset: usize, bytes: &[u8]| {
+            elf[offset..offset + bytes.len()].copy_from_slice(bytes)
+        };
+        put(&mut elf, 0x3c, &6u16.to_le_bytes());
+        // .symtab: the null symbol, an absolute object and a function in the segment
+        put(&mut elf, 0x1a8, &1u32.to_le_bytes());
+        put(&mut elf, 0x1ac, &[0x11, 0]);
+        put(&mut elf, 0x1ae, &0xfff1u16.to_le_bytes());
+        put(&mut elf, 0x1b0, &0x2au64.to_le_bytes());
+        put(&mut elf, 0x1c0, &8u32.to_le_bytes());
+        put(&mut elf, 0x1c4, &[0x12, 0]);
+        put(&mut elf, 0x1c6, &1u16.to_le_bytes());
+        put(&mut elf, 0x1c8, &0x40u64.to_le_bytes());
+        // .strtab
+        put(&mut elf, 0x1d8, b"\0answer\0main\0");
+        for (idx, kind, offset, size, link) in
+            [(4, 2u32, 0x190u64, 72u64, 5u32), (5, 3, 0x1d8, 13, 0)]
+        {
+            let hdr = 0x200 + idx * 64;
+            put(&mut elf, hdr + 4, &kind.to_le_bytes());
+            put(&mut elf, hdr + 0x18, &offset.to_le_bytes());
+            put(&mut elf, hdr + 0x20, &size.to_le_bytes());
+            put(&mut elf, hdr + 0x28, &link.to_le_bytes());
+            put(
+                &mut elf,
+                hdr + 0x38,
+                &(if kind == 2 { 24u64 } else { 0 }).to_le_bytes(),
+            );
+        }
+        let file = ElfBytes::<LittleEndian>::minimal_parse(&elf).unwrap();
+        let img = Image::from_elf(&file, 0x10000).unwrap();
+        assert_eq!(img.get_symbol("main").unwrap().address, 0x10040);
+        assert_eq!(img.get_symbol("answer").unwrap().address, 0x2a);
+    }
+
+    #[test]
+    fn test_elf_relocations() {
+        for bytes in [make_elf(), make_sectionless_elf()] {
+            check_relocations(&bytes);
+        }
+    }
+
+    fn check_relocations(bytes: &[u8]) {
+        let file = ElfBytes::<LittleEndian>::minimal_parse(bytes).unwrap();
+        let img = Image::from_elf(&file, 0x10000).unwrap();
+        let puts = img.get_symbol("puts").unwrap();
+        assert_eq!(puts.kind, SymbolKind::Import);
+        assert_eq!(puts.address, 0x11000);
+        assert!(img.contains_address(0x11000));
+        let read = |addr: usize| {
+            let bytes: Vec<u8> = (addr..addr + 8).map(|a| img.get_byte(a).unwrap()).collect();
+            u64::from_le_bytes(bytes.try_into().unwrap())
+        };
+        assert_eq!(read(0x10180), 0x11000);
+        assert_eq!(read(0x10188), 0x10040);
+        assert_eq!(img.get_nearest_symbol(0x11000).unwrap().0.name, "puts");
     }
 }
@@ -1,29 +1,201 @@
 use crate::context::builder::image::Perms;
-use crate::context::{Image, ImageSection};
+use crate::context::{Image, ImageSection, ImageSymbol, SymbolBinding, SymbolKind};
 use crate::JingleSleighError;
 use crate::JingleSleighError::ImageLoadError;
 use object::elf::{PF_R, PF_W, PF_X};
 use object::macho::{VM_PROT_EXECUTE, VM_PROT_READ, VM_PROT_WRITE};
-use object::{Architecture, Endianness, File, Object, ObjectSegment, SegmentFlags};
+use object::pe::{
+    IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM, IMAGE_FILE_MACHINE_ARM64,
+    IMAGE_FILE_MACHINE_ARM64EC, IMAGE_FILE_MACHINE_ARMNT, IMAGE_FILE_MACHINE_I386,
+    IMAGE_FILE_MACHINE_POWERPC, IMAGE_FILE_MACHINE_R4000, IMAGE_FILE_MACHINE_THUMB,
+    IMAGE_SCN_CNT_UNINITIALIZED_DATA, IMAGE_SCN_LNK_INFO, IMAGE_SCN_LNK_REMOVE,
+    IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_READ, IMAGE_SCN_MEM_WRITE,
+};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::{
+    Architecture, Endianness, File, LittleEndian as LE, Object, ObjectSection, ObjectSegment,
+    ObjectSymbol, ReadRef, SectionFlags, SegmentFlags,
+};
+use std::cmp::min;
 
 impl<'d> TryFrom<File<'d>> for Image {
     type Error = JingleSleighError;
     fn try_from(value: File) -> Result<Self, Self::Error> {
-        let mut img: Image = Image { sections: vec![] };
-        for x in value.segments() {
-            let base_address = x.address();
-            let data = x.data().map_err(|_| ImageLoadError)?.to_vec();
-            let perms = map_flags(&x.flags());
-            img.sections.push(ImageSection {
-                perms,
-                data,
-                base_address: base_address as usize,
-            })
-        }
+        let mut img = match &value {
+            File::Pe32(pe) => load_pe(pe)?,
+            File::Pe64(pe) => load_pe(pe)?,
+            File::Coff(_) | File::CoffBig(_) => load_coff(&value)?,
+            _ => load_segments(&value)?,
+        };
+        read_symbols(&value, &mut img)?;
         Ok(img)
     }
 }
 
+fn load_segments(file: &File) -> Result<Image, JingleSleighError> {
+    let mut img: Image = Image {
+        sections: vec![],
+        symbols: Default::default(),
+    };
+    for x in file.segments() {
+        let base_address = x.address();
+        let data = x.data().map_err(|_| ImageLoadError)?.to_vec();
+        let perms = map_flags(&x.flags());
+        img.sections.push(ImageSection {
+            perms,
+            data: data.into(),
+            base_address: base_address as usize,
+            module: String::new(),
+        })
+    }
+    Ok(img)
+}
+
+/// Add the defined, named symbols of the file to the image. PE images rarely carry a symbol
+/// table, so their exports are included as well.
+fn read_symbols(file: &File, img: &mut Image) -> Result<(), JingleSleighError> {
+    for sym in file.symbols().chain(file.dynamic_symbols()) {
+        if sym.is_undefined() {
+            continue;
+        }
+        let kind = match sym.kind() {
+            object::SymbolKind::Text | object::SymbolKind::Label => SymbolKind::Function,
+            object::SymbolKind::Data => SymbolKind::Data,
+            object::SymbolKind::Section => SymbolKind::Section,
+            object::SymbolKind::File => SymbolKind::File,
+            object::SymbolKind::Tls => SymbolKind::Tls,
+            _ => SymbolKind::Unknown,
+        };
+        let binding = if sym.is_weak() {
+            SymbolBinding::Weak
+        } else if sym.is_global() {
+            SymbolBinding::Global
+        } else {
+            SymbolBinding::Local
+        };
+        img.add_symbol(ImageSymbol {
+            name: sym.name().map_err(|_| ImageLoadError)?.to_string(),
+            address: sym.address(),
+            size: sym.size(),
+            kind,
+            binding,
+        });
+    }
+    if matches!(file, File::Pe32(_) | File::Pe64(_)) {
+        for export in file.exports().map_err(|_| ImageLoadError)? {
+            img.add_symbol(ImageSymbol {
+                name: String::from_utf8_lossy(export.name()).to_string(),
+                address: export.address(),
+                size: 0,
+                kind: SymbolKind::Unknown,
+                binding: SymbolBinding::Global,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// PE images are loaded section by section at `image base + virtual address`, as the Windows
+/// loader would. Sections are sized by their virtual size, with any space not backed by the
+/// file zero-filled. The headers are mapped read-only at the image base.
+fn load_pe<'d, Pe: ImageNtHeaders, R: ReadRef<'d>>(
+    pe: &PeFile<'d, Pe, R>,
+) -> Result<Image, JingleSleighError> {
+    let optional_header = pe.nt_headers().optional_header();
+    let image_base = optional_header.image_base();
+    let image_size = optional_header.size_of_image() as u64;
+    let mut img: Image = Image {
+        sections: vec![],
+        symbols: Default::default(),
+    };
+
+    let header_size = optional_header.size_of_headers() as u64;
+    let headers = pe
+        .data()
+        .read_bytes_at(0, header_size)
+        .map_err(|_| ImageLoadError)?;
+    img.sections.push(ImageSection {
+        perms: Perms {
+            read: true,
+            write: false,
+            exec: false,
+        },
+        data: headers.to_vec().into(),
+        base_address: image_base as usize,
+        module: String::new(),
+    });
+
+    for hdr in pe.section_table().iter() {
+        let characteristics = hdr.characteristics.get(LE);
+        let raw_size = if characteristics & IMAGE_SCN_CNT_UNINITIALIZED_DATA != 0 {
+            0
+        } else {
+            hdr.size_of_raw_data.get(LE)
+        };
+        // some linkers leave the virtual size empty and expect the raw size to be used
+        let virtual_size = match hdr.virtual_size.get(LE) {
+            0 => raw_size,
+            size => size,
+        };
+        // the zero-filled tail is allocated up front, so don't take the header's word for its
+        // size: every section must fit in the image the loader would map
+        let virtual_address = hdr.virtual_address.get(LE) as u64;
+        if virtual_address + virtual_size as u64 > image_size {
+            return Err(ImageLoadError);
+        }
+        let file_size = min(raw_size, virtual_size);
+        let file_data = pe
+            .data()
+            .read_bytes_at(hdr.pointer_to_raw_data.get(LE) as u64, file_size as u64)
+            .map_err(|_| ImageLoadError)?;
+        let mut data = vec![0; virtual_size as usize];
+        data[0..file_data.len()].copy_from_slice(file_data);
+        img.sections.push(ImageSection {
+            perms: map_coff_characteristics(characteristics),
+            data: data.into(),
+            base_address: (image_base + virtual_address) as usize,
+            module: String::new(),
+        })
+    }
+    Ok(img)
+}
+
+/// COFF object files have no segments, so their sections are loaded at the addresses they
+/// declare, skipping sections that only carry information for the linker
+fn load_coff(file: &File) -> Result<Image, JingleSleighError> {
+    let mut img: Image = Image {
+        sections: vec![],
+        symbols: Default::default(),
+    };
+    for section in file.sections() {
+        let SectionFlags::Coff { characteristics } = section.flags() else {
+            continue;
+        };
+        if characteristics & (IMAGE_SCN_LNK_INFO | IMAGE_SCN_LNK_REMOVE) != 0 {
+            continue;
+        }
+        let file_data = section.data().map_err(|_| ImageLoadError)?;
+        let mut data = vec![0; section.size() as usize];
+        let len = min(data.len(), file_data.len());
+        data[0..len].copy_from_slice(&file_data[0..len]);
+        img.sections.push(ImageSection {
+            perms: map_coff_characteristics(characteristics),
+            data: data.into(),
+            base_address: section.address() as usize,
+            module: String::new(),
+        })
+    }
+    Ok(img)
+}
+
+fn map_coff_characteristics(characteristics: u32) -> Perms {
+    Perms {
+        exec: (characteristics & IMAGE_SCN_MEM_EXECUTE) == IMAGE_SCN_MEM_EXECUTE,
+        write: (characteristics & IMAGE_SCN_MEM_WRITE) == IMAGE_SCN_MEM_WRITE,
+        read: (characteristics & IMAGE_SCN_MEM_READ) == IMAGE_SCN_MEM_READ,
+    }
+}
+
 fn map_flags(flags: &SegmentFlags) -> Perms {
     match flags {
         SegmentFlags::Elf { p_flags } => Perms {
@@ -36,6 +208,7 @@ fn map_flags(flags: &SegmentFlags) -> Perms {
             write: (flags & VM_PROT_WRITE) == VM_PROT_WRITE,
             read: (flags & VM_PROT_READ) == VM_PROT_READ,
         },
+        SegmentFlags::Coff { characteristics } => map_coff_characteristics(*characteristics),
         _ => Perms {
             read: false,
             write: false,
@@ -43,7 +216,28 @@ fn map_flags(flags: &SegmentFlags) -> Perms {
         },
     }
 }
+
+/// `object` only reports the PE machine types it knows how to relocate for, and folds the
+/// Thumb-2 only `ARMNT` into plain ARM, so PE machine types are mapped directly.
+fn map_pe_machine(machine: u16) -> Option<&'static str> {
+    match machine {
+        IMAGE_FILE_MACHINE_I386 => Some("x86:LE:32:default"),
+        IMAGE_FILE_MACHINE_AMD64 => Some("x86:LE:64:default"),
+        IMAGE_FILE_MACHINE_ARM => Some("ARM:LE:32:v8"),
+        IMAGE_FILE_MACHINE_THUMB | IMAGE_FILE_MACHINE_ARMNT => Some("ARM:LE:32:v8T"),
+        IMAGE_FILE_MACHINE_ARM64 | IMAGE_FILE_MACHINE_ARM64EC => Some("AARCH64:LE:64:v8A"),
+        IMAGE_FILE_MACHINE_R4000 => Some("MIPS:LE:32:default"),
+        IMAGE_FILE_MACHINE_POWERPC => Some("PowerPC:LE:32:default"),
+        _ => None,
+    }
+}
+
 pub fn map_gimli_architecture(file: &File) -> Option<&'static str> {
+    match file {
+        File::Pe32(pe) => return map_pe_machine(pe.nt_headers().file_header.machine.get(LE)),
+        File::Pe64(pe) => return map_pe_machine(pe.nt_headers().file_header.machine.get(LE)),
+        _ => {}
+    }
     match &file.architecture() {
         Architecture::Unknown => None,
         Architecture::Aarch64 => match file.endianness() {
@@ -68,3 +262,95 @@ pub fn map_gimli_architecture(file: &File) -> Option<&'static str> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::builder::image::gimli::map_gimli_architecture;
+    use crate::context::Image;
+    use crate::JingleSleighError;
+    use object::File;
+
+    /// A minimal x86-64 PE with a code section and an uninitialized data section
+    fn make_pe() -> Vec<u8> {
+        make_pe_with_bss_size(0x80)
+    }
+
+    fn make_pe_with_bss_size(bss_size: u32) -> Vec<u8> {
+        let mut pe = vec![0u8; 0x400];
+        let put = |pe: &mut Vec<u8>, offset: usize, bytes: &[u8]| {
+            pe[offset..offset + bytes.len()].copy_from_slice(bytes)
+        };
+        put(&mut pe, 0, b"MZ");
+        put(&mut pe, 0x3c, &0x40u32.to_le_bytes());
+        put(&mut pe, 0x40, b"PE\0\0");
+        // file header: machine, section count, optional header size
+        put(&mut pe, 0x44, &0x8664u16.to_le_bytes());
+        put(&mut pe, 0x46, &2u16.to_le_bytes());
+        put(&mut pe, 0x54, &240u16.to_le_bytes());
+        // optional header: magic, image base, alignments, image and header sizes, directories
+        let opt = 0x58;
+        put(&mut pe, opt, &0x20bu16.to_le_bytes());
+        put(&mut pe, opt + 24, &0x140000000u64.to_le_bytes());
+        put(&mut pe, opt + 32, &0x1000u32.to_le_bytes());
+        put(&mut pe, opt + 36, &0x200u32.to_le_bytes());
+        put(&mut pe, opt + 56, &0x3000u32.to_le_bytes());
+        put(&mut pe, opt + 60, &0x200u32.to_le_bytes());
+        put(&mut pe, opt + 108, &16u32.to_le_bytes());
+        // section headers
+        let mut section = |idx: usize, name: &[u8], vsize: u32, va: u32, raw: u32, chars: u32| {
+            let hdr = opt + 240 + idx * 40;
+            put(&mut pe, hdr, name);
+            put(&mut pe, hdr + 8, &vsize.to_le_bytes());
+            put(&mut pe, hdr + 12, &va.to_le_bytes());
+            put(&mut pe, hdr + 16, &raw.to_le_bytes());
+            put(
+                &mut pe,
+                hdr + 20,
+                &(if raw == 0 { 0u32 } else { 0x200 }).to_le_bytes(),
+            );
+            put(&mut pe, hdr + 36, &chars.to_le_bytes());
+        };
+        section(0, b".text", 0x10, 0x1000, 0x200, 0x6000_0020);
+        section(1, b".bss", bss_size, 0x2000, 0, 0xc000_0080);
+        // mov eax, 0x7; ret
+        put(&mut pe, 0x200, &[0xb8, 0x07, 0x00, 0x00, 0x00, 0xc3]);
+        pe
+    }
+
+    #[test]
+    fn test_pe() {
+        let bytes = make_pe();
+        let file = File::parse(bytes.as_slice()).unwrap();
+        assert_eq!(map_gimli_architecture(&file), Some("x86:LE:64:default"));
+        let img = Image::try_from(file).unwrap();
+        let text = img
+            .sections()
+            .iter()
+            .find(|s| s.base_address == 0x140001000)
+            .unwrap();
+        assert_eq!(text.data.len(), 0x10);
+        assert_eq!(text.data[5], 0xc3);
+        assert!(text.perms.exec && text.perms.read && !text.perms.write);
+        let bss = img
+            .sections()
+            .iter()
+            .find(|s| s.base_address == 0x140002000)
+            .unwrap();
+        assert_eq!(bss.data, vec![0; 0x80]);
+        assert!(!bss.perms.exec && bss.perms.read && bss.perms.write);
+        assert_eq!(img.get_byte(0x140000000), Some(b'M'));
+    }
+
+    #[test]
+    fn test_pe_oversized_section() {
+        // fills the rest of the image exactly
+        let bytes = make_pe_with_bss_size(0x1000);
+        assert!(Image::try_from(File::parse(bytes.as_slice()).unwrap()).is_ok());
+        let bytes = make_pe_with_bss_size(0xffff_0000);
+        let file = File::parse(bytes.as_slice()).unwrap();
+        assert!(matches!(
+            Image::try_from(file),
+            Err(JingleSleighError::ImageLoadError)
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::context::builder::image::Image;
+use crate::context::builder::image::Perms;
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::MalformedRecord;
+
+const DATA: u8 = 0x00;
+const END_OF_FILE: u8 = 0x01;
+const EXTENDED_SEGMENT_ADDRESS: u8 = 0x02;
+const START_SEGMENT_ADDRESS: u8 = 0x03;
+const EXTENDED_LINEAR_ADDRESS: u8 = 0x04;
+const START_LINEAR_ADDRESS: u8 = 0x05;
+
+impl Image {
+    /// Load an Intel HEX file. Each run of contiguous data records becomes its own section;
+    /// since the format carries no permissions, every section is readable, writable and
+    /// executable.
+    pub fn from_ihex(text: &str) -> Result<Self, JingleSleighError> {
+        let mut chunks = vec![];
+        let mut base: usize = 0;
+        for (idx, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+            if line.is_empty() {
+                continue;
+            }
+            let bytes = line
+                .strip_prefix(':')
+                .and_then(decode_hex)
+                .ok_or(MalformedRecord(idx))?;
+            if bytes.len() < 5
+                || bytes.len() != bytes[0] as usize + 5
+                || bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0
+            {
+                return Err(MalformedRecord(idx));
+            }
+            let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+            let data = &bytes[4..bytes.len() - 1];
+            match bytes[3] {
+                DATA => chunks.push((base + offset, data.to_vec())),
+                END_OF_FILE => break,
+                EXTENDED_SEGMENT_ADDRESS if data.len() == 2 => {
+                    base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 4
+                }
+                EXTENDED_LINEAR_ADDRESS if data.len() == 2 => {
+                    base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 16
+                }
+                START_SEGMENT_ADDRESS | START_LINEAR_ADDRESS => {}
+                _ => return Err(MalformedRecord(idx)),
+            }
+        }
+        Image::from_chunks(chunks, Perms::RWX)
+    }
+}
+
+/// Decode a string of hex digit pairs
+pub(crate) fn decode_hex(digits: &str) -> Option<Vec<u8>> {
+    if digits.len() % 2 != 0 || !digits.is_ascii() {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Image;
+
+    #[test]
+    fn test_ihex() {
+        let text = "\
+:0400000001020304F2
+:02000004000AF0
+:020010000506E3
+:020012000708DD
+:00000001FF
+";
+        let img = Image::from_ihex(text).unwrap();
+        assert_eq!(img.sections().len(), 2);
+        assert_eq!(img.get_byte(0x2), Some(0x03));
+        assert_eq!(img.get_byte(0xa0010), Some(0x05));
+        assert_eq!(img.get_byte(0xa0013), Some(0x08));
+        assert!(Image::from_ihex(":0400000001020304F3").is_err());
+    }
+}
@@ -1,10 +1,75 @@
+pub mod data;
 #[cfg(feature = "elf")]
 pub mod elf;
 #[cfg(feature = "gimli")]
 pub mod gimli;
+pub mod ihex;
+pub mod raw;
+pub mod srec;
+mod symbols;
 
-pub use crate::ffi::image::bridge::{Image, ImageSection, Perms};
+use crate::context::builder::image::data::SectionData;
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::OverlappingSections;
 use std::ops::Range;
+use symbols::SymbolTable;
+
+#[derive(Debug, Clone)]
+pub struct Perms {
+    pub(crate) read: bool,
+    pub(crate) write: bool,
+    pub(crate) exec: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageSection {
+    pub(crate) data: SectionData,
+    pub(crate) base_address: usize,
+    pub(crate) perms: Perms,
+    /// The name of the module (e.g. `libc.so.6`) this section was loaded from, when the
+    /// image is composed of several modules; empty otherwise
+    pub(crate) module: String,
+}
+
+/// What a [ImageSymbol] refers to
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SymbolKind {
+    Unknown,
+    Function,
+    Data,
+    Section,
+    File,
+    Tls,
+    /// A location synthesized by the loader to stand in for a symbol imported from
+    /// another module
+    Import,
+}
+
+/// The linkage visibility of an [ImageSymbol]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SymbolBinding {
+    Local,
+    Global,
+    Weak,
+}
+
+/// A named location in an [Image], as recorded in the symbol table of the file it
+/// was loaded from
+#[derive(Debug, Clone)]
+pub struct ImageSymbol {
+    pub name: String,
+    pub address: u64,
+    /// The size of the object the symbol refers to; zero if unknown
+    pub size: u64,
+    pub kind: SymbolKind,
+    pub binding: SymbolBinding,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub sections: Vec<ImageSection>,
+    pub(crate) symbols: SymbolTable,
+}
 
 impl Image {
     pub fn get_range(&self) -> Option<Range<usize>> {
@@ -22,9 +87,228 @@ impl Image {
     }
 
     pub fn contains_address(&self, addr: usize) -> bool {
+        self.sections.iter().any(|s| s.range().contains(&addr))
+    }
+
+    /// Get the section covering the given address, if any
+    pub fn get_section(&self, addr: usize) -> Option<&ImageSection> {
+        self.sections.iter().find(|s| s.range().contains(&addr))
+    }
+
+    /// Read a single byte out of the image, if some section covers the given address
+    pub fn get_byte(&self, addr: usize) -> Option<u8> {
+        self.sections.iter().find_map(|s| {
+            addr.checked_sub(s.base_address)
+                .and_then(|idx| s.data.get(idx).copied())
+        })
+    }
+
+    /// The symbols of this image, in the order they were added
+    pub fn symbols(&self) -> &[ImageSymbol] {
+        self.symbols.as_slice()
+    }
+
+    /// Add a symbol to this image. Symbols without a name, or with the same name and address
+    /// as one already present, are ignored.
+    pub fn add_symbol(&mut self, symbol: ImageSymbol) {
+        self.symbols.insert(symbol)
+    }
+
+    /// Get the first symbol added with the given name
+    pub fn get_symbol(&self, name: &str) -> Option<&ImageSymbol> {
+        self.symbols.get(name)
+    }
+
+    /// Given a symbol name, get the address it refers to, if it exists
+    pub fn get_symbol_address(&self, name: &str) -> Option<u64> {
+        self.get_symbol(name).map(|s| s.address)
+    }
+
+    /// Given an address, get the closest symbol at or before it, along with the offset of the
+    /// address from that symbol. Symbols with a known size only label the addresses they cover.
+    pub fn get_nearest_symbol(&self, address: u64) -> Option<(&ImageSymbol, u64)> {
+        self.symbols.nearest(address)
+    }
+
+    /// Move the image so that its lowest section starts at `base`, shifting its symbols along
+    /// with it.
+    ///
+    /// This does not re-apply relocations; position-independent ELFs should instead be loaded
+    /// at the desired base with [Image::from_elf].
+    pub fn rebase(&mut self, base: usize) {
+        let Some(range) = self.get_range() else {
+            return;
+        };
+        let delta = base.wrapping_sub(range.start);
+        for section in self.sections.iter_mut() {
+            section.base_address = section.base_address.wrapping_add(delta);
+        }
+        self.symbols.rebase(delta as u64);
+    }
+
+    /// Add the sections and symbols of another image to this one, recording `name` as the
+    /// module its sections came from. If `base` is given, the module is first rebased to it.
+    ///
+    /// Fails without modifying this image if any section of the module would overlap a
+    /// section already present.
+    pub fn add_module(
+        &mut self,
+        name: &str,
+        mut module: Image,
+        base: Option<usize>,
+    ) -> Result<(), JingleSleighError> {
+        if let Some(base) = base {
+            module.rebase(base);
+        }
+        let overlaps = module
+            .sections
+            .iter()
+            .any(|new| self.sections.iter().any(|old| old.overlaps(new)));
+        if overlaps {
+            return Err(OverlappingSections);
+        }
+        for mut section in module.sections {
+            section.module = name.to_string();
+            self.sections.push(section);
+        }
+        for symbol in module.symbols.into_vec() {
+            self.add_symbol(symbol);
+        }
+        Ok(())
+    }
+
+    /// Build a single image out of several named modules, each placed at the given base
+    /// address (or left where it is, if none is given)
+    pub fn compose<'a, I: IntoIterator<Item = (&'a str, Image, Option<usize>)>>(
+        modules: I,
+    ) -> Result<Image, JingleSleighError> {
+        let mut img = Image {
+            sections: vec![],
+            symbols: Default::default(),
+        };
+        for (name, module, base) in modules {
+            img.add_module(name, module, base)?;
+        }
+        Ok(img)
+    }
+
+    /// Find a pair of sections of this image that cover some of the same addresses, if any
+    pub fn find_overlap(&self) -> Option<(&ImageSection, &ImageSection)> {
+        self.sections.iter().enumerate().find_map(|(i, a)| {
+            self.sections[i + 1..]
+                .iter()
+                .find(|b| a.overlaps(b))
+                .map(|b| (a, b))
+        })
+    }
+
+    /// The names of the modules this image is composed of, in the order they were added
+    pub fn modules(&self) -> Vec<&str> {
+        let mut modules: Vec<&str> = vec![];
+        for section in &self.sections {
+            if !section.module.is_empty() && !modules.contains(&section.module.as_str()) {
+                modules.push(&section.module)
+            }
+        }
+        modules
+    }
+
+    /// The name of the module whose sections cover the given address, if any
+    pub fn get_module(&self, addr: usize) -> Option<&str> {
         self.sections
             .iter()
-            .any(|s| s.base_address <= addr && (s.base_address + s.data.len()) >= addr)
+            .find(|s| s.range().contains(&addr) && !s.module.is_empty())
+            .map(|s| s.module.as_str())
+    }
+}
+
+impl Image {
+    /// Build an image out of chunks of data given in any order, merging chunks that
+    /// are adjacent in memory into a single section
+    pub(crate) fn from_chunks(
+        mut chunks: Vec<(usize, Vec<u8>)>,
+        perms: Perms,
+    ) -> Result<Self, JingleSleighError> {
+        chunks.sort_by_key(|(addr, _)| *addr);
+        let mut sections: Vec<ImageSection> = vec![];
+        for (addr, data) in chunks.into_iter().filter(|(_, data)| !data.is_empty()) {
+            match sections.last_mut() {
+                Some(last) if last.range().end == addr => last.data.to_mut().extend(data),
+                Some(last) if last.range().end > addr => return Err(OverlappingSections),
+                _ => sections.push(ImageSection {
+                    data: data.into(),
+                    base_address: addr,
+                    perms: perms.clone(),
+                    module: String::new(),
+                }),
+            }
+        }
+        Ok(Image {
+            sections,
+            symbols: Default::default(),
+        })
+    }
+}
+
+impl Perms {
+    pub const R: Perms = Perms::new(true, false, false);
+    pub const RW: Perms = Perms::new(true, true, false);
+    pub const RX: Perms = Perms::new(true, false, true);
+    pub const RWX: Perms = Perms::new(true, true, true);
+
+    pub const fn new(read: bool, write: bool, exec: bool) -> Self {
+        Self { read, write, exec }
+    }
+
+    pub fn read(&self) -> bool {
+        self.read
+    }
+
+    pub fn write(&self) -> bool {
+        self.write
+    }
+
+    pub fn exec(&self) -> bool {
+        self.exec
+    }
+}
+
+impl ImageSection {
+    pub fn new<D: Into<SectionData>>(base_address: usize, data: D, perms: Perms) -> Self {
+        Self {
+            data: data.into(),
+            base_address,
+            perms,
+            module: String::new(),
+        }
+    }
+
+    pub fn base_address(&self) -> usize {
+        self.base_address
+    }
+
+    pub fn data(&self) -> &SectionData {
+        &self.data
+    }
+
+    pub fn perms(&self) -> &Perms {
+        &self.perms
+    }
+
+    /// The range of addresses covered by this section
+    pub fn range(&self) -> Range<usize> {
+        self.base_address..self.base_address + self.data.len()
+    }
+
+    /// The name of the module this section was loaded from; empty if the image it belongs
+    /// to was not composed from several modules
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    fn overlaps(&self, other: &ImageSection) -> bool {
+        let (a, b) = (self.range(), other.range());
+        !a.is_empty() && !b.is_empty() && a.start < b.end && b.start < a.end
     }
 }
 
@@ -32,14 +316,16 @@ impl From<&[u8]> for Image {
     fn from(value: &[u8]) -> Self {
         Self {
             sections: vec![ImageSection {
-                data: value.to_vec(),
+                data: value.to_vec().into(),
                 perms: Perms {
                     read: true,
                     write: true,
                     exec: true,
                 },
                 base_address: 0,
+                module: String::new(),
             }],
+            symbols: Default::default(),
         }
     }
 }
@@ -48,14 +334,82 @@ impl From<Vec<u8>> for Image {
     fn from(value: Vec<u8>) -> Self {
         Self {
             sections: vec![ImageSection {
-                data: value,
+                data: value.into(),
                 perms: Perms {
                     read: true,
                     write: true,
                     exec: true,
                 },
                 base_address: 0,
+                module: String::new(),
             }],
+            symbols: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{Image, ImageSymbol, SymbolBinding, SymbolKind};
+
+    fn symbol(name: &str, address: u64, kind: SymbolKind) -> ImageSymbol {
+        ImageSymbol {
+            name: name.to_string(),
+            address,
+            size: 0,
+            kind,
+            binding: SymbolBinding::Global,
         }
     }
+
+    #[test]
+    fn test_symbol_lookup() {
+        let mut img = Image::from(vec![0u8; 0x100]);
+        img.add_symbol(symbol(".text", 0, SymbolKind::Section));
+        img.add_symbol(symbol("foo", 0x10, SymbolKind::Function));
+        img.add_symbol(symbol("bar", 0x40, SymbolKind::Function));
+        assert_eq!(img.get_symbol_address("bar"), Some(0x40));
+        assert_eq!(img.get_symbol_address("baz"), None);
+        let (sym, offset) = img.get_nearest_symbol(0x24).unwrap();
+        assert_eq!((sym.name.as_str(), offset), ("foo", 0x14));
+        let (sym, offset) = img.get_nearest_symbol(0x40).unwrap();
+        assert_eq!((sym.name.as_str(), offset), ("bar", 0));
+        // section symbols are not used as labels
+        assert!(img.get_nearest_symbol(0x8).is_none());
+        // nor are sized symbols past their end
+        img.add_symbol(ImageSymbol {
+            size: 0x10,
+            ..symbol("baz", 0x80, SymbolKind::Data)
+        });
+        assert_eq!(img.get_nearest_symbol(0x8f).unwrap().1, 0xf);
+        assert!(img.get_nearest_symbol(0x90).is_none());
+        // duplicates are dropped
+        img.add_symbol(symbol("foo", 0x10, SymbolKind::Function));
+        assert_eq!(img.symbols().len(), 4);
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut lib = Image::from(vec![0u8; 0x100]);
+        lib.add_symbol(symbol("puts", 0x20, SymbolKind::Function));
+        let img = Image::compose([
+            ("main", Image::from(vec![0u8; 0x100]), Some(0x1000)),
+            ("libc", lib.clone(), Some(0x2000)),
+        ])
+        .unwrap();
+        assert_eq!(img.modules(), vec!["main", "libc"]);
+        assert_eq!(img.get_module(0x1080), Some("main"));
+        assert_eq!(img.get_module(0x2000), Some("libc"));
+        assert_eq!(img.get_module(0x3000), None);
+        assert_eq!(img.get_symbol_address("puts"), Some(0x2020));
+        assert!(img.find_overlap().is_none());
+
+        let mut overlapping = img.clone();
+        assert!(overlapping
+            .add_module("ld", lib.clone(), Some(0x10ff))
+            .is_err());
+        assert_eq!(overlapping.sections().len(), 2);
+        overlapping.add_module("ld", lib, Some(0x2100)).unwrap();
+        assert_eq!(overlapping.get_module(0x2100), Some("ld"));
+    }
 }
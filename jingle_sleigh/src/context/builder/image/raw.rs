@@ -0,0 +1,170 @@
+#[cfg(feature = "mmap")]
+use crate::context::builder::image::data::MappedFile;
+use crate::context::builder::image::data::SectionData;
+use crate::context::builder::image::{Image, ImageSection, Perms};
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::{ImageLoadError, OverlappingSections};
+use std::ops::Range;
+
+/// Describes where a region of a raw memory dump lives in the target's address space
+#[derive(Debug, Clone)]
+pub struct MemoryRegion {
+    /// The offset of the region's contents in the dump
+    pub file_offset: usize,
+    /// The address the region is mapped at
+    pub base_address: usize,
+    /// The size of the region in memory. Any part of it beyond the end of the dump is
+    /// zero-filled, as is done for `.bss`
+    pub size: usize,
+    pub perms: Perms,
+}
+
+impl Image {
+    /// Load a raw memory dump (e.g. firmware read out of a flash chip), mapping each of the
+    /// given regions of the dump to its own section.
+    pub fn from_memory_map(
+        data: &[u8],
+        regions: &[MemoryRegion],
+    ) -> Result<Self, JingleSleighError> {
+        load_regions(data, regions, |_| None)
+    }
+
+    /// Load a raw memory dump out of a [MappedFile], as with [Image::from_memory_map]. Regions
+    /// lying entirely within the file are borrowed from the mapping rather than copied.
+    #[cfg(feature = "mmap")]
+    pub fn from_mapped_memory_map(
+        file: &MappedFile,
+        regions: &[MemoryRegion],
+    ) -> Result<Self, JingleSleighError> {
+        load_regions(file.bytes(), regions, |range| file.slice(range))
+    }
+}
+
+/// Load the regions of a dump, using `borrow` to avoid copying the contents of a region
+/// where possible
+fn load_regions<F: Fn(Range<usize>) -> Option<SectionData>>(
+    data: &[u8],
+    regions: &[MemoryRegion],
+    borrow: F,
+) -> Result<Image, JingleSleighError> {
+    let mut img = Image {
+        sections: vec![],
+        symbols: Default::default(),
+    };
+    for region in regions {
+        let end = region
+            .file_offset
+            .checked_add(region.size)
+            .ok_or(ImageLoadError)?;
+        if region.file_offset > data.len() {
+            return Err(ImageLoadError);
+        }
+        let contents = match borrow(region.file_offset..end) {
+            Some(contents) => contents,
+            None => {
+                let mut contents = vec![0; region.size];
+                let available = &data[region.file_offset..];
+                let len = region.size.min(available.len());
+                contents[..len].copy_from_slice(&available[..len]);
+                contents.into()
+            }
+        };
+        img.sections.push(ImageSection {
+            data: contents,
+            base_address: region.base_address,
+            perms: region.perms.clone(),
+            module: String::new(),
+        });
+    }
+    match img.find_overlap() {
+        Some(_) => Err(OverlappingSections),
+        None => Ok(img),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "mmap")]
+    use crate::context::MappedFile;
+    use crate::context::{Image, MemoryRegion, Perms};
+    #[cfg(feature = "mmap")]
+    use crate::tests::TempFile;
+    #[cfg(feature = "mmap")]
+    use crate::JingleSleighError::ImageFileRead;
+
+    #[test]
+    fn test_memory_map() {
+        let dump: Vec<u8> = (0..0x20).collect();
+        let flash = MemoryRegion {
+            file_offset: 0,
+            base_address: 0x0800_0000,
+            size: 0x10,
+            perms: Perms::RX,
+        };
+        let sram = MemoryRegion {
+            file_offset: 0x10,
+            base_address: 0x2000_0000,
+            size: 0x40,
+            perms: Perms::RW,
+        };
+        let img = Image::from_memory_map(&dump, &[flash.clone(), sram.clone()]).unwrap();
+        assert_eq!(img.get_byte(0x0800_000f), Some(0x0f));
+        assert_eq!(img.get_byte(0x2000_0000), Some(0x10));
+        assert_eq!(img.get_byte(0x2000_003f), Some(0));
+        assert!(img.sections()[0].perms.exec);
+        assert!(!img.sections()[1].perms.exec);
+
+        let alias = MemoryRegion {
+            base_address: 0x0800_0008,
+            ..flash.clone()
+        };
+        assert!(Image::from_memory_map(&dump, &[flash, alias]).is_err());
+
+        let wrapping = MemoryRegion {
+            file_offset: 0x10,
+            size: usize::MAX,
+            ..sram
+        };
+        assert!(Image::from_memory_map(&dump, &[wrapping]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_mapped_memory_map() {
+        let dump = TempFile::new(".bin", (0..0x20).collect::<Vec<u8>>());
+        let file = MappedFile::open(dump.path()).unwrap();
+        let flash = MemoryRegion {
+            file_offset: 0,
+            base_address: 0x0800_0000,
+            size: 0x10,
+            perms: Perms::RX,
+        };
+        let sram = MemoryRegion {
+            file_offset: 0x10,
+            base_address: 0x2000_0000,
+            size: 0x40,
+            perms: Perms::RW,
+        };
+        let mut img = Image::from_mapped_memory_map(&file, &[flash, sram]).unwrap();
+        // the region running past the end of the file has to be copied to be zero-filled
+        assert!(img.sections()[0].data.is_mapped());
+        assert!(!img.sections()[1].data.is_mapped());
+        assert_eq!(img.get_byte(0x0800_0003), Some(0x03));
+
+        let copy = img.clone();
+        img.sections[0].data.to_mut()[0] = 0xff;
+        assert!(!img.sections()[0].data.is_mapped());
+        assert_eq!(img.get_byte(0x0800_0000), Some(0xff));
+        assert_eq!(copy.get_byte(0x0800_0000), Some(0x00));
+
+        let path = dump.path().to_path_buf();
+        drop(dump);
+        match MappedFile::open(&path) {
+            Err(ImageFileRead { path: p, source }) => {
+                assert_eq!(p, path);
+                assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+            }
+            r => panic!("unexpected result {:?}", r.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::context::builder::image::ihex::decode_hex;
+use crate::context::builder::image::Image;
+use crate::context::builder::image::Perms;
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::MalformedRecord;
+
+impl Image {
+    /// Load a Motorola S-record file. Each run of contiguous `S1`/`S2`/`S3` data records becomes
+    /// its own section; since the format carries no permissions, every section is readable,
+    /// writable and executable.
+    pub fn from_srec(text: &str) -> Result<Self, JingleSleighError> {
+        let mut chunks = vec![];
+        for (idx, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+            if line.is_empty() {
+                continue;
+            }
+            let (kind, bytes) = line
+                .strip_prefix('S')
+                .filter(|rest| rest.len() > 1 && rest.is_char_boundary(1))
+                .map(|rest| rest.split_at(1))
+                .and_then(|(kind, rest)| Some((kind, decode_hex(rest)?)))
+                .ok_or(MalformedRecord(idx))?;
+            if bytes.is_empty()
+                || bytes.len() != bytes[0] as usize + 1
+                || bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0xff
+            {
+                return Err(MalformedRecord(idx));
+            }
+            let address_size = match kind {
+                "1" => 2,
+                "2" => 3,
+                "3" => 4,
+                // headers, record counts and start addresses carry no image data
+                "0" | "5" | "6" | "7" | "8" | "9" => continue,
+                _ => return Err(MalformedRecord(idx)),
+            };
+            let body = &bytes[1..bytes.len() - 1];
+            if body.len() < address_size {
+                return Err(MalformedRecord(idx));
+            }
+            let (address, data) = body.split_at(address_size);
+            let address = address
+                .iter()
+                .fold(0usize, |addr, b| (addr << 8) | *b as usize);
+            chunks.push((address, data.to_vec()));
+        }
+        Image::from_chunks(chunks, Perms::RWX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Image;
+
+    #[test]
+    fn test_srec() {
+        let text = "\
+S00600004844521B
+S107000001020304EE
+S2060100000506ED
+S307000100020708E6
+S9030000FC
+";
+        let img = Image::from_srec(text).unwrap();
+        // the S3 record picks up where the S2 record leaves off
+        assert_eq!(img.sections().len(), 2);
+        assert_eq!(img.get_byte(0x3), Some(0x04));
+        assert_eq!(img.get_byte(0x10000), Some(0x05));
+        assert_eq!(img.get_byte(0x10002), Some(0x07));
+        assert_eq!(img.get_byte(0x10003), Some(0x08));
+        assert!(Image::from_srec("S107000001020304EF").is_err());
+    }
+}
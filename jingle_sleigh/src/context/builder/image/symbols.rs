@@ -0,0 +1,81 @@
+use crate::context::builder::image::{ImageSymbol, SymbolKind};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// The symbols of an [Image](crate::context::Image), in the order they were added, indexed
+/// for lookup by name and by address.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SymbolTable {
+    symbols: Vec<ImageSymbol>,
+    /// The name and address of every symbol, so that a symbol appearing in several of a file's
+    /// tables is only recorded once
+    seen: HashSet<(String, u64)>,
+    /// The first symbol added with a given name
+    by_name: HashMap<String, usize>,
+    /// The symbols usable as labels, sorted by address. Built on the first address lookup and
+    /// discarded whenever the table changes.
+    by_address: OnceLock<Vec<usize>>,
+}
+
+impl SymbolTable {
+    /// Add a symbol, ignoring it if it has no name or a symbol with the same name and address
+    /// is already present
+    pub(crate) fn insert(&mut self, symbol: ImageSymbol) {
+        if symbol.name.is_empty() || !self.seen.insert((symbol.name.clone(), symbol.address)) {
+            return;
+        }
+        self.by_name
+            .entry(symbol.name.clone())
+            .or_insert(self.symbols.len());
+        self.symbols.push(symbol);
+        self.by_address = OnceLock::new();
+    }
+
+    pub(crate) fn as_slice(&self) -> &[ImageSymbol] {
+        &self.symbols
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<ImageSymbol> {
+        self.symbols
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&ImageSymbol> {
+        self.by_name.get(name).map(|i| &self.symbols[*i])
+    }
+
+    /// The closest label at or before `address`, along with the offset of the address from it.
+    /// A label with a known size only covers the addresses within it.
+    pub(crate) fn nearest(&self, address: u64) -> Option<(&ImageSymbol, u64)> {
+        let labels = self.by_address.get_or_init(|| {
+            // section and file symbols describe containers rather than locations, so they
+            // only make for confusing labels
+            let mut labels: Vec<usize> = (0..self.symbols.len())
+                .filter(|i| {
+                    let kind = self.symbols[*i].kind;
+                    kind != SymbolKind::Section && kind != SymbolKind::File
+                })
+                .collect();
+            // stable, so the last symbol added wins among several at the same address
+            labels.sort_by_key(|i| self.symbols[*i].address);
+            labels
+        });
+        let idx = labels
+            .partition_point(|i| self.symbols[*i].address <= address)
+            .checked_sub(1)?;
+        let symbol = &self.symbols[labels[idx]];
+        let offset = address - symbol.address;
+        if symbol.size != 0 && offset >= symbol.size {
+            return None;
+        }
+        Some((symbol, offset))
+    }
+
+    /// Shift every symbol by `delta`, wrapping around the address space
+    pub(crate) fn rebase(&mut self, delta: u64) {
+        let symbols = std::mem::take(self).into_vec();
+        for mut symbol in symbols {
+            symbol.address = symbol.address.wrapping_add(delta);
+            self.insert(symbol);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::context::builder::image::Image;
 use crate::context::builder::language_def::{parse_ldef, LanguageDefinition};
 use crate::context::builder::processor_spec::parse_pspec;
-use crate::context::SleighContext;
+use crate::context::{SleighContext, SleighLanguage};
 use crate::error::JingleSleighError;
 use crate::error::JingleSleighError::{InvalidLanguageId, LanguageSpecRead, NoImageProvided};
 use std::fmt::Debug;
@@ -30,18 +30,30 @@ impl SleighContextBuilder {
     #[instrument(skip_all, fields(%id))]
     pub fn build(mut self, id: &str) -> Result<SleighContext, JingleSleighError> {
         let image = self.image.take().ok_or(NoImageProvided)?;
-        let (lang, path) = self.get_language(id).ok_or(InvalidLanguageId)?;
-        let sla_path = path.join(&lang.sla_file);
-        let mut context = SleighContext::new(&sla_path, image)?;
+        let context = self.build_language(id)?.build_context(image)?;
         event!(Level::INFO, "Created sleigh context");
+        Ok(context)
+    }
+
+    /// Load a language, from which any number of contexts can then be built without loading it
+    /// again. No image needs to have been set.
+    #[instrument(skip_all, fields(%id))]
+    pub fn build_language(&self, id: &str) -> Result<SleighLanguage, JingleSleighError> {
+        let (lang, path) = self.get_language(id).ok_or(InvalidLanguageId)?;
         let pspec_path = path.join(&lang.processor_spec);
         let pspec = parse_pspec(&pspec_path)?;
-        for set in pspec.context_data.context_set.sets {
-            context.set_initial_context(&set.name, set.value as u32)
-        }
-
-        Ok(context)
+        let context_defaults = pspec
+            .context_data
+            .context_set
+            .sets
+            .into_iter()
+            .map(|set| (set.name, set.value as u32))
+            .collect();
+        let language = SleighLanguage::new(id, &path.join(&lang.sla_file), context_defaults)?;
+        event!(Level::INFO, "Loaded sleigh language");
+        Ok(language)
     }
+
     pub fn load_folder<T: AsRef<Path>>(path: T) -> Result<Self, JingleSleighError> {
         let ldef = SleighContextBuilder::_load_folder(path.as_ref())?;
         Ok(SleighContextBuilder {
@@ -0,0 +1,147 @@
+use crate::instruction::Instruction;
+use std::collections::{HashMap, VecDeque};
+
+/// Counters describing how well a [SleighContext](crate::context::SleighContext)'s decode
+/// cache is doing
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct DecodeCacheStats {
+    /// Decodes answered from the cache
+    pub hits: u64,
+    /// Decodes that had to go to `SLEIGH`, including all decodes made while the cache is
+    /// disabled
+    pub misses: u64,
+    /// Entries dropped to make room for newer ones
+    pub evictions: u64,
+    /// The number of instructions currently cached
+    pub entries: usize,
+    /// The most instructions the cache will hold; zero when caching is disabled
+    pub capacity: usize,
+}
+
+/// An instruction decodes differently depending on the context variables in effect at its
+/// address, so those are part of the key alongside the address
+type CacheKey = (u64, Vec<u32>);
+
+/// A bounded map from decode key to [Instruction]. Once full, the oldest entry is evicted;
+/// lookups do not refresh an entry, so this is first-in first-out rather than LRU.
+#[derive(Debug, Default)]
+pub(crate) struct DecodeCache {
+    entries: HashMap<CacheKey, Instruction>,
+    order: VecDeque<CacheKey>,
+    stats: DecodeCacheStats,
+}
+
+impl DecodeCache {
+    pub(crate) fn capacity(&self) -> usize {
+        self.stats.capacity
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.stats.capacity = capacity;
+        while self.order.len() > capacity {
+            self.evict();
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: &CacheKey) -> Option<Instruction> {
+        let instr = self.entries.get(key).cloned();
+        match instr {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        instr
+    }
+
+    /// Record a decode that bypassed the cache
+    pub(crate) fn miss(&mut self) {
+        self.stats.misses += 1;
+    }
+
+    pub(crate) fn insert(&mut self, key: CacheKey, instr: Instruction) {
+        if self.stats.capacity == 0 || self.entries.contains_key(&key) {
+            return;
+        }
+        if self.order.len() == self.stats.capacity {
+            self.evict();
+        }
+        self.order.push_back(key.clone());
+        self.entries.insert(key, instr);
+    }
+
+    /// Drop every cached instruction, keeping the statistics
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    pub(crate) fn stats(&self) -> DecodeCacheStats {
+        DecodeCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    pub(crate) fn reset_stats(&mut self) {
+        self.stats = DecodeCacheStats {
+            capacity: self.stats.capacity,
+            ..Default::default()
+        };
+    }
+
+    fn evict(&mut self) {
+        if let Some(key) = self.order.pop_front() {
+            self.entries.remove(&key);
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::cache::{DecodeCache, DecodeCacheStats};
+    use crate::instruction::Instruction;
+    use crate::Disassembly;
+
+    fn instr(address: u64) -> Instruction {
+        Instruction {
+            disassembly: Disassembly {
+                mnemonic: "NOP".to_string(),
+                args: "".to_string(),
+                operands: vec![],
+            },
+            delay_slots: vec![],
+            ops: vec![],
+            length: 1,
+            address,
+        }
+    }
+
+    #[test]
+    fn test_decode_cache() {
+        let mut cache = DecodeCache::default();
+        cache.insert((0, vec![]), instr(0));
+        assert!(cache.get(&(0, vec![])).is_none());
+        cache.set_capacity(2);
+        cache.insert((0, vec![0]), instr(0));
+        cache.insert((1, vec![0]), instr(1));
+        assert_eq!(cache.get(&(0, vec![0])).unwrap().address, 0);
+        // same address, different context
+        assert!(cache.get(&(0, vec![1])).is_none());
+        cache.insert((2, vec![0]), instr(2));
+        assert!(cache.get(&(0, vec![0])).is_none());
+        assert_eq!(cache.get(&(2, vec![0])).unwrap().address, 2);
+        assert_eq!(
+            cache.stats(),
+            DecodeCacheStats {
+                hits: 2,
+                misses: 3,
+                evictions: 1,
+                entries: 2,
+                capacity: 2,
+            }
+        );
+        cache.clear();
+        assert!(cache.get(&(2, vec![0])).is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
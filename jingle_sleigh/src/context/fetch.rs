@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+/// How a [SleighContext](crate::context::SleighContext) treats instruction bytes that no
+/// section of its image covers
+#[derive(Clone, Default)]
+pub enum FetchPolicy {
+    /// Read unmapped bytes as zero. This is lenient, but decoding near the edge of a section or
+    /// in a gap between sections will produce instructions that aren't really there.
+    #[default]
+    ZeroFill,
+    /// Fail to decode any instruction with a byte outside of the image, or within a section
+    /// that is not executable
+    Error,
+    /// Ask the callback for the value of each unmapped byte, e.g. to lazily page in memory
+    /// from a debugger. Bytes the callback returns `None` for, or panics on, are treated as
+    /// under [FetchPolicy::Error], as are fetches from non-executable sections. The callback is
+    /// asked about each address at most once until the image or policy is next set.
+    Callback(Arc<dyn Fn(u64) -> Option<u8> + Send + Sync>),
+}
+
+impl Debug for FetchPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchPolicy::ZeroFill => write!(f, "ZeroFill"),
+            FetchPolicy::Error => write!(f, "Error"),
+            FetchPolicy::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+/// Resolves unmapped bytes under a [FetchPolicy], remembering what a callback returned for
+/// each address. SLEIGH's load image and the checks made before decoding both read through
+/// clones of the same resolver, so the callback is only run once per byte and both always see
+/// the same value.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FetchResolver {
+    policy: FetchPolicy,
+    resolved: Arc<Mutex<HashMap<u64, Option<u8>>>>,
+}
+
+impl FetchResolver {
+    pub(crate) fn new(policy: FetchPolicy) -> Self {
+        Self {
+            policy,
+            resolved: Default::default(),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> &FetchPolicy {
+        &self.policy
+    }
+
+    /// The value the policy supplies for an unmapped byte, if any. A panicking callback
+    /// supplies nothing: the load image calls this from inside SLEIGH, and unwinding out
+    /// through C++ would abort the process.
+    pub(crate) fn resolve(&self, addr: u64) -> Option<u8> {
+        let FetchPolicy::Callback(callback) = &self.policy else {
+            return None;
+        };
+        let mut resolved = self.resolved.lock().unwrap_or_else(|e| e.into_inner());
+        *resolved
+            .entry(addr)
+            .or_insert_with(|| catch_unwind(AssertUnwindSafe(|| callback(addr))).unwrap_or(None))
+    }
+}
@@ -0,0 +1,108 @@
+use crate::context::{Image, SleighContext};
+use crate::error::JingleSleighError::{LanguageSpecRead, SleighInit};
+use crate::error::{JingleSleighError, SleighErrorClass};
+use crate::ffi::context_ffi::bridge::{makeLanguage, LanguageFFI};
+use cxx::SharedPtr;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A compiled `SLEIGH` language, loaded once and shared by every [SleighContext] built from it.
+///
+/// Loading a language builds its decoding tables (constructors, symbols, registers and spaces)
+/// from the `.sla` file. Contexts built from the language share those tables and only add their
+/// own context variables and decode caches, so building a context is cheap. Cloning a language
+/// is cheap too: clones share the same tables, and a language can be shared between threads.
+#[derive(Clone)]
+pub struct SleighLanguage {
+    pub(crate) ffi: SharedPtr<LanguageFFI>,
+    id: String,
+    sla_path: PathBuf,
+    context_defaults: Arc<[(String, u32)]>,
+}
+
+impl Debug for SleighLanguage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SleighLanguage {{id: {}, sla_path: {:?}}}",
+            self.id, self.sla_path
+        )
+    }
+}
+
+impl SleighLanguage {
+    /// Load the `.sla` file at the given path as the language with the given id. Contexts built
+    /// from the language start with the given context variable defaults, usually those from the
+    /// language's processor spec.
+    pub(crate) fn new(
+        id: &str,
+        sla_path: &Path,
+        context_defaults: Vec<(String, u32)>,
+    ) -> Result<Self, JingleSleighError> {
+        let abs = sla_path.canonicalize().map_err(|_| LanguageSpecRead)?;
+        let path_str = abs.to_str().ok_or(LanguageSpecRead)?;
+        let ffi = makeLanguage(path_str).map_err(|e| {
+            let (class, message) = SleighErrorClass::from_what(e.what());
+            SleighInit {
+                path: abs.clone(),
+                class,
+                message,
+            }
+        })?;
+        Ok(Self {
+            ffi,
+            id: id.to_string(),
+            sla_path: abs,
+            context_defaults: context_defaults.into(),
+        })
+    }
+
+    /// The id of this language in its `.ldefs` file, e.g. `x86:LE:64:default`
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The path of the `.sla` file this language was loaded from
+    pub fn sla_path(&self) -> &Path {
+        &self.sla_path
+    }
+
+    /// Build a context decoding instructions from the given image
+    pub fn build_context(&self, image: Image) -> Result<SleighContext, JingleSleighError> {
+        let mut context = SleighContext::new(self, image)?;
+        // nothing has been decoded yet, so setting these does not rebuild SLEIGH's caches
+        for (name, value) in self.context_defaults.iter() {
+            context.set_context_default(name, *value)?;
+        }
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::SleighLanguage;
+    use crate::error::SleighErrorClass;
+    use crate::tests::TempFile;
+    use crate::JingleSleighError::SleighInit;
+
+    #[test]
+    fn load_error() {
+        let file = TempFile::new(".sla", "this is not a sla file");
+        let abs = file.path().canonicalize().unwrap();
+        let err = SleighLanguage::new("test", file.path(), vec![]).unwrap_err();
+        match err {
+            SleighInit { path, class, .. } => {
+                assert_eq!(path, abs);
+                assert_eq!(class, SleighErrorClass::Decoder);
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn languages_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SleighLanguage>();
+    }
+}
@@ -1,26 +1,54 @@
 mod builder;
+mod cache;
+pub(crate) mod fetch;
+mod language;
+pub(crate) mod registers;
 
 use crate::error::JingleSleighError;
-use crate::error::JingleSleighError::{LanguageSpecRead, SleighInitError};
+use crate::error::JingleSleighError::{
+    ImageLoadError, InstructionDecode, InvalidContextVariable, NonExecutableFetch, SleighInit,
+    UnmappedFetch,
+};
+use crate::error::SleighErrorClass;
 use crate::ffi::addrspace::bridge::AddrSpaceHandle;
-use crate::ffi::context_ffi::bridge::ContextFFI;
+use crate::ffi::context_ffi::bridge::{makeContext, ContextFFI};
 use crate::instruction::Instruction;
-use crate::space::{RegisterManager, SpaceInfo, SpaceManager};
+use crate::space::{RegisterManager, SpaceInfo, SpaceManager, SymbolManager, UserOpManager};
+#[cfg(feature = "mmap")]
+pub use builder::image::data::MappedFile;
+pub use builder::image::data::SectionData;
 #[cfg(feature = "gimli")]
 pub use builder::image::gimli::map_gimli_architecture;
-pub use builder::image::{Image, ImageSection};
+pub use builder::image::raw::MemoryRegion;
+pub use builder::image::{Image, ImageSection, ImageSymbol, Perms, SymbolBinding, SymbolKind};
 pub use builder::SleighContextBuilder;
+pub use cache::DecodeCacheStats;
+pub use fetch::FetchPolicy;
+pub use language::SleighLanguage;
 
-use crate::ffi::context_ffi::CTX_BUILD_MUTEX;
-use crate::ffi::instruction::bridge::VarnodeInfoFFI;
+use crate::context::cache::DecodeCache;
+use crate::context::fetch::FetchResolver;
+use crate::context::registers::RegisterTable;
+use crate::ffi::image::ImageLoader;
 use crate::VarNode;
 use cxx::{SharedPtr, UniquePtr};
+use std::cell::RefCell;
 use std::fmt::{Debug, Formatter};
-use std::path::Path;
+use std::ops::Range;
 
+/// A loaded `SLEIGH` language along with the image it decodes instructions from.
+///
+/// Contexts can be moved between threads but not shared, since decoding updates `SLEIGH`'s
+/// internal caches. To decode in parallel, build one context per thread; contexts can be built
+/// concurrently, e.g. from one shared [SleighLanguage].
 pub struct SleighContext {
     ctx: UniquePtr<ContextFFI>,
+    language: SleighLanguage,
     spaces: Vec<SpaceInfo>,
+    userops: Vec<String>,
+    registers: RegisterTable,
+    fetch: FetchResolver,
+    cache: RefCell<DecodeCache>,
     pub image: Image,
 }
 
@@ -46,58 +74,306 @@ impl SpaceManager for SleighContext {
             .getDefaultCodeSpace()
             .getIndex() as usize
     }
+
+    fn as_userop_manager(&self) -> Option<&dyn UserOpManager> {
+        Some(self)
+    }
 }
 
 impl RegisterManager for SleighContext {
     fn get_register(&self, name: &str) -> Option<VarNode> {
-        self.ctx.getRegister(name).map(|f| VarNode::from(f)).ok()
+        self.registers.get(name).cloned()
     }
 
     fn get_register_name(&self, location: VarNode) -> Option<&str> {
-        let space = self.ctx.getSpaceByIndex(location.space_index as i32);
-        self.ctx
-            .getRegisterName(VarnodeInfoFFI {
-                space,
-                offset: location.offset,
-                size: location.size,
-            })
-            .ok()
+        self.registers.name(&location)
     }
 
     fn get_registers(&self) -> Vec<(VarNode, String)> {
-        self.ctx
-            .getRegisters()
+        self.registers.all().to_vec()
+    }
+
+    fn get_register_space_idx(&self) -> Option<usize> {
+        self.registers.space()
+    }
+
+    fn get_parent_register(&self, name: &str) -> Option<&str> {
+        self.registers.parent(name)
+    }
+
+    fn get_child_registers(&self, name: &str) -> Vec<&str> {
+        self.registers.children(name)
+    }
+
+    fn get_covering_register(&self, location: &VarNode) -> Option<&str> {
+        self.registers.covering(location)
+    }
+
+    fn get_overlapping_registers(&self, location: &VarNode) -> Vec<&str> {
+        self.registers.overlapping(location)
+    }
+}
+
+impl UserOpManager for SleighContext {
+    fn get_userop_name(&self, index: u64) -> Option<&str> {
+        self.userops.get(index as usize).map(|s| s.as_str())
+    }
+
+    fn get_userop_index(&self, name: &str) -> Option<u64> {
+        self.userops
+            .iter()
+            .position(|s| s == name)
+            .map(|idx| idx as u64)
+    }
+
+    fn get_userops(&self) -> Vec<(u64, String)> {
+        self.userops
             .iter()
-            .map(|b| (VarNode::from(&b.varnode), b.name.clone()))
+            .enumerate()
+            .map(|(idx, name)| (idx as u64, name.clone()))
             .collect()
     }
 }
 
+impl SymbolManager for SleighContext {
+    fn get_symbol_address(&self, name: &str) -> Option<u64> {
+        self.image.get_symbol_address(name)
+    }
+
+    fn get_nearest_symbol(&self, address: u64) -> Option<(&ImageSymbol, u64)> {
+        self.image.get_nearest_symbol(address)
+    }
+}
+
 impl SleighContext {
-    pub(crate) fn new(path: &Path, image: Image) -> Result<Self, JingleSleighError> {
-        let abs = path.canonicalize().map_err(|_| LanguageSpecRead)?;
-        let path_str = abs.to_str().ok_or(LanguageSpecRead)?;
-        match CTX_BUILD_MUTEX.lock() {
-            Ok(make_context) => {
-                let ctx = make_context(path_str, image.clone()).map_err(|_| SleighInitError)?;
-                let mut spaces: Vec<SpaceInfo> = Vec::with_capacity(ctx.getNumSpaces() as usize);
-                for idx in 0..ctx.getNumSpaces() {
-                    spaces.push(SpaceInfo::from(ctx.getSpaceByIndex(idx)));
-                }
-                Ok(Self { image, ctx, spaces })
+    pub(crate) fn new(language: &SleighLanguage, image: Image) -> Result<Self, JingleSleighError> {
+        let loader = ImageLoader {
+            image: image.clone(),
+            fetch: FetchResolver::default(),
+        };
+        let ctx = makeContext(language.ffi.clone(), Box::new(loader)).map_err(|e| {
+            let (class, message) = SleighErrorClass::from_what(e.what());
+            SleighInit {
+                path: language.sla_path().to_path_buf(),
+                class,
+                message,
+            }
+        })?;
+        let mut spaces: Vec<SpaceInfo> = Vec::with_capacity(ctx.getNumSpaces() as usize);
+        for idx in 0..ctx.getNumSpaces() {
+            spaces.push(SpaceInfo::from(ctx.getSpaceByIndex(idx)));
+        }
+        let userops = ctx.getUserOpNames();
+        let registers = RegisterTable::new(
+            ctx.getRegisters()
+                .iter()
+                .map(|r| (VarNode::from(&r.varnode), r.name.clone()))
+                .collect(),
+        );
+        Ok(Self {
+            image,
+            ctx,
+            language: language.clone(),
+            spaces,
+            userops,
+            registers,
+            fetch: FetchResolver::default(),
+            cache: Default::default(),
+        })
+    }
+
+    /// The language this context decodes, which more contexts can be built from without
+    /// reading the `.sla` file again
+    pub fn language(&self) -> &SleighLanguage {
+        &self.language
+    }
+
+    /// Set the value a context variable (e.g. ARM's `TMode`) takes at any address it has not
+    /// been explicitly set for.
+    ///
+    /// Changing context is expensive once instructions have been decoded: `SLEIGH` has to rebuild
+    /// its caches before decoding again. The rebuild is deferred to the next decode, so make all
+    /// changes to context together rather than interleaving them with decoding.
+    pub fn set_context_default(&mut self, name: &str, value: u32) -> Result<(), JingleSleighError> {
+        self.ctx
+            .pin_mut()
+            .set_initial_context(name, value)
+            .map_err(|_| InvalidContextVariable)?;
+        self.cache.get_mut().clear();
+        Ok(())
+    }
+
+    pub fn get_context_default(&self, name: &str) -> Result<u32, JingleSleighError> {
+        self.ctx
+            .getContextDefault(name)
+            .map_err(|_| InvalidContextVariable)
+    }
+
+    /// Set the value of a context variable over a range of addresses in the code space, taking
+    /// precedence over its default value. Instructions decoded within the range will be
+    /// decoded using the given value.
+    ///
+    /// Like [SleighContext::set_context_default], this is expensive once instructions have been
+    /// decoded.
+    pub fn set_context_range(
+        &mut self,
+        name: &str,
+        range: Range<u64>,
+        value: u32,
+    ) -> Result<(), JingleSleighError> {
+        if range.is_empty() {
+            return Ok(());
+        }
+        self.ctx
+            .pin_mut()
+            .setContextRange(name, range.start, range.end, value)
+            .map_err(|_| InvalidContextVariable)?;
+        self.cache.get_mut().clear();
+        Ok(())
+    }
+
+    /// Get the value of a context variable in effect at the given address
+    pub fn get_context(&self, name: &str, offset: u64) -> Result<u32, JingleSleighError> {
+        self.ctx
+            .getContextValue(name, offset)
+            .map_err(|_| InvalidContextVariable)
+    }
+
+    /// Replace the image instructions are decoded from. Context variable values and the
+    /// compiled language are kept, so this is much cheaper than building a new context.
+    ///
+    /// Changes made directly to [SleighContext::image] are not seen by the decoder until they
+    /// are passed through this method.
+    pub fn set_image(&mut self, image: Image) -> Result<(), JingleSleighError> {
+        // callbacks are asked about unmapped bytes afresh for the new image
+        self.fetch = FetchResolver::new(self.fetch.policy().clone());
+        let loader = ImageLoader {
+            image: image.clone(),
+            fetch: self.fetch.clone(),
+        };
+        self.ctx
+            .pin_mut()
+            .setImage(Box::new(loader))
+            .map_err(|_| ImageLoadError)?;
+        self.image = image;
+        self.cache.get_mut().clear();
+        Ok(())
+    }
+
+    /// Set how instruction bytes outside of the image are treated. By default they read as
+    /// zero.
+    pub fn set_fetch_policy(&mut self, policy: FetchPolicy) -> Result<(), JingleSleighError> {
+        self.fetch = FetchResolver::new(policy);
+        self.set_image(self.image.clone())
+    }
+
+    pub fn fetch_policy(&self) -> &FetchPolicy {
+        self.fetch.policy()
+    }
+
+    /// Add another module (e.g. a shared library) to the image instructions are decoded from,
+    /// optionally rebasing it first. See [Image::add_module].
+    pub fn add_module(
+        &mut self,
+        name: &str,
+        module: Image,
+        base: Option<usize>,
+    ) -> Result<(), JingleSleighError> {
+        let mut image = self.image.clone();
+        image.add_module(name, module, base)?;
+        self.set_image(image)
+    }
+
+    /// Decode the instruction at `offset`, subject to the [FetchPolicy]. The first byte of the
+    /// instruction must be available regardless of the policy.
+    pub fn instruction_at(&self, offset: u64) -> Result<Instruction, JingleSleighError> {
+        self.check_fetch(offset, 1)?;
+        let key = if self.cache.borrow().capacity() > 0 {
+            let key = (offset, self.ctx.getContextWords(offset));
+            if let Some(instr) = self.cache.borrow_mut().get(&key) {
+                return Ok(instr);
             }
-            Err(_) => Err(SleighInitError),
+            Some(key)
+        } else {
+            self.cache.borrow_mut().miss();
+            None
+        };
+        let instr = self
+            .ctx
+            .get_one_instruction(offset)
+            .map(Instruction::from)
+            .map_err(|e| {
+                let (class, message) = SleighErrorClass::from_what(e.what());
+                InstructionDecode {
+                    address: offset,
+                    class,
+                    message,
+                }
+            })?;
+        self.check_fetch(offset, instr.length)?;
+        if let Some(key) = key {
+            self.cache.borrow_mut().insert(key, instr.clone());
         }
+        Ok(instr)
+    }
+
+    /// Keep up to `capacity` decoded instructions, so that decoding the same address again skips
+    /// `SLEIGH`'s instruction parser. Instructions are cached along with the context variables
+    /// they were decoded under. Decoding can change those variables (through `globalset`), so a
+    /// hit still has to read them from `SLEIGH`, which makes it cheaper than a decode but not
+    /// free.
+    ///
+    /// Once full, the cache evicts in insertion order: the oldest instruction goes first, however
+    /// recently it was used. The cache is emptied whenever the image, fetch policy or a context
+    /// variable is changed. A capacity of zero, the default, disables caching.
+    pub fn set_decode_cache_capacity(&mut self, capacity: usize) {
+        self.cache.get_mut().set_capacity(capacity)
+    }
+
+    pub fn decode_cache_stats(&self) -> DecodeCacheStats {
+        self.cache.borrow().stats()
     }
 
-    pub(crate) fn set_initial_context(&mut self, name: &str, value: u32) {
-        self.ctx.pin_mut().set_initial_context(name, value);
+    /// Zero the hit, miss and eviction counters of the decode cache, leaving its contents alone
+    pub fn reset_decode_cache_stats(&self) {
+        self.cache.borrow_mut().reset_stats()
     }
 
+    /// `SLEIGH` always reads a fixed-size window of bytes when decoding, so whether an
+    /// instruction strayed outside of the image can only be checked once its length is known
+    fn check_fetch(&self, offset: u64, len: usize) -> Result<(), JingleSleighError> {
+        for (i, addr) in (0..len as u64).map(|i| (i, offset.wrapping_add(i))) {
+            match (self.fetch.policy(), self.image.get_section(addr as usize)) {
+                (FetchPolicy::Error | FetchPolicy::Callback(_), Some(section))
+                    if !section.perms.exec =>
+                {
+                    return Err(NonExecutableFetch(addr))
+                }
+                (_, Some(_)) => {}
+                (FetchPolicy::Callback(_), None) if self.fetch.resolve(addr).is_some() => {}
+                (FetchPolicy::ZeroFill, None) if i > 0 => return Ok(()),
+                (_, None) => return Err(UnmappedFetch(addr)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Linearly decode up to `max_instrs` instructions starting at `offset`.
+    ///
+    /// Context that instructions commit to later addresses (through SLEIGH's `globalset`) is
+    /// recorded as they are decoded, and persists in this context for future reads.
     pub fn read(&self, offset: u64, max_instrs: usize) -> SleighContextInstructionIterator {
         SleighContextInstructionIterator::new(self, offset, max_instrs)
     }
 
+    /// Linearly decode up to `max_instrs` instructions starting at `offset`, as with
+    /// [SleighContext::read], but yield the error that stopped decoding instead of silently
+    /// ending. Why the iterator ended can be queried with
+    /// [TryInstructionIterator::stop_reason].
+    pub fn try_read(&self, offset: u64, max_instrs: usize) -> TryInstructionIterator<'_> {
+        TryInstructionIterator::new(self, offset, max_instrs)
+    }
+
     pub fn spaces(&self) -> Vec<SharedPtr<AddrSpaceHandle>> {
         let mut spaces = Vec::with_capacity(self.ctx.getNumSpaces() as usize);
         for i in 0..self.ctx.getNumSpaces() {
@@ -107,41 +383,94 @@ impl SleighContext {
     }
 }
 
-pub struct SleighContextInstructionIterator<'a> {
+/// Why a [TryInstructionIterator] stopped yielding instructions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StopReason {
+    /// The requested number of instructions was decoded
+    InstructionLimit,
+    /// The next instruction would have started at the given address, which is outside of
+    /// the image
+    LeftImage(u64),
+    /// Decoding failed at the given address; the error was the last item yielded
+    Error(u64),
+}
+
+/// Reads consecutive instructions out of a [SleighContext], decoding each one using the
+/// context variable values in effect at its address.
+///
+/// Yields an error at most once, as its final item.
+pub struct TryInstructionIterator<'a> {
     sleigh: &'a SleighContext,
     remaining: usize,
     offset: u64,
+    stop_reason: Option<StopReason>,
 }
 
-impl<'a> SleighContextInstructionIterator<'a> {
+impl<'a> TryInstructionIterator<'a> {
     pub(crate) fn new(sleigh: &'a SleighContext, offset: u64, remaining: usize) -> Self {
-        SleighContextInstructionIterator {
+        TryInstructionIterator {
             sleigh,
             remaining,
             offset,
+            stop_reason: None,
         }
     }
+
+    /// Why the iterator stopped, or `None` if it has more to yield
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+    }
 }
 
-impl<'a> Iterator for SleighContextInstructionIterator<'a> {
-    type Item = Instruction;
+impl<'a> Iterator for TryInstructionIterator<'a> {
+    type Item = Result<Instruction, JingleSleighError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.stop_reason.is_some() {
+            return None;
+        }
         if self.remaining == 0 {
+            self.stop_reason = Some(StopReason::InstructionLimit);
             return None;
         }
         if !self.sleigh.image.contains_address(self.offset as usize) {
+            self.stop_reason = Some(StopReason::LeftImage(self.offset));
             return None;
         }
-        let instr = self
-            .sleigh
-            .ctx
-            .get_one_instruction(self.offset)
-            .map(Instruction::from)
-            .ok()?;
-        self.offset += instr.length as u64;
-        self.remaining -= 1;
-        Some(instr)
+        match self.sleigh.instruction_at(self.offset) {
+            Ok(instr) => {
+                self.offset += instr.length as u64;
+                self.remaining -= 1;
+                Some(Ok(instr))
+            }
+            Err(e) => {
+                self.stop_reason = Some(StopReason::Error(self.offset));
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads consecutive instructions out of a [SleighContext], decoding each one using the
+/// context variable values in effect at its address. Stops at the first instruction that
+/// fails to decode; see [TryInstructionIterator] to find out why.
+pub struct SleighContextInstructionIterator<'a> {
+    inner: TryInstructionIterator<'a>,
+}
+
+impl<'a> SleighContextInstructionIterator<'a> {
+    pub(crate) fn new(sleigh: &'a SleighContext, offset: u64, remaining: usize) -> Self {
+        SleighContextInstructionIterator {
+            inner: TryInstructionIterator::new(sleigh, offset, remaining),
+        }
+    }
+}
+
+impl<'a> Iterator for SleighContextInstructionIterator<'a> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()?.ok()
     }
 }
 
@@ -149,8 +478,13 @@ impl<'a> Iterator for SleighContextInstructionIterator<'a> {
 mod test {
     use crate::context::builder::image::Image;
     use crate::context::builder::SleighContextBuilder;
+    use crate::context::SleighContext;
+    use crate::context::{FetchPolicy, ImageSection, Perms, StopReason};
+    use crate::error::SleighErrorClass;
     use crate::pcode::PcodeOperation;
-    use crate::SpaceManager;
+    use crate::JingleSleighError::{InstructionDecode, NonExecutableFetch, UnmappedFetch};
+    use crate::{OperandKind, RegisterManager, SpaceManager, UserOpManager, VarNode};
+    use std::sync::Arc;
 
     use crate::tests::SLEIGH_ARCH;
     use crate::varnode;
@@ -175,4 +509,333 @@ mod test {
         };
         assert!(matches!(&instr.ops[0], _op))
     }
+
+    #[test]
+    fn userops() {
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        let idx = ctx.get_userop_index("syscall").unwrap();
+        assert_eq!(ctx.get_userop_name(idx), Some("syscall"));
+        assert!(ctx
+            .get_userops()
+            .iter()
+            .any(|(i, name)| *i == idx && name == "syscall"));
+        assert_eq!(ctx.get_userop_index("not_a_userop"), None);
+    }
+
+    #[test]
+    fn registers() {
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        let eax = ctx.get_register("EAX").unwrap();
+        assert_eq!(ctx.get_register_name(eax.clone()), Some("EAX"));
+        assert_eq!(ctx.get_parent_register("EAX"), Some("RAX"));
+        assert_eq!(ctx.get_parent_register("AL"), Some("AX"));
+        assert_eq!(ctx.get_child_registers("AX"), vec!["AL", "AH"]);
+        let upper_half = VarNode {
+            offset: eax.offset + 2,
+            size: 2,
+            ..eax.clone()
+        };
+        assert_eq!(ctx.get_register_name(upper_half.clone()), None);
+        assert_eq!(ctx.get_covering_register(&upper_half), Some("EAX"));
+        let overlapping = ctx.get_overlapping_registers(&upper_half);
+        assert!(overlapping.contains(&"RAX"));
+        assert!(!overlapping.contains(&"AX"));
+    }
+
+    #[test]
+    fn context_range() {
+        // mov eax, 0x7
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0xb8, 0x07, 0x00, 0x00, 0x00];
+        let mut ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        assert_eq!(ctx.get_context_default("longMode").unwrap(), 1);
+        assert_eq!(ctx.read(5, 1).next().unwrap().length, 5);
+        // decode the second instruction as 16-bit code, where it becomes `mov ax, 0x7`
+        for name in ["longMode", "addrsize", "opsize"] {
+            ctx.set_context_range(name, 5..10, 0).unwrap();
+        }
+        assert_eq!(ctx.get_context("longMode", 0).unwrap(), 1);
+        assert_eq!(ctx.get_context("longMode", 5).unwrap(), 0);
+        let instrs: Vec<_> = ctx.read(0, 2).collect();
+        assert_eq!(instrs[0].length, 5);
+        assert_eq!(instrs[1].length, 3);
+        assert!(ctx.get_context("not_a_variable", 0).is_err());
+    }
+
+    #[test]
+    fn globalset_commits() {
+        // thumb: it eq; movs r0, #1
+        let bytes: Vec<u8> = vec![0x08, 0xbf, 0x01, 0x20];
+        let mut ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build("ARM:LE:32:v8T")
+            .unwrap();
+        ctx.set_context_default("TMode", 1).unwrap();
+        assert_eq!(ctx.get_context("condit", 2).unwrap(), 0);
+        let instrs: Vec<_> = ctx.read(0, 2).collect();
+        assert_eq!(instrs.len(), 2);
+        // the IT instruction commits its condition to the instruction following it
+        assert_ne!(ctx.get_context("condit", 2).unwrap(), 0);
+        let again = ctx.read(2, 1).next().unwrap();
+        assert_eq!(again.disassembly.mnemonic, instrs[1].disassembly.mnemonic);
+    }
+
+    #[test]
+    fn swap_image() {
+        // mov eax, 0x7
+        let mov: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00];
+        // ret
+        let ret: Vec<u8> = vec![0xc3];
+        let mut ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(mov.clone()))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        assert_eq!(ctx.read(0, 1).next().unwrap().disassembly.mnemonic, "MOV");
+        ctx.set_image(Image::from(ret.clone())).unwrap();
+        assert_eq!(ctx.read(0, 1).next().unwrap().disassembly.mnemonic, "RET");
+        ctx.add_module("lib", Image::from(mov), Some(0x1000))
+            .unwrap();
+        assert_eq!(ctx.image.get_module(0x1000), Some("lib"));
+        assert_eq!(
+            ctx.read(0x1000, 1).next().unwrap().disassembly.mnemonic,
+            "MOV"
+        );
+        assert!(ctx.add_module("lib", Image::from(ret), None).is_err());
+    }
+
+    #[test]
+    fn fetch_policy() {
+        // mov eax, 0x7, cut off after its first byte, followed by a non-executable ret
+        let mut ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image {
+                sections: vec![
+                    ImageSection::new(0, vec![0xb8], Perms::RX),
+                    ImageSection::new(0x10, vec![0xc3], Perms::RW),
+                ],
+                symbols: Default::default(),
+            })
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        assert_eq!(ctx.instruction_at(0).unwrap().length, 5);
+        assert!(ctx.instruction_at(0x10).is_ok());
+
+        ctx.set_fetch_policy(FetchPolicy::Error).unwrap();
+        assert!(matches!(ctx.instruction_at(0), Err(UnmappedFetch(1))));
+        assert!(matches!(
+            ctx.instruction_at(0x10),
+            Err(NonExecutableFetch(0x10))
+        ));
+        assert_eq!(ctx.read(0, 1).count(), 0);
+
+        let imm = [0x07, 0x00, 0x00, 0x00];
+        ctx.set_fetch_policy(FetchPolicy::Callback(Arc::new(move |addr| {
+            imm.get(addr as usize - 1).copied()
+        })))
+        .unwrap();
+        let instr = ctx.instruction_at(0).unwrap();
+        assert_eq!(instr.length, 5);
+        assert!(matches!(ctx.instruction_at(0x20), Err(UnmappedFetch(0x20))));
+        assert!(matches!(
+            ctx.instruction_at(0x10),
+            Err(NonExecutableFetch(0x10))
+        ));
+    }
+
+    #[test]
+    fn contexts_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<SleighContext>();
+    }
+
+    #[test]
+    fn parallel_decode() {
+        // mov eax, 0x7; ret
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0xc3];
+        let builder = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes));
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let builder = builder.clone();
+                    s.spawn(move || {
+                        let ctx = builder.build(SLEIGH_ARCH).unwrap();
+                        ctx.read(0, 2).count()
+                    })
+                })
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), 2);
+            }
+        });
+    }
+
+    #[test]
+    fn shared_language() {
+        let language = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .build_language(SLEIGH_ARCH)
+            .unwrap();
+        // mov eax, 0x7
+        let mov = language
+            .build_context(Image::from(vec![0xb8, 0x07, 0x00, 0x00, 0x00]))
+            .unwrap();
+        // ret
+        let ret = language.build_context(Image::from(vec![0xc3])).unwrap();
+        assert_eq!(mov.instruction_at(0).unwrap().disassembly.mnemonic, "MOV");
+        assert_eq!(ret.instruction_at(0).unwrap().disassembly.mnemonic, "RET");
+        let sibling = mov
+            .language()
+            .build_context(Image::from(vec![0xc3]))
+            .unwrap();
+        assert_eq!(sibling.instruction_at(0).unwrap().length, 1);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let language = language.clone();
+                s.spawn(move || {
+                    let ctx = language.build_context(Image::from(vec![0xc3])).unwrap();
+                    assert_eq!(ctx.instruction_at(0).unwrap().length, 1);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn shared_language_flush() {
+        let language = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .build_language(SLEIGH_ARCH)
+            .unwrap();
+        // mov eax, 0x7 and ret
+        let images = [vec![0xb8, 0x07, 0x00, 0x00, 0x00], vec![0xc3]];
+        std::thread::scope(|s| {
+            for thread in 0..4 {
+                let language = language.clone();
+                let images = images.clone();
+                s.spawn(move || {
+                    let mut ctx = language.build_context(Image::from(vec![])).unwrap();
+                    // each swap after a decode rebuilds this context's caches, while the
+                    // other threads keep decoding with the same language
+                    for i in thread..thread + 50 {
+                        ctx.set_image(Image::from(images[i % 2].clone())).unwrap();
+                        let expected = ["MOV", "RET"][i % 2];
+                        let instr = ctx.instruction_at(0).unwrap();
+                        assert_eq!(instr.disassembly.mnemonic, expected);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn decode_cache() {
+        // mov eax, 0x7; ret
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0xc3];
+        let mut ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        ctx.set_decode_cache_capacity(16);
+        for _ in 0..3 {
+            assert_eq!(ctx.read(0, 2).count(), 2);
+        }
+        let stats = ctx.decode_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (4, 2, 2));
+        // the cache must not outlive the image it was filled from
+        ctx.set_image(Image::from(vec![0xc3])).unwrap();
+        assert_eq!(ctx.decode_cache_stats().entries, 0);
+        assert_eq!(ctx.instruction_at(0).unwrap().disassembly.mnemonic, "RET");
+        ctx.reset_decode_cache_stats();
+        assert_eq!(ctx.decode_cache_stats().misses, 0);
+    }
+
+    #[test]
+    fn operands() {
+        // mov eax, 0x7; mov eax, dword ptr [rbx + 0x4]
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0x8b, 0x43, 0x04];
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        let eax = ctx.get_register("EAX").unwrap();
+        let instr = ctx.instruction_at(0).unwrap();
+        let operands = instr.disassembly.operands;
+        assert_eq!(operands.len(), 2);
+        assert_eq!(operands[0].kind, OperandKind::Register);
+        assert_eq!(operands[0].varnodes, vec![eax.clone()]);
+        assert_eq!(operands[1].kind, OperandKind::Immediate);
+        assert_eq!(operands[1].value, Some(7));
+        let instr = ctx.instruction_at(5).unwrap();
+        let operands = instr.disassembly.operands;
+        assert_eq!(operands[0].varnodes, vec![eax]);
+        assert_eq!(operands[1].kind, OperandKind::Memory);
+        assert!(operands[1]
+            .varnodes
+            .contains(&ctx.get_register("RBX").unwrap()));
+    }
+
+    #[test]
+    fn try_read() {
+        // mov eax, 0x7; push es, which is not encodable in 64-bit mode
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0x06];
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        let mut iter = ctx.try_read(0, 1);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().is_none());
+        assert_eq!(iter.stop_reason(), Some(StopReason::InstructionLimit));
+
+        let mut iter = ctx.try_read(5, 10);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(InstructionDecode {
+                address: 5,
+                class: SleighErrorClass::BadData,
+                ..
+            }))
+        ));
+        assert!(iter.next().is_none());
+        assert_eq!(iter.stop_reason(), Some(StopReason::Error(5)));
+
+        let mut iter = ctx.try_read(0, 10);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+
+        let mut iter = ctx.try_read(6, 10);
+        assert!(iter.next().is_none());
+        assert_eq!(iter.stop_reason(), Some(StopReason::LeftImage(6)));
+    }
+
+    #[test]
+    fn delay_slots() {
+        // mips: jr ra; nop
+        let bytes: Vec<u8> = vec![0x03, 0xe0, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00];
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build("MIPS:BE:32:default")
+            .unwrap();
+        let instr = ctx.read(0, 1).next().unwrap();
+        assert_eq!(instr.length, 8);
+        assert_eq!(instr.next_addr(), 8);
+        assert_eq!(instr.delay_slots.len(), 1);
+        assert!(instr.terminates_basic_block());
+    }
 }
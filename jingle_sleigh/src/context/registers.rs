@@ -0,0 +1,169 @@
+use crate::VarNode;
+use std::collections::HashMap;
+
+/// The architectural registers of a language, looked up on the Rust side so that register
+/// queries do not cross the FFI boundary.
+///
+/// Registers are kept ordered by space, then offset, then by size with the largest first. With
+/// this ordering, every register containing another comes before it.
+#[derive(Debug, Default)]
+pub(crate) struct RegisterTable {
+    registers: Vec<(VarNode, String)>,
+    by_name: HashMap<String, usize>,
+    by_varnode: HashMap<VarNode, usize>,
+    /// The index of the smallest register strictly containing each register
+    parents: Vec<Option<usize>>,
+    max_size: usize,
+}
+
+impl RegisterTable {
+    pub(crate) fn new(mut registers: Vec<(VarNode, String)>) -> Self {
+        registers.sort_by_key(|(vn, _)| (vn.space_index, vn.offset, std::cmp::Reverse(vn.size)));
+        let mut by_name = HashMap::with_capacity(registers.len());
+        let mut by_varnode = HashMap::with_capacity(registers.len());
+        for (idx, (vn, name)) in registers.iter().enumerate() {
+            by_name.entry(name.clone()).or_insert(idx);
+            by_varnode.entry(vn.clone()).or_insert(idx);
+        }
+        // Sweep the registers in order, keeping a stack of those that might still contain the
+        // current one. The parent is the closest stacked register that contains it; registers
+        // can partially overlap, so this is not always the top of the stack.
+        let mut parents = Vec::with_capacity(registers.len());
+        let mut open: Vec<usize> = vec![];
+        for (idx, (vn, _)) in registers.iter().enumerate() {
+            open.retain(|&o| {
+                let other = &registers[o].0;
+                other.space_index == vn.space_index && end(other) > vn.offset
+            });
+            let parent = open
+                .iter()
+                .rev()
+                .copied()
+                .find(|&o| registers[o].0.covers(vn) && registers[o].0 != *vn);
+            parents.push(parent);
+            open.push(idx);
+        }
+        let max_size = registers.iter().map(|(vn, _)| vn.size).max().unwrap_or(0);
+        Self {
+            registers,
+            by_name,
+            by_varnode,
+            parents,
+            max_size,
+        }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&VarNode> {
+        self.by_name.get(name).map(|&idx| &self.registers[idx].0)
+    }
+
+    pub(crate) fn name(&self, location: &VarNode) -> Option<&str> {
+        self.by_varnode
+            .get(location)
+            .map(|&idx| self.registers[idx].1.as_str())
+    }
+
+    pub(crate) fn all(&self) -> &[(VarNode, String)] {
+        &self.registers
+    }
+
+    pub(crate) fn space(&self) -> Option<usize> {
+        self.registers.first().map(|(vn, _)| vn.space_index)
+    }
+
+    pub(crate) fn parent(&self, name: &str) -> Option<&str> {
+        let idx = *self.by_name.get(name)?;
+        self.parents[idx].map(|p| self.registers[p].1.as_str())
+    }
+
+    pub(crate) fn children(&self, name: &str) -> Vec<&str> {
+        let Some(&idx) = self.by_name.get(name) else {
+            return vec![];
+        };
+        self.overlapping_indices(&self.registers[idx].0)
+            .filter(|&o| self.parents[o] == Some(idx))
+            .map(|o| self.registers[o].1.as_str())
+            .collect()
+    }
+
+    pub(crate) fn covering(&self, location: &VarNode) -> Option<&str> {
+        self.overlapping_indices(location)
+            .filter(|&o| self.registers[o].0.covers(location))
+            .min_by_key(|&o| self.registers[o].0.size)
+            .map(|o| self.registers[o].1.as_str())
+    }
+
+    pub(crate) fn overlapping(&self, location: &VarNode) -> Vec<&str> {
+        self.overlapping_indices(location)
+            .map(|o| self.registers[o].1.as_str())
+            .collect()
+    }
+
+    fn overlapping_indices<'a>(
+        &'a self,
+        location: &'a VarNode,
+    ) -> impl Iterator<Item = usize> + 'a {
+        // no register starting before this can reach the location
+        let earliest = location.offset.saturating_sub(self.max_size as u64);
+        let start = self.registers.partition_point(|(vn, _)| {
+            (vn.space_index, vn.offset) < (location.space_index, earliest)
+        });
+        let stop = self.registers.partition_point(|(vn, _)| {
+            (vn.space_index, vn.offset) < (location.space_index, end(location))
+        });
+        (start..stop.max(start)).filter(move |&o| {
+            let vn = &self.registers[o].0;
+            vn.space_index == location.space_index && end(vn) > location.offset
+        })
+    }
+}
+
+fn end(vn: &VarNode) -> u64 {
+    vn.offset.saturating_add(vn.size as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::registers::RegisterTable;
+    use crate::tests::REGISTER;
+    use crate::VarNode;
+
+    fn reg(offset: u64, size: usize, name: &str) -> (VarNode, String) {
+        (vn(offset, size), name.to_string())
+    }
+
+    fn vn(offset: u64, size: usize) -> VarNode {
+        crate::tests::vn(REGISTER, offset, size)
+    }
+
+    #[test]
+    fn test_register_table() {
+        let table = RegisterTable::new(vec![
+            reg(1, 1, "AH"),
+            reg(0, 1, "AL"),
+            reg(0, 8, "RAX"),
+            reg(0, 2, "AX"),
+            reg(0, 4, "EAX"),
+            reg(8, 8, "RCX"),
+            reg(8, 4, "ECX"),
+        ]);
+        assert_eq!(table.get("EAX"), Some(&vn(0, 4)));
+        assert_eq!(table.name(&vn(8, 4)), Some("ECX"));
+        assert_eq!(table.name(&vn(0, 3)), None);
+        assert_eq!(table.parent("AL"), Some("AX"));
+        assert_eq!(table.parent("AH"), Some("AX"));
+        assert_eq!(table.parent("EAX"), Some("RAX"));
+        assert_eq!(table.parent("RAX"), None);
+        assert_eq!(table.children("AX"), vec!["AL", "AH"]);
+        assert_eq!(table.children("RCX"), vec!["ECX"]);
+        assert!(table.children("AL").is_empty());
+        assert_eq!(table.covering(&vn(0, 3)), Some("EAX"));
+        assert_eq!(table.covering(&vn(1, 1)), Some("AH"));
+        assert_eq!(table.covering(&vn(6, 4)), None);
+        assert_eq!(table.overlapping(&vn(6, 4)), vec!["RAX", "RCX", "ECX"]);
+        assert_eq!(table.overlapping(&vn(1, 1)), vec!["RAX", "EAX", "AX", "AH"]);
+        assert!(table.overlapping(&vn(16, 4)).is_empty());
+        assert!(table.overlapping(&vn(u64::MAX - 1, 8)).is_empty());
+        assert_eq!(table.space(), Some(REGISTER));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::ffi::instruction::bridge::{DisassemblyFFI, OperandFFI, OperandKindFFI};
+use crate::VarNode;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A display-friendly representation of an instruction, generated by SLEIGH.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Disassembly {
+    /// SLEIGH's name for an ISA instruction
+    pub mnemonic: String,
+    /// A combined string representation of all the arguments of the instruction.
+    pub args: String,
+    /// The arguments of the instruction, in the order they appear in [args](Self::args)
+    #[serde(default)]
+    pub operands: Vec<Operand>,
+}
+
+impl Display for Disassembly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.mnemonic, self.args)
+    }
+}
+
+/// What an instruction [Operand] refers to, as determined from the value SLEIGH resolved it to
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum OperandKind {
+    /// An architectural register
+    Register,
+    /// A constant encoded in the instruction
+    Immediate,
+    /// A fixed location in memory, e.g. the target of a direct branch
+    Address,
+    /// A location in memory computed when the instruction runs, e.g. `[RAX + 0x10]`
+    Memory,
+    /// An operand SLEIGH does not resolve to a value, e.g. an ARM shift specifier
+    Other,
+}
+
+/// A single argument of a disassembled instruction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Operand {
+    pub kind: OperandKind,
+    /// How SLEIGH displays the operand
+    pub text: String,
+    /// The value of an [OperandKind::Immediate], or the offset of an [OperandKind::Address]
+    pub value: Option<u64>,
+    /// The location an [OperandKind::Register] or [OperandKind::Address] refers to. For
+    /// [OperandKind::Memory] and [OperandKind::Other], these are the registers that the operand
+    /// is built from.
+    pub varnodes: Vec<VarNode>,
+}
+
+impl From<OperandFFI> for Operand {
+    fn from(value: OperandFFI) -> Self {
+        let kind = match value.kind {
+            OperandKindFFI::Register => OperandKind::Register,
+            OperandKindFFI::Immediate => OperandKind::Immediate,
+            OperandKindFFI::Address => OperandKind::Address,
+            OperandKindFFI::Memory => OperandKind::Memory,
+            _ => OperandKind::Other,
+        };
+        Operand {
+            kind,
+            text: value.text,
+            value: matches!(kind, OperandKind::Immediate | OperandKind::Address)
+                .then_some(value.value),
+            varnodes: value.varnodes.iter().map(VarNode::from).collect(),
+        }
+    }
+}
+
+impl From<DisassemblyFFI> for Disassembly {
+    fn from(value: DisassemblyFFI) -> Self {
+        Disassembly {
+            mnemonic: value.mnemonic,
+            args: value.args,
+            operands: value.operands.into_iter().map(Operand::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+mod state;
+
+use crate::context::SleighContext;
+use crate::error::JingleSleighError;
+use crate::instruction::Instruction;
+pub use state::{EmulatorFlow, EmulatorState};
+
+/// A concrete interpreter for `PCODE`. Instructions are decoded on demand out of a
+/// [`SleighContext`] and executed against an [`EmulatorState`] initialized from the
+/// context's [`Image`](crate::context::Image).
+///
+/// Instructions are always decoded from the context's image, never from the emulator's own
+/// memory, so code that the emulated program writes (self-modifying or unpacked code) is not
+/// what gets executed. Emulating such code requires decoding it separately and passing it to
+/// [`EmulatorState::execute_instruction`].
+#[derive(Debug)]
+pub struct Emulator<'a> {
+    sleigh: &'a SleighContext,
+    state: EmulatorState,
+    pc: u64,
+}
+
+impl<'a> Emulator<'a> {
+    /// Create an emulator that will begin executing at the given address
+    pub fn new(sleigh: &'a SleighContext, entry: u64) -> Self {
+        Self {
+            sleigh,
+            state: EmulatorState::with_image(sleigh, sleigh.image.clone()),
+            pc: entry,
+        }
+    }
+
+    /// The address of the next instruction to execute
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: u64) {
+        self.pc = pc;
+    }
+
+    pub fn state(&self) -> &EmulatorState {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut EmulatorState {
+        &mut self.state
+    }
+
+    /// Decode and execute a single instruction, returning the instruction that was executed.
+    ///
+    /// The instruction is decoded from the [`SleighContext`]'s image, so writes the program
+    /// made to its own code are not seen.
+    pub fn step(&mut self) -> Result<Instruction, JingleSleighError> {
+        let instr = self.sleigh.instruction_at(self.pc)?;
+        self.pc = self.state.execute_instruction(&instr)?;
+        Ok(instr)
+    }
+
+    /// Execute up to `max_instrs` instructions, returning the number actually executed. Like
+    /// [`step`](Self::step), this decodes from the context's image rather than emulated memory.
+    pub fn run(&mut self, max_instrs: usize) -> Result<usize, JingleSleighError> {
+        for _ in 0..max_instrs {
+            self.step()?;
+        }
+        Ok(max_instrs)
+    }
+
+    /// Execute instructions until the program counter reaches `stop`, or until `max_instrs`
+    /// instructions have executed. Returns the number of instructions executed.
+    pub fn run_until(&mut self, stop: u64, max_instrs: usize) -> Result<usize, JingleSleighError> {
+        let mut count = 0;
+        while self.pc != stop && count < max_instrs {
+            self.step()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{Image, SleighContextBuilder};
+    use crate::emulator::Emulator;
+    use crate::tests::SLEIGH_ARCH;
+    use crate::RegisterManager;
+
+    #[test]
+    fn test_emulate_x86() {
+        // mov eax, 0x7; add eax, 0x3; ret
+        let bytes: Vec<u8> = vec![0xb8, 0x07, 0x00, 0x00, 0x00, 0x83, 0xc0, 0x03, 0xc3];
+        let ctx = SleighContextBuilder::load_ghidra_installation("/Applications/ghidra")
+            .unwrap()
+            .set_image(Image::from(bytes))
+            .build(SLEIGH_ARCH)
+            .unwrap();
+        let mut emu = Emulator::new(&ctx, 0);
+        emu.run(2).unwrap();
+        let eax = ctx.get_register("EAX").unwrap();
+        assert_eq!(emu.state().read_varnode(&eax).unwrap(), 10);
+        assert_eq!(emu.pc(), 8);
+    }
+}
@@ -0,0 +1,843 @@
+use crate::context::Image;
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::{
+    EmulatorConstantWrite, EmulatorDivisionByZero, EmulatorValueTooWide, InvalidRelativeBranch,
+    InvalidSpaceName, UnsupportedOperation,
+};
+use crate::ffi::addrspace::bridge::SpaceType;
+use crate::instruction::Instruction;
+use crate::pcode::PcodeOperation;
+use crate::space::{SleighEndianness, SpaceInfo, SpaceManager};
+use crate::varnode::{IndirectVarNode, VarNode};
+use std::collections::HashMap;
+
+/// The widest varnode (in bytes) the emulator is able to do arithmetic on
+const MAX_VALUE_BYTES: usize = 16;
+
+/// Describes where control goes after executing a single [`PcodeOperation`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EmulatorFlow {
+    /// Continue with the next operation
+    Fallthrough,
+    /// Transfer control to the given address in the default code space
+    Branch(u64),
+    /// Transfer control to another operation of the current instruction, relative to the
+    /// operation that was just executed. `SLEIGH` encodes these as branches to the `const` space.
+    RelativeBranch(i64),
+}
+
+/// Concrete, byte-addressed memory for every space of a `SLEIGH` context.
+///
+/// Unwritten bytes of the default code space are read out of the [`Image`] the state was
+/// created with; all other unwritten bytes read as zero. Multi-byte values are read and written
+/// using the endianness of the space they live in.
+#[derive(Debug, Clone)]
+pub struct EmulatorState {
+    spaces: Vec<SpaceInfo>,
+    code_space_idx: usize,
+    memory: Vec<HashMap<u64, u8>>,
+    image: Image,
+}
+
+impl SpaceManager for EmulatorState {
+    fn get_space_info(&self, idx: usize) -> Option<&SpaceInfo> {
+        self.spaces.get(idx)
+    }
+
+    fn get_all_space_info(&self) -> &[SpaceInfo] {
+        self.spaces.as_slice()
+    }
+
+    fn get_code_space_idx(&self) -> usize {
+        self.code_space_idx
+    }
+}
+
+impl EmulatorState {
+    /// Create an empty state for the spaces of the given [`SpaceManager`]
+    pub fn new<T: SpaceManager>(spaces: &T) -> Self {
+        Self::with_image(
+            spaces,
+            Image {
+                sections: vec![],
+                symbols: Default::default(),
+            },
+        )
+    }
+
+    /// Create a state for the spaces of the given [`SpaceManager`], with the default code space
+    /// backed by the given [`Image`]
+    pub fn with_image<T: SpaceManager>(spaces: &T, image: Image) -> Self {
+        let spaces_info = spaces.get_all_space_info().to_vec();
+        let memory = spaces_info.iter().map(|_| HashMap::new()).collect();
+        Self {
+            spaces: spaces_info,
+            code_space_idx: spaces.get_code_space_idx(),
+            memory,
+            image,
+        }
+    }
+
+    /// Read `size` raw bytes from the given space, in address order
+    pub fn read_bytes(
+        &self,
+        space_index: usize,
+        offset: u64,
+        size: usize,
+    ) -> Result<Vec<u8>, JingleSleighError> {
+        let space = self.memory.get(space_index).ok_or(InvalidSpaceName)?;
+        let bytes = (0..size as u64)
+            .map(|i| {
+                let addr = offset.wrapping_add(i);
+                match space.get(&addr) {
+                    Some(b) => *b,
+                    None if space_index == self.code_space_idx => {
+                        self.image.get_byte(addr as usize).unwrap_or(0)
+                    }
+                    None => 0,
+                }
+            })
+            .collect();
+        Ok(bytes)
+    }
+
+    /// Write raw bytes to the given space, in address order
+    pub fn write_bytes(
+        &mut self,
+        space_index: usize,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), JingleSleighError> {
+        let info = self.spaces.get(space_index).ok_or(InvalidSpaceName)?;
+        if info._type == SpaceType::IPTR_CONSTANT {
+            return Err(EmulatorConstantWrite);
+        }
+        let space = &mut self.memory[space_index];
+        for (i, b) in data.iter().enumerate() {
+            space.insert(offset.wrapping_add(i as u64), *b);
+        }
+        Ok(())
+    }
+
+    /// Read the value of a [`VarNode`] as an unsigned integer. Constants evaluate to their offset.
+    pub fn read_varnode(&self, vn: &VarNode) -> Result<u128, JingleSleighError> {
+        let info = self
+            .get_space_info(vn.space_index)
+            .ok_or(InvalidSpaceName)?;
+        if vn.size > MAX_VALUE_BYTES {
+            return Err(EmulatorValueTooWide);
+        }
+        if info._type == SpaceType::IPTR_CONSTANT {
+            return Ok(vn.offset as u128 & mask(vn.size));
+        }
+        let bytes = self.read_bytes(vn.space_index, vn.offset, vn.size)?;
+        Ok(bytes_to_value(&bytes, info.endianness))
+    }
+
+    /// Write an unsigned integer into a [`VarNode`], truncating it to the size of the varnode
+    pub fn write_varnode(&mut self, vn: &VarNode, val: u128) -> Result<(), JingleSleighError> {
+        let info = self
+            .get_space_info(vn.space_index)
+            .ok_or(InvalidSpaceName)?;
+        if vn.size > MAX_VALUE_BYTES {
+            return Err(EmulatorValueTooWide);
+        }
+        let bytes = value_to_bytes(val, vn.size, info.endianness);
+        self.write_bytes(vn.space_index, vn.offset, &bytes)
+    }
+
+    /// Resolve the location an [`IndirectVarNode`] points to
+    fn resolve_indirect(&self, vn: &IndirectVarNode) -> Result<VarNode, JingleSleighError> {
+        let offset = self.read_varnode(&vn.pointer_location)? as u64;
+        Ok(VarNode {
+            space_index: vn.pointer_space_index,
+            offset,
+            size: vn.access_size_bytes,
+        })
+    }
+
+    /// Copy the raw bytes of one [`VarNode`] into another. This works for varnodes of any size.
+    fn copy_varnode(&mut self, input: &VarNode, output: &VarNode) -> Result<(), JingleSleighError> {
+        let info = self
+            .get_space_info(input.space_index)
+            .ok_or(InvalidSpaceName)?;
+        if info._type == SpaceType::IPTR_CONSTANT {
+            let val = self.read_varnode(input)?;
+            return self.write_varnode(output, val);
+        }
+        let bytes = self.read_bytes(input.space_index, input.offset, input.size)?;
+        self.write_bytes(output.space_index, output.offset, &bytes)
+    }
+
+    fn is_constant(&self, vn: &VarNode) -> bool {
+        self.get_space_info(vn.space_index)
+            .map(|s| s._type == SpaceType::IPTR_CONSTANT)
+            .unwrap_or(false)
+    }
+
+    /// Determine the destination of a direct branch operation
+    fn branch_target(&self, input: &VarNode) -> EmulatorFlow {
+        if self.is_constant(input) {
+            EmulatorFlow::RelativeBranch(sign_extend(input.offset as u128, input.size) as i64)
+        } else {
+            EmulatorFlow::Branch(input.offset)
+        }
+    }
+
+    /// Execute every operation of the given [`Instruction`], honoring relative `PCODE` branches.
+    /// Returns the address of the next instruction to execute.
+    pub fn execute_instruction(&mut self, instr: &Instruction) -> Result<u64, JingleSleighError> {
+        let mut idx: usize = 0;
+        while idx < instr.ops.len() {
+            match self.execute(&instr.ops[idx])? {
+                EmulatorFlow::Fallthrough => idx += 1,
+                EmulatorFlow::Branch(addr) => return Ok(addr),
+                EmulatorFlow::RelativeBranch(rel) => {
+                    let dest = idx as i64 + rel;
+                    if dest < 0 || dest as usize > instr.ops.len() {
+                        return Err(InvalidRelativeBranch);
+                    }
+                    idx = dest as usize;
+                }
+            }
+        }
+        Ok(instr.next_addr())
+    }
+
+    /// Apply the updates of a single [`PcodeOperation`] to this state
+    pub fn execute(&mut self, op: &PcodeOperation) -> Result<EmulatorFlow, JingleSleighError> {
+        macro_rules! unary {
+            ($input:expr, $output:expr, |$a:ident| $body:expr) => {{
+                let $a = self.read_varnode($input)?;
+                let result: u128 = $body;
+                self.write_varnode($output, result)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }};
+        }
+        macro_rules! binary {
+            ($input0:expr, $input1:expr, $output:expr, |$a:ident, $b:ident| $body:expr) => {{
+                let $a = self.read_varnode($input0)?;
+                let $b = self.read_varnode($input1)?;
+                let result: u128 = $body;
+                self.write_varnode($output, result)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }};
+        }
+        macro_rules! float_unary {
+            ($input:expr, $output:expr, |$a:ident| $body:expr) => {{
+                let $a = read_float(self.read_varnode($input)?, $input.size, op)?;
+                let result: f64 = $body;
+                self.write_varnode($output, write_float(result, $output.size, op)?)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }};
+        }
+        macro_rules! float_binary {
+            ($input0:expr, $input1:expr, $output:expr, |$a:ident, $b:ident| $body:expr) => {{
+                let $a = read_float(self.read_varnode($input0)?, $input0.size, op)?;
+                let $b = read_float(self.read_varnode($input1)?, $input1.size, op)?;
+                let result: f64 = $body;
+                self.write_varnode($output, write_float(result, $output.size, op)?)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }};
+        }
+        macro_rules! float_compare {
+            ($input0:expr, $input1:expr, $output:expr, |$a:ident, $b:ident| $body:expr) => {{
+                let $a = read_float(self.read_varnode($input0)?, $input0.size, op)?;
+                let $b = read_float(self.read_varnode($input1)?, $input1.size, op)?;
+                let result: bool = $body;
+                self.write_varnode($output, result as u128)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }};
+        }
+        match op {
+            PcodeOperation::Copy { input, output } | PcodeOperation::Cast { input, output } => {
+                self.copy_varnode(input, output)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::Load { input, output } => {
+                let location = self.resolve_indirect(input)?;
+                self.copy_varnode(&location, output)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::Store { output, input } => {
+                let location = self.resolve_indirect(output)?;
+                self.copy_varnode(input, &location)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::Branch { input } | PcodeOperation::Call { input } => {
+                Ok(self.branch_target(input))
+            }
+            PcodeOperation::CBranch { input0, input1 } => {
+                if self.read_varnode(input1)? != 0 {
+                    Ok(self.branch_target(input0))
+                } else {
+                    Ok(EmulatorFlow::Fallthrough)
+                }
+            }
+            PcodeOperation::BranchInd { input }
+            | PcodeOperation::CallInd { input }
+            | PcodeOperation::Return { input } => Ok(EmulatorFlow::Branch(
+                self.read_varnode(&input.pointer_location)? as u64,
+            )),
+            PcodeOperation::IntEqual {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a == b) as u128),
+            PcodeOperation::IntNotEqual {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a != b) as u128),
+            PcodeOperation::IntSignedLess {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (sign_extend(a, input0.size)
+                < sign_extend(b, input1.size))
+                as u128),
+            PcodeOperation::IntSignedLessEqual {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (sign_extend(a, input0.size)
+                <= sign_extend(b, input1.size))
+                as u128),
+            PcodeOperation::IntLess {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a < b) as u128),
+            PcodeOperation::IntLessEqual {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a <= b) as u128),
+            PcodeOperation::IntZExt { input, output } => unary!(input, output, |a| a),
+            PcodeOperation::IntSExt { input, output } => {
+                unary!(input, output, |a| sign_extend(a, input.size) as u128)
+            }
+            PcodeOperation::IntAdd {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a.wrapping_add(b)),
+            PcodeOperation::IntSub {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a.wrapping_sub(b)),
+            PcodeOperation::IntCarry {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                (a.wrapping_add(b) & mask(input0.size) < a) as u128
+            }),
+            PcodeOperation::IntSignedCarry {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let a = sign_extend(a, input0.size);
+                let b = sign_extend(b, input0.size);
+                let sum = sign_extend(a.wrapping_add(b) as u128, input0.size);
+                ((a < 0) == (b < 0) && (sum < 0) != (a < 0)) as u128
+            }),
+            PcodeOperation::IntSignedBorrow {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let a = sign_extend(a, input0.size);
+                let b = sign_extend(b, input0.size);
+                let diff = sign_extend(a.wrapping_sub(b) as u128, input0.size);
+                ((a < 0) != (b < 0) && (diff < 0) != (a < 0)) as u128
+            }),
+            PcodeOperation::Int2Comp { input, output } => {
+                unary!(input, output, |a| a.wrapping_neg())
+            }
+            PcodeOperation::IntNegate { input, output } => unary!(input, output, |a| !a),
+            PcodeOperation::IntXor {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a ^ b),
+            PcodeOperation::IntAnd {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a & b),
+            PcodeOperation::IntOr {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a | b),
+            PcodeOperation::IntLeftShift {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                if b >= (output.size * 8) as u128 {
+                    0
+                } else {
+                    a << b
+                }
+            }),
+            PcodeOperation::IntRightShift {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                if b >= (input0.size * 8) as u128 {
+                    0
+                } else {
+                    a >> b
+                }
+            }),
+            PcodeOperation::IntSignedRightShift {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let a = sign_extend(a, input0.size);
+                let shift = b.min((input0.size * 8 - 1) as u128);
+                (a >> shift) as u128
+            }),
+            PcodeOperation::IntMult {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a.wrapping_mul(b)),
+            PcodeOperation::IntDiv {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                a.checked_div(b).ok_or(EmulatorDivisionByZero)?
+            }),
+            PcodeOperation::IntSignedDiv {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let a = sign_extend(a, input0.size);
+                let b = sign_extend(b, input1.size);
+                if b == 0 {
+                    return Err(EmulatorDivisionByZero);
+                }
+                a.wrapping_div(b) as u128
+            }),
+            PcodeOperation::IntRem {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                a.checked_rem(b).ok_or(EmulatorDivisionByZero)?
+            }),
+            PcodeOperation::IntSignedRem {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let a = sign_extend(a, input0.size);
+                let b = sign_extend(b, input1.size);
+                if b == 0 {
+                    return Err(EmulatorDivisionByZero);
+                }
+                a.wrapping_rem(b) as u128
+            }),
+            PcodeOperation::BoolNegate { input, output } => unary!(input, output, |a| (a & 1) ^ 1),
+            PcodeOperation::BoolXor {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a ^ b) & 1),
+            PcodeOperation::BoolAnd {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a & b & 1),
+            PcodeOperation::BoolOr {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| (a | b) & 1),
+            PcodeOperation::FloatEqual {
+                input0,
+                input1,
+                output,
+            } => float_compare!(input0, input1, output, |a, b| a == b),
+            PcodeOperation::FloatNotEqual {
+                input0,
+                input1,
+                output,
+            } => float_compare!(input0, input1, output, |a, b| a != b),
+            PcodeOperation::FloatLess {
+                input0,
+                input1,
+                output,
+            } => float_compare!(input0, input1, output, |a, b| a < b),
+            PcodeOperation::FloatLessEqual {
+                input0,
+                input1,
+                output,
+            } => float_compare!(input0, input1, output, |a, b| a <= b),
+            PcodeOperation::FloatNaN { input, output } => {
+                let a = read_float(self.read_varnode(input)?, input.size, op)?;
+                self.write_varnode(output, a.is_nan() as u128)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::FloatAdd {
+                input0,
+                input1,
+                output,
+            } => float_binary!(input0, input1, output, |a, b| a + b),
+            PcodeOperation::FloatDiv {
+                input0,
+                input1,
+                output,
+            } => float_binary!(input0, input1, output, |a, b| a / b),
+            PcodeOperation::FloatMult {
+                input0,
+                input1,
+                output,
+            } => float_binary!(input0, input1, output, |a, b| a * b),
+            PcodeOperation::FloatSub {
+                input0,
+                input1,
+                output,
+            } => float_binary!(input0, input1, output, |a, b| a - b),
+            PcodeOperation::FloatNeg { input, output } => float_unary!(input, output, |a| -a),
+            PcodeOperation::FloatAbs { input, output } => float_unary!(input, output, |a| a.abs()),
+            PcodeOperation::FloatSqrt { input, output } => {
+                float_unary!(input, output, |a| a.sqrt())
+            }
+            PcodeOperation::FloatFloatToFloat { input, output } => {
+                float_unary!(input, output, |a| a)
+            }
+            PcodeOperation::FloatCeil { input, output } => {
+                float_unary!(input, output, |a| a.ceil())
+            }
+            PcodeOperation::FloatFloor { input, output } => {
+                float_unary!(input, output, |a| a.floor())
+            }
+            PcodeOperation::FloatRound { input, output } => {
+                // SLEIGH rounds halfway cases up, not away from zero as f64::round does
+                float_unary!(input, output, |a| (a + 0.5).floor())
+            }
+            PcodeOperation::FloatIntToFloat { input, output } => {
+                let a = sign_extend(self.read_varnode(input)?, input.size);
+                self.write_varnode(output, write_float(a as f64, output.size, op)?)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::FloatTrunc { input, output } => {
+                let a = read_float(self.read_varnode(input)?, input.size, op)?;
+                self.write_varnode(output, a.trunc() as i128 as u128)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::Indirect { input0, output, .. } => {
+                self.copy_varnode(input0, output)?;
+                Ok(EmulatorFlow::Fallthrough)
+            }
+            PcodeOperation::Piece {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                if input1.size >= MAX_VALUE_BYTES {
+                    return Err(EmulatorValueTooWide);
+                }
+                (a << (input1.size * 8)) | b
+            }),
+            PcodeOperation::SubPiece {
+                input0,
+                input1,
+                output,
+            } => {
+                let shift = input1.offset as usize * 8;
+                unary!(input0, output, |a| if shift >= 128 {
+                    0
+                } else {
+                    a >> shift
+                })
+            }
+            PcodeOperation::PtrAdd {
+                input0,
+                input1,
+                input2,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                a.wrapping_add(b.wrapping_mul(input2.offset as u128))
+            }),
+            PcodeOperation::PtrSub {
+                input0,
+                input1,
+                output,
+            } => binary!(input0, input1, output, |a, b| a.wrapping_add(b)),
+            PcodeOperation::Insert {
+                input0,
+                input1,
+                position,
+                size,
+                output,
+            } => binary!(input0, input1, output, |a, b| {
+                let field = bit_mask(size.offset as u32) << position.offset;
+                (a & !field) | ((b << position.offset) & field)
+            }),
+            PcodeOperation::Extract {
+                input0,
+                position,
+                size,
+                output,
+            } => unary!(input0, output, |a| {
+                (a >> position.offset) & bit_mask(size.offset as u32)
+            }),
+            PcodeOperation::PopCount { input, output } => {
+                unary!(input, output, |a| a.count_ones() as u128)
+            }
+            PcodeOperation::LzCount { input, output } => unary!(input, output, |a| {
+                (a.leading_zeros() - (128 - input.size as u32 * 8)) as u128
+            }),
+            PcodeOperation::CallOther { .. }
+            | PcodeOperation::MultiEqual { .. }
+            | PcodeOperation::SegmentOp { .. }
+            | PcodeOperation::CPoolRef { .. }
+            | PcodeOperation::New { .. } => Err(UnsupportedOperation(Box::new(op.clone()))),
+        }
+    }
+}
+
+/// A mask covering the low `size` bytes of a value
+fn mask(size: usize) -> u128 {
+    bit_mask(size as u32 * 8)
+}
+
+/// A mask covering the low `bits` bits of a value
+fn bit_mask(bits: u32) -> u128 {
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << bits) - 1
+    }
+}
+
+/// Interpret the low `size` bytes of a value as a two's complement signed integer
+fn sign_extend(val: u128, size: usize) -> i128 {
+    let bits = (size * 8) as u32;
+    if bits == 0 || bits >= 128 {
+        return val as i128;
+    }
+    let shift = 128 - bits;
+    ((val << shift) as i128) >> shift
+}
+
+fn bytes_to_value(bytes: &[u8], endianness: SleighEndianness) -> u128 {
+    let fold = |acc: u128, b: &u8| (acc << 8) | *b as u128;
+    match endianness {
+        SleighEndianness::Big => bytes.iter().fold(0, fold),
+        SleighEndianness::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+fn value_to_bytes(val: u128, size: usize, endianness: SleighEndianness) -> Vec<u8> {
+    let le = val.to_le_bytes();
+    let mut bytes = le[0..size].to_vec();
+    if let SleighEndianness::Big = endianness {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Interpret a raw value as an IEEE float of the given size. Only single and double precision
+/// values are supported concretely.
+fn read_float(val: u128, size: usize, op: &PcodeOperation) -> Result<f64, JingleSleighError> {
+    match size {
+        4 => Ok(f32::from_bits(val as u32) as f64),
+        8 => Ok(f64::from_bits(val as u64)),
+        _ => Err(UnsupportedOperation(Box::new(op.clone()))),
+    }
+}
+
+fn write_float(val: f64, size: usize, op: &PcodeOperation) -> Result<u128, JingleSleighError> {
+    match size {
+        4 => Ok((val as f32).to_bits() as u128),
+        8 => Ok(val.to_bits() as u128),
+        _ => Err(UnsupportedOperation(Box::new(op.clone()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::state::{EmulatorFlow, EmulatorState};
+    use crate::space::SleighEndianness;
+    use crate::tests::{vn, TestContext, CONST, RAM, REGISTER};
+    use crate::PcodeOperation;
+
+    #[test]
+    fn test_endianness() {
+        for (e, expected) in [
+            (SleighEndianness::Little, [0xef, 0xbe, 0xad, 0xde]),
+            (SleighEndianness::Big, [0xde, 0xad, 0xbe, 0xef]),
+        ] {
+            let mut state = EmulatorState::new(&TestContext::with_endianness(e));
+            state
+                .write_varnode(&vn(REGISTER, 0, 4), 0xdead_beef)
+                .unwrap();
+            assert_eq!(state.read_bytes(REGISTER, 0, 4).unwrap(), expected);
+            assert_eq!(
+                state.read_varnode(&vn(REGISTER, 0, 4)).unwrap(),
+                0xdead_beef
+            );
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut state = EmulatorState::new(&TestContext::new());
+        let a = vn(REGISTER, 0, 4);
+        let b = vn(REGISTER, 8, 4);
+        let out = vn(REGISTER, 16, 4);
+        let flag = vn(REGISTER, 24, 1);
+        state.write_varnode(&a, 0xffff_fff0).unwrap();
+        state.write_varnode(&b, 0x20).unwrap();
+        state
+            .execute(&PcodeOperation::IntAdd {
+                input0: a.clone(),
+                input1: b.clone(),
+                output: out.clone(),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&out).unwrap(), 0x10);
+        state
+            .execute(&PcodeOperation::IntCarry {
+                input0: a.clone(),
+                input1: b.clone(),
+                output: flag.clone(),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&flag).unwrap(), 1);
+        state
+            .execute(&PcodeOperation::IntSignedLess {
+                input0: a.clone(),
+                input1: b.clone(),
+                output: flag.clone(),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&flag).unwrap(), 1);
+        state
+            .execute(&PcodeOperation::IntSignedRightShift {
+                input0: a.clone(),
+                input1: vn(CONST, 4, 1),
+                output: out.clone(),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&out).unwrap(), 0xffff_ffff);
+        state
+            .execute(&PcodeOperation::IntSignedDiv {
+                input0: a.clone(),
+                input1: vn(CONST, 0, 4),
+                output: out.clone(),
+            })
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_load_store() {
+        let mut state = EmulatorState::new(&TestContext::new());
+        let ptr = vn(REGISTER, 0, 8);
+        state.write_varnode(&ptr, 0x1000).unwrap();
+        state
+            .execute(&PcodeOperation::Store {
+                output: crate::IndirectVarNode {
+                    pointer_space_index: RAM,
+                    pointer_location: ptr.clone(),
+                    access_size_bytes: 2,
+                },
+                input: vn(CONST, 0x1234, 2),
+            })
+            .unwrap();
+        assert_eq!(state.read_bytes(RAM, 0x1000, 2).unwrap(), [0x34, 0x12]);
+        state
+            .execute(&PcodeOperation::Load {
+                input: crate::IndirectVarNode {
+                    pointer_space_index: RAM,
+                    pointer_location: ptr.clone(),
+                    access_size_bytes: 2,
+                },
+                output: vn(REGISTER, 8, 2),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&vn(REGISTER, 8, 2)).unwrap(), 0x1234);
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut state = EmulatorState::new(&TestContext::new());
+        let flow = state
+            .execute(&PcodeOperation::Branch {
+                input: vn(CONST, (-2i64) as u64, 8),
+            })
+            .unwrap();
+        assert_eq!(flow, EmulatorFlow::RelativeBranch(-2));
+        let flow = state
+            .execute(&PcodeOperation::CBranch {
+                input0: vn(RAM, 0x400, 8),
+                input1: vn(CONST, 1, 1),
+            })
+            .unwrap();
+        assert_eq!(flow, EmulatorFlow::Branch(0x400));
+        let flow = state
+            .execute(&PcodeOperation::CBranch {
+                input0: vn(RAM, 0x400, 8),
+                input1: vn(CONST, 0, 1),
+            })
+            .unwrap();
+        assert_eq!(flow, EmulatorFlow::Fallthrough);
+    }
+
+    #[test]
+    fn test_float() {
+        let mut state = EmulatorState::new(&TestContext::new());
+        state
+            .write_varnode(&vn(REGISTER, 0, 8), 1.5f64.to_bits() as u128)
+            .unwrap();
+        state
+            .write_varnode(&vn(REGISTER, 8, 8), 2.25f64.to_bits() as u128)
+            .unwrap();
+        state
+            .execute(&PcodeOperation::FloatMult {
+                input0: vn(REGISTER, 0, 8),
+                input1: vn(REGISTER, 8, 8),
+                output: vn(REGISTER, 16, 4),
+            })
+            .unwrap();
+        let result = state.read_varnode(&vn(REGISTER, 16, 4)).unwrap() as u32;
+        assert_eq!(f32::from_bits(result), 3.375);
+        state
+            .execute(&PcodeOperation::FloatTrunc {
+                input: vn(REGISTER, 16, 4),
+                output: vn(REGISTER, 24, 4),
+            })
+            .unwrap();
+        assert_eq!(state.read_varnode(&vn(REGISTER, 24, 4)).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_float_round() {
+        let mut state = EmulatorState::new(&TestContext::new());
+        for (input, expected) in [(2.5f64, 3.0f64), (2.4, 2.0), (-2.5, -2.0), (-2.6, -3.0)] {
+            state
+                .write_varnode(&vn(REGISTER, 0, 8), input.to_bits() as u128)
+                .unwrap();
+            state
+                .execute(&PcodeOperation::FloatRound {
+                    input: vn(REGISTER, 0, 8),
+                    output: vn(REGISTER, 8, 8),
+                })
+                .unwrap();
+            let result = state.read_varnode(&vn(REGISTER, 8, 8)).unwrap() as u64;
+            assert_eq!(f64::from_bits(result), expected);
+        }
+    }
+}
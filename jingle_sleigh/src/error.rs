@@ -1,5 +1,66 @@
+use crate::pcode::PcodeOperation;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 use thiserror::Error;
 
+/// The type of the C++ exception `SLEIGH` raised. `cxx` only carries the exception's message
+/// across the FFI boundary, so `jingle` tags the message with the type before it crosses.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SleighErrorClass {
+    /// `UnimplError`: the instruction decoded, but `SLEIGH` has no semantics for it
+    Unimplemented,
+    /// `BadDataError`: the bytes do not decode to any instruction of the language
+    BadData,
+    /// `DataUnavailError`: the requested bytes could not be read from the image
+    DataUnavailable,
+    /// `SleighError`: the language was asked for something it does not define, e.g. an
+    /// unknown register
+    Sleigh,
+    /// `ParseError`: a specification file could not be parsed
+    Parse,
+    /// `DecoderError`: a `.sla` file is malformed, e.g. compiled for another `SLEIGH` version
+    Decoder,
+    /// Any other `LowlevelError`
+    Lowlevel,
+    /// A C++ exception not specific to `SLEIGH`
+    Other,
+}
+
+impl SleighErrorClass {
+    /// Split the class tag off of a message produced by `jingle`'s C++ exception handler
+    pub(crate) fn from_what(what: &str) -> (Self, String) {
+        let (tag, message) = what.split_once(": ").unwrap_or(("", what));
+        let class = match tag {
+            "UnimplError" => Self::Unimplemented,
+            "BadDataError" => Self::BadData,
+            "DataUnavailError" => Self::DataUnavailable,
+            "SleighError" => Self::Sleigh,
+            "ParseError" => Self::Parse,
+            "DecoderError" => Self::Decoder,
+            "LowlevelError" => Self::Lowlevel,
+            "exception" => Self::Other,
+            _ => return (Self::Other, what.to_string()),
+        };
+        (class, message.to_string())
+    }
+}
+
+impl Display for SleighErrorClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Unimplemented => "UnimplError",
+            Self::BadData => "BadDataError",
+            Self::DataUnavailable => "DataUnavailError",
+            Self::Sleigh => "SleighError",
+            Self::Parse => "ParseError",
+            Self::Decoder => "DecoderError",
+            Self::Lowlevel => "LowlevelError",
+            Self::Other => "exception",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// An error (usually from across the FFI boundary) in something involving sleigh
 #[derive(Debug, Error)]
 pub enum JingleSleighError {
@@ -13,12 +74,29 @@ pub enum JingleSleighError {
     /// The user provided a sleigh language ID that has not been loaded
     #[error("that's not a valid language id")]
     InvalidLanguageId,
-    /// Attempted to initialize sleigh but something went wrong
-    #[error("Something went wrong putting bytes into sleigh")]
-    SleighInitError,
+    /// `SLEIGH` raised an exception while loading a compiled language, e.g. because the
+    /// `.sla` file was compiled for a different version of `SLEIGH`
+    #[error("Sleigh unable to load {}: {class}: {message}", path.display())]
+    SleighInit {
+        path: PathBuf,
+        class: SleighErrorClass,
+        message: String,
+    },
     /// Unable to load the provided binary image for sleigh
     #[error("Something went wrong putting bytes into sleigh")]
     ImageLoadError,
+    /// An image file could not be opened or memory-mapped
+    #[error("Unable to map image file {}: {source}", path.display())]
+    ImageFileRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A line of an Intel HEX or S-record file could not be parsed, or failed its checksum
+    #[error("Malformed record on line {0}")]
+    MalformedRecord(usize),
+    /// Two sections of an image were placed over the same addresses
+    #[error("Image sections overlap")]
+    OverlappingSections,
     /// Unable to parse the provided elf for sleigh
     #[cfg(feature = "elf")]
     #[error("Trouble loading an elf")]
@@ -28,11 +106,61 @@ pub enum JingleSleighError {
     NoImageProvided,
     /// Sleigh encountered an error attempting to disassemble an instruction.
     /// This most likely just indicates an invalid opcode.
-    #[error("Sleigh unable to decode an instruction")]
-    InstructionDecode,
+    #[error("Sleigh unable to decode the instruction at {address:#x}: {class}: {message}")]
+    InstructionDecode {
+        address: u64,
+        class: SleighErrorClass,
+        message: String,
+    },
+    /// An instruction was fetched from an address no section of the image covers
+    #[error("Instruction fetch from unmapped address {0:#x}")]
+    UnmappedFetch(u64),
+    /// An instruction was fetched from a section without execute permissions
+    #[error("Instruction fetch from non-executable address {0:#x}")]
+    NonExecutableFetch(u64),
+    /// A context variable was referenced that the loaded language does not define
+    #[error("The language does not define the requested context variable")]
+    InvalidContextVariable,
     /// A [`VarNode`](crate::VarNode) was constructed referencing a non-existent space
     #[error("A varnode was constructed referencing a non-existent space")]
     InvalidSpaceName,
+    /// The concrete emulator has no semantics for the given operation
+    #[error("The emulator does not support this operation")]
+    UnsupportedOperation(Box<PcodeOperation>),
+    /// The concrete emulator was asked to do arithmetic on a varnode wider than 16 bytes
+    #[error("Varnode is too wide for concrete arithmetic")]
+    EmulatorValueTooWide,
+    /// The concrete emulator attempted an integer division or remainder by zero
+    #[error("Attempted to divide by zero")]
+    EmulatorDivisionByZero,
+    /// The concrete emulator attempted to write into the constant space
+    #[error("Cannot write values into constant space")]
+    EmulatorConstantWrite,
+    /// Text given to a [PcodeParser](crate::parse::PcodeParser) was not valid `PCODE`
+    #[error("Unable to parse pcode on line {line}: {message}")]
+    PcodeParse { line: usize, message: String },
+    /// Portable instructions were bound to a context for a different language than the one
+    /// that lifted them
+    #[error("Instructions were lifted for {expected}, but the context is for {found}")]
+    PortableLanguageMismatch { expected: String, found: String },
+    /// Portable instructions reference a space that the context they are bound to lacks, or
+    /// that has a different layout there
+    #[error("The context has no space compatible with the stored space {0}")]
+    IncompatibleSpace(String),
+    /// Portable instructions were written by an unsupported version of `jingle`
+    #[error("Unsupported portable instruction format version {0}")]
+    PortableVersion(u32),
+    /// Portable instructions could not be read from or written to JSON
+    #[cfg(feature = "json")]
+    #[error("Trouble encoding portable instructions as JSON")]
+    PortableJson(#[from] serde_json::Error),
+    /// Portable instructions could not be read from or written to their binary encoding
+    #[cfg(feature = "bincode")]
+    #[error("Trouble encoding portable instructions as binary")]
+    PortableBinary(#[from] bincode::Error),
+    /// A relative `PCODE` branch pointed outside of the instruction it belongs to
+    #[error("Relative pcode branch leaves the current instruction")]
+    InvalidRelativeBranch,
 }
 
 impl From<JingleSleighError> for std::fmt::Error {
@@ -40,3 +168,30 @@ impl From<JingleSleighError> for std::fmt::Error {
         std::fmt::Error
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::error::SleighErrorClass;
+
+    #[test]
+    fn test_error_class() {
+        assert_eq!(
+            SleighErrorClass::from_what("UnimplError: Instruction not implemented: foo"),
+            (
+                SleighErrorClass::Unimplemented,
+                "Instruction not implemented: foo".to_string()
+            )
+        );
+        assert_eq!(
+            SleighErrorClass::from_what("DecoderError: Unsupported sla version"),
+            (
+                SleighErrorClass::Decoder,
+                "Unsupported sla version".to_string()
+            )
+        );
+        assert_eq!(
+            SleighErrorClass::from_what("something untagged"),
+            (SleighErrorClass::Other, "something untagged".to_string())
+        );
+    }
+}
@@ -1,16 +1,28 @@
-use crate::ffi::context_ffi::bridge::makeContext;
-use bridge::ContextFFI;
-use cxx::{Exception, UniquePtr};
-use std::sync::Mutex;
+use crate::ffi::image::ImageLoader;
+use bridge::{ContextFFI, LanguageFFI};
 
-pub(crate) static CTX_BUILD_MUTEX: Mutex<
-    fn(&str, bridge::Image) -> Result<UniquePtr<ContextFFI>, Exception>,
-> = Mutex::new(makeContext);
+// SAFETY: a ContextFFI owns its decoder, decode caches and context database outright, and reads
+// image bytes only through its ImageLoader, which is itself Send. The language tables it shares
+// with other contexts are only ever read. It is not Sync: decoding through a shared reference
+// still mutates the decode caches.
+unsafe impl Send for ContextFFI {}
+
+// SAFETY: a LanguageFFI is only written to while it is being constructed. Afterwards it hands out
+// nothing but a const reference to its tables, and nothing written by decoding or flushing lives
+// in them: every decode entry point only reads the tables, and the parse caches, p-code buffer
+// and context database belong to each context's own JingleSleigh (see SleighTables in
+// context.h). Parsing the .sla file itself is guarded on the C++ side.
+unsafe impl Send for LanguageFFI {}
+unsafe impl Sync for LanguageFFI {}
 
 #[cxx::bridge]
 pub(crate) mod bridge {
+    extern "Rust" {
+        type ImageLoader;
+        fn load_fill(self: &ImageLoader, buf: &mut [u8], addr: u64);
+    }
+
     unsafe extern "C++" {
-        type Image = crate::context::Image;
         type InstructionFFI = crate::ffi::instruction::bridge::InstructionFFI;
 
         type VarnodeInfoFFI = crate::ffi::instruction::bridge::VarnodeInfoFFI;
@@ -24,18 +36,42 @@ pub(crate) mod bridge {
         include!("jingle_sleigh/src/ffi/cpp/context.h");
         include!("jingle_sleigh/src/ffi/cpp/exception.h");
 
+        pub(crate) type LanguageFFI;
+        pub(crate) fn makeLanguage(slaPath: &str) -> Result<SharedPtr<LanguageFFI>>;
+    }
+
+    unsafe extern "C++" {
         pub(crate) type ContextFFI;
-        pub(super) fn makeContext(slaPath: &str, img: Image) -> Result<UniquePtr<ContextFFI>>;
-        pub(crate) fn set_initial_context(self: Pin<&mut ContextFFI>, name: &str, value: u32);
+        pub(crate) fn makeContext(
+            language: SharedPtr<LanguageFFI>,
+            img: Box<ImageLoader>,
+        ) -> Result<UniquePtr<ContextFFI>>;
+        pub(crate) fn set_initial_context(
+            self: Pin<&mut ContextFFI>,
+            name: &str,
+            value: u32,
+        ) -> Result<()>;
+
+        pub(crate) fn getContextDefault(&self, name: &str) -> Result<u32>;
+        pub(crate) fn setContextRange(
+            self: Pin<&mut ContextFFI>,
+            name: &str,
+            start: u64,
+            end: u64,
+            value: u32,
+        ) -> Result<()>;
+        pub(crate) fn getContextValue(&self, name: &str, offset: u64) -> Result<u32>;
+        pub(crate) fn getContextWords(&self, offset: u64) -> Vec<u32>;
+
+        pub(crate) fn setImage(self: Pin<&mut ContextFFI>, img: Box<ImageLoader>) -> Result<()>;
 
         pub(crate) fn get_one_instruction(&self, offset: u64) -> Result<InstructionFFI>;
 
         pub(crate) fn getSpaceByIndex(&self, idx: i32) -> SharedPtr<AddrSpaceHandle>;
         pub(crate) fn getNumSpaces(&self) -> i32;
 
-        pub(crate) fn getRegister(&self, name: &str) -> Result<VarnodeInfoFFI>;
-        pub(crate) fn getRegisterName(&self, name: VarnodeInfoFFI) -> Result<&str>;
-
         pub(crate) fn getRegisters(&self) -> Vec<RegisterInfoFFI>;
+
+        pub(crate) fn getUserOpNames(&self) -> Vec<String>;
     }
 }
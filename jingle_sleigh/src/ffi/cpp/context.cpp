@@ -2,9 +2,13 @@
 #include "context.h"
 
 #include <memory>
+#include <mutex>
+#include <sstream>
 #include <utility>
 #include "jingle_sleigh/src/ffi/instruction.rs.h"
+#include "jingle_sleigh/src/ffi/context_ffi.rs.h"
 #include "sleigh/loadimage.hh"
+#include "sleigh/xml.hh"
 
 class PcodeCacher : public ghidra::PcodeEmit {
 public:
@@ -53,29 +57,14 @@ public:
     }
 };
 
-DummyLoadImage::DummyLoadImage() : ghidra::LoadImage("jingle") {
-    img = Image{};
-}
+DummyLoadImage::DummyLoadImage(rust::Box<ImageLoader> image) : ghidra::LoadImage("jingle"), img(std::move(image)) {}
 
-DummyLoadImage::DummyLoadImage(Image image) : ghidra::LoadImage("jingle") {
+void DummyLoadImage::setImage(rust::Box<ImageLoader> image) {
     img = std::move(image);
 }
 
 void DummyLoadImage::loadFill(ghidra::uint1 *ptr, ghidra::int4 size, const ghidra::Address &addr) {
-    size_t offset = addr.getOffset();
-    for (const auto &section: img.sections) {
-        size_t start = section.base_address;
-        size_t end = start + section.data.size();
-        if (start <= offset && offset < end) {
-            size_t len = std::min((size_t) size, (size_t) end - (size_t) offset);
-            size_t start_idx = offset - start;
-            std::memcpy(ptr, &section.data[start_idx], len);
-            offset = offset + len;
-        }
-    }
-    for (size_t i = offset; i < size; ++i) {
-        ptr[i] = 0;
-    }
+    img->load_fill(rust::Slice<uint8_t>(ptr, size), addr.getOffset());
 }
 
 void DummyLoadImage::adjustVma(long adjust) {}
@@ -84,37 +73,555 @@ std::string DummyLoadImage::getArchType() const {
     return "placeholder";
 }
 
-ContextFFI::ContextFFI(rust::Str slaPath, Image image) {
-    ghidra::AttributeId::initialize();
-    ghidra::ElementId::initialize();
+void SleighTables::initialize(ghidra::DocumentStorage &store) {
+    const ghidra::Element *el = store.getTag("sleigh");
+    if (el == nullptr) {
+        throw ghidra::LowlevelError("Could not find sleigh tag");
+    }
+    restoreXml(el);
+}
+
+void SleighTables::registerContext(const std::string &name, ghidra::int4 sbit, ghidra::int4 ebit) {
+    contextFields.push_back({name, sbit, ebit});
+}
+
+const std::vector<SleighTables::ContextField> &SleighTables::getContextFields() const {
+    return contextFields;
+}
 
-    this->img = DummyLoadImage(std::move(image));
-    documentStorage = ghidra::DocumentStorage();
-    ghidra::Document *doc = documentStorage.openDocument(slaPath.operator std::string());
-    ghidra::Element *root = doc->getRoot();
-    documentStorage.registerTag(root);
-    sleigh = std::make_unique<ghidra::Sleigh>(&img, &contextDatabase);
-    sleigh->initialize(documentStorage);
+ghidra::SubtableSymbol *SleighTables::getRoot() const {
+    return root;
+}
+
+ghidra::uint4 SleighTables::getMaxDelaySlotBytes() const {
+    return maxdelayslotbytes;
+}
+
+ghidra::uint4 SleighTables::getUniqueAllocateMask() const {
+    return unique_allocatemask;
+}
+
+ghidra::int4 SleighTables::instructionLength(const ghidra::Address &baseaddr) const {
+    throw ghidra::LowlevelError("SleighTables cannot decode on their own");
+}
+
+ghidra::int4 SleighTables::oneInstruction(ghidra::PcodeEmit &emit, const ghidra::Address &baseaddr) const {
+    throw ghidra::LowlevelError("SleighTables cannot decode on their own");
+}
+
+ghidra::int4 SleighTables::printAssembly(ghidra::AssemblyEmit &emit, const ghidra::Address &baseaddr) const {
+    throw ghidra::LowlevelError("SleighTables cannot decode on their own");
+}
+
+JingleSleigh::JingleSleigh(const SleighTables &tables, ghidra::LoadImage *ld, ghidra::ContextDatabase *c_db)
+        : tables(tables), loader(ld), contextDb(c_db) {
+    for (const SleighTables::ContextField &field: tables.getContextFields()) {
+        contextDb->registerVariable(field.name, field.sbit, field.ebit);
+    }
+    flushCaches();
+}
+
+const SleighTables &JingleSleigh::getTables() const {
+    return tables;
+}
+
+void JingleSleigh::initialize(ghidra::DocumentStorage &store) {}
+
+void JingleSleigh::setContextDefault(const std::string &name, ghidra::uintm val) {
+    contextDb->setVariableDefault(name, val);
+}
+
+void JingleSleigh::allowContextSet(bool val) const {
+    contextCache->allowSet(val);
+}
+
+const ghidra::VarnodeData &JingleSleigh::getRegister(const std::string &nm) const {
+    return tables.getRegister(nm);
+}
+
+std::string JingleSleigh::getRegisterName(ghidra::AddrSpace *base, ghidra::uintb off, ghidra::int4 size) const {
+    return tables.getRegisterName(base, off, size);
+}
+
+void JingleSleigh::getAllRegisters(std::map<ghidra::VarnodeData, std::string> &reglist) const {
+    tables.getAllRegisters(reglist);
+}
 
+void JingleSleigh::getUserOpNames(std::vector<std::string> &res) const {
+    tables.getUserOpNames(res);
+}
+
+void JingleSleigh::flushCaches() {
+    // the disassembly cache refers to the context cache, so it goes first
+    discache.reset();
+    contextCache = std::make_unique<ghidra::ContextCache>(contextDb);
+    ghidra::uint4 cacheSize = 2;
+    ghidra::uint4 windowSize = 32;
+    if (tables.getMaxDelaySlotBytes() > 1 || tables.getUniqueAllocateMask() != 0) {
+        cacheSize = 8;
+        windowSize = 256;
+    }
+    discache = std::make_unique<ghidra::DisassemblyCache>(this, contextCache.get(), tables.getConstantSpace(),
+                                                          cacheSize, windowSize);
+    pcodeCache.clear();
+}
+
+ghidra::ParserContext *JingleSleigh::obtainContext(const ghidra::Address &addr, ghidra::int4 state) const {
+    ghidra::ParserContext *pos = discache->getParserContext(addr);
+    ghidra::int4 curstate = pos->getParserState();
+    if (curstate >= state) {
+        return pos;
+    }
+    if (curstate == ghidra::ParserContext::uninitialized) {
+        resolve(*pos);
+        if (state == ghidra::ParserContext::disassembly) {
+            return pos;
+        }
+    }
+    resolveHandles(*pos);
+    return pos;
+}
+
+// Resolve every constructor making up the instruction at the parser's address
+void JingleSleigh::resolve(ghidra::ParserContext &pos) const {
+    loader->loadFill(pos.getBuffer(), 16, pos.getAddr());
+    ghidra::ParserWalkerChange walker(&pos);
+    pos.deallocateState(walker);
+    pos.setDelaySlot(0);
+    walker.setOffset(0);
+    pos.clearCommits();
+    pos.loadContext();
+    ghidra::Constructor *ct = tables.getRoot()->resolve(walker);
+    walker.setConstructor(ct);
+    ct->applyContext(walker);
+    while (walker.isState()) {
+        ct = walker.getConstructor();
+        ghidra::int4 oper = walker.getOperand();
+        ghidra::int4 numoper = ct->getNumOperands();
+        while (oper < numoper) {
+            ghidra::OperandSymbol *sym = ct->getOperand(oper);
+            ghidra::uint4 off = walker.getOffset(sym->getOffsetBase()) + sym->getRelativeOffset();
+            pos.allocateOperand(oper, walker);
+            walker.setOffset(off);
+            ghidra::TripleSymbol *tsym = sym->getDefiningSymbol();
+            if (tsym != nullptr) {
+                ghidra::Constructor *subct = tsym->resolve(walker);
+                if (subct != nullptr) {
+                    walker.setConstructor(subct);
+                    subct->applyContext(walker);
+                    break;
+                }
+            }
+            walker.setCurrentLength(sym->getMinimumLength());
+            walker.popOperand();
+            oper += 1;
+        }
+        if (oper >= numoper) {
+            walker.calcCurrentLength(ct->getMinimumLength(), numoper);
+            walker.popOperand();
+            ghidra::ConstructTpl *templ = ct->getTempl();
+            if (templ != nullptr && templ->delaySlot() > 0) {
+                pos.setDelaySlot(templ->delaySlot());
+            }
+        }
+    }
+    pos.setNaddr(pos.getAddr() + pos.getLength());
+    pos.setParserState(ghidra::ParserContext::disassembly);
+}
+
+// Fill in the handles of an already resolved parse, so that p-code can be built from it
+void JingleSleigh::resolveHandles(ghidra::ParserContext &pos) const {
+    ghidra::ParserWalker walker(&pos);
+    walker.baseState();
+    while (walker.isState()) {
+        ghidra::Constructor *ct = walker.getConstructor();
+        ghidra::int4 oper = walker.getOperand();
+        ghidra::int4 numoper = ct->getNumOperands();
+        while (oper < numoper) {
+            ghidra::OperandSymbol *sym = ct->getOperand(oper);
+            walker.pushOperand(oper);
+            ghidra::TripleSymbol *triple = sym->getDefiningSymbol();
+            if (triple != nullptr) {
+                if (triple->getType() == ghidra::SleighSymbol::subtable_symbol) {
+                    break;
+                }
+                triple->getFixedHandle(walker.getParentHandle(), walker);
+            } else {
+                ghidra::PatternExpression *patexp = sym->getDefiningExpression();
+                ghidra::intb res = patexp->getValue(walker);
+                ghidra::FixedHandle &hand(walker.getParentHandle());
+                hand.space = pos.getConstSpace();
+                hand.offset_space = nullptr;
+                hand.offset_offset = (ghidra::uintb) res;
+                hand.size = 0;
+            }
+            walker.popOperand();
+            oper += 1;
+        }
+        if (oper >= numoper) {
+            ghidra::ConstructTpl *templ = ct->getTempl();
+            if (templ != nullptr) {
+                ghidra::HandleTpl *res = templ->getResult();
+                if (res != nullptr) {
+                    res->fix(walker.getParentHandle(), walker);
+                }
+            }
+            walker.popOperand();
+        }
+    }
+    pos.setParserState(ghidra::ParserContext::pcode);
+}
+
+ghidra::int4 JingleSleigh::instructionLength(const ghidra::Address &baseaddr) const {
+    ghidra::ParserContext *pos = obtainContext(baseaddr, ghidra::ParserContext::disassembly);
+    return pos->getLength();
+}
+
+ghidra::int4 JingleSleigh::printAssembly(ghidra::AssemblyEmit &emit, const ghidra::Address &baseaddr) const {
+    ghidra::ParserContext *pos = obtainContext(baseaddr, ghidra::ParserContext::disassembly);
+    ghidra::ParserWalker walker(pos);
+    walker.baseState();
+    ghidra::Constructor *ct = walker.getConstructor();
+    std::ostringstream mons;
+    ct->printMnemonic(mons, walker);
+    std::ostringstream body;
+    ct->printBody(body, walker);
+    emit.dump(baseaddr, mons.str(), body.str());
+    return pos->getLength();
+}
+
+ghidra::int4 JingleSleigh::oneInstruction(ghidra::PcodeEmit &emit, const ghidra::Address &baseaddr) const {
+    ghidra::int4 alignment = tables.getAlignment();
+    if (alignment != 1 && baseaddr.getOffset() % alignment != 0) {
+        std::ostringstream s;
+        s << "Instruction address not aligned: " << baseaddr;
+        throw ghidra::UnimplError(s.str(), 0);
+    }
+    ghidra::ParserContext *pos = obtainContext(baseaddr, ghidra::ParserContext::pcode);
+    pos->applyCommits();
+    ghidra::int4 fallOffset = pos->getLength();
+    if (pos->getDelaySlot() > 0) {
+        ghidra::int4 bytecount = 0;
+        do {
+            // pos may have been cached with an adjusted naddr, so it can't be used to find the slot
+            ghidra::ParserContext *delaypos = obtainContext(pos->getAddr() + fallOffset,
+                                                            ghidra::ParserContext::pcode);
+            delaypos->applyCommits();
+            ghidra::int4 len = delaypos->getLength();
+            fallOffset += len;
+            bytecount += len;
+        } while (bytecount < pos->getDelaySlot());
+        pos->setNaddr(pos->getAddr() + fallOffset);
+    }
+    ghidra::ParserWalker walker(pos);
+    walker.baseState();
+    pcodeCache.clear();
+    ghidra::SleighBuilder builder(&walker, discache.get(), &pcodeCache, tables.getConstantSpace(),
+                                  tables.getUniqueSpace(), tables.getUniqueAllocateMask());
+    try {
+        builder.build(walker.getConstructor()->getTempl(), -1);
+        pcodeCache.resolveRelatives();
+        pcodeCache.emit(baseaddr, &emit);
+    } catch (ghidra::UnimplError &err) {
+        std::ostringstream s;
+        s << "Instruction not implemented in pcode:\n ";
+        ghidra::ParserWalker *cur = builder.getCurrentWalker();
+        cur->baseState();
+        ghidra::Constructor *ct = cur->getConstructor();
+        cur->getAddr().printRaw(s);
+        s << ": ";
+        ct->printMnemonic(s, *cur);
+        s << "  ";
+        ct->printBody(s, *cur);
+        err.explain = s.str();
+        err.instruction_length = fallOffset;
+        throw err;
+    }
+    return fallOffset;
+}
+
+// Decoding an instruction can commit context (through globalset) to addresses that have
+// already been parsed and cached, so cached parses can't be trusted to reflect the context
+// currently in effect at their address.
+void JingleSleigh::forgetInstruction(const ghidra::Address &addr) const {
+    ghidra::ParserContext *pos = obtainContext(addr, ghidra::ParserContext::uninitialized);
+    pos->setParserState(ghidra::ParserContext::uninitialized);
+}
+
+// SLEIGH's XML parser keeps its state in globals, and the attribute/element tables are filled
+// in on first use, so those two steps are the only parts of loading a language that cannot run
+// concurrently
+static std::once_flag markupIdsInitialized;
+static std::mutex xmlParserMutex;
+
+// Constructor keeps its display section private, and its XML form is the only way to get at it
+const DisplayLayout &JingleSleigh::getDisplayLayout(ghidra::Constructor *ct) const {
+    auto found = displayLayouts.find(ct);
+    if (found != displayLayouts.end()) {
+        return found->second;
+    }
+    std::ostringstream xml;
+    ct->saveXml(xml);
+    std::istringstream in(xml.str());
+    std::unique_ptr<ghidra::Document> doc;
+    {
+        std::lock_guard<std::mutex> guard(xmlParserMutex);
+        doc.reset(ghidra::xml_tree(in));
+    }
+    const ghidra::Element *root = doc->getRoot();
+    ghidra::int4 first = std::stoi(root->getAttributeValue("first"));
+    // the operand index of each piece of the display section, or -1 for literal text
+    std::vector<ghidra::int4> pieces;
+    for (const ghidra::Element *child: root->getChildren()) {
+        if (child->getName() == "opprint") {
+            pieces.push_back(std::stoi(child->getAttributeValue("id")));
+        } else if (child->getName() == "print") {
+            pieces.push_back(-1);
+        }
+    }
+    DisplayLayout layout;
+    if (pieces.size() == 1 && pieces[0] >= 0) {
+        layout.flowthru = pieces[0];
+    }
+    // pieces up to the first whitespace make up the mnemonic
+    if (first != -1) {
+        for (size_t i = first + 1; i < pieces.size(); i++) {
+            if (pieces[i] >= 0) {
+                layout.operands.push_back(pieces[i]);
+            }
+        }
+    }
+    return displayLayouts.emplace(ct, std::move(layout)).first->second;
+}
+
+static bool isSubtable(const ghidra::OperandSymbol *sym) {
+    ghidra::TripleSymbol *triple = sym->getDefiningSymbol();
+    return triple != nullptr && triple->getType() == ghidra::SleighSymbol::subtable_symbol;
+}
+
+// Whether the operand the walker is on resolved to a value. Subtables may export nothing, in
+// which case their handle is left stale, and named values are better described by their name.
+static bool hasValue(const ghidra::OperandSymbol *sym, ghidra::ParserWalker &walker) {
+    ghidra::TripleSymbol *triple = sym->getDefiningSymbol();
+    if (triple == nullptr) {
+        return true;
+    }
+    switch (triple->getType()) {
+        case ghidra::SleighSymbol::subtable_symbol: {
+            ghidra::ConstructTpl *templ = walker.getConstructor()->getTempl();
+            return templ != nullptr && templ->getResult() != nullptr;
+        }
+        case ghidra::SleighSymbol::name_symbol:
+            return false;
+        default:
+            return walker.getParentHandle().space != nullptr;
+    }
+}
+
+static void addVarnode(rust::Vec<VarnodeInfoFFI> &out, ghidra::AddrSpace *space, ghidra::uintb offset,
+                       ghidra::uint4 size) {
+    for (const VarnodeInfoFFI &vn: out) {
+        if (vn.space->getRaw() == space && vn.offset == offset && vn.size == size) {
+            return;
+        }
+    }
+    VarnodeInfoFFI info;
+    info.space = std::make_unique<AddrSpaceHandle>(space);
+    info.offset = offset;
+    info.size = size;
+    out.push_back(std::move(info));
+}
+
+bool JingleSleigh::isRegister(ghidra::AddrSpace *space, ghidra::uintb offset, ghidra::int4 size) const {
+    return space != nullptr && !getRegisterName(space, offset, size).empty();
+}
+
+// Gather the registers a subtable operand is built from, e.g. the base and index of an address
+void JingleSleigh::collectRegisters(ghidra::ParserWalker &walker, rust::Vec<VarnodeInfoFFI> &out) const {
+    ghidra::Constructor *ct = walker.getConstructor();
+    for (ghidra::int4 i = 0; i < ct->getNumOperands(); i++) {
+        ghidra::OperandSymbol *sym = ct->getOperand(i);
+        walker.pushOperand(i);
+        const ghidra::FixedHandle &hand = walker.getParentHandle();
+        bool value = hasValue(sym, walker);
+        if (value && hand.offset_space == nullptr && isRegister(hand.space, hand.offset_offset, hand.size)) {
+            addVarnode(out, hand.space, hand.offset_offset, hand.size);
+        } else if (isSubtable(sym)) {
+            collectRegisters(walker, out);
+        }
+        walker.popOperand();
+    }
+}
+
+OperandFFI JingleSleigh::describeOperand(ghidra::ParserWalker &walker, ghidra::int4 index) const {
+    ghidra::OperandSymbol *sym = walker.getConstructor()->getOperand(index);
+    OperandFFI op;
+    std::ostringstream text;
+    sym->print(text, walker);
+    op.text = text.str();
+    op.value = 0;
+    walker.pushOperand(index);
+    const ghidra::FixedHandle &hand = walker.getParentHandle();
+    if (!hasValue(sym, walker)) {
+        op.kind = OperandKindFFI::Other;
+        if (isSubtable(sym)) {
+            collectRegisters(walker, op.varnodes);
+        }
+    } else if (hand.offset_space != nullptr) {
+        op.kind = OperandKindFFI::Memory;
+        if (isRegister(hand.offset_space, hand.offset_offset, hand.offset_size)) {
+            addVarnode(op.varnodes, hand.offset_space, hand.offset_offset, hand.offset_size);
+        }
+        if (isSubtable(sym)) {
+            collectRegisters(walker, op.varnodes);
+        }
+    } else if (hand.space->getType() == ghidra::IPTR_CONSTANT) {
+        op.kind = OperandKindFFI::Immediate;
+        op.value = hand.offset_offset;
+    } else if (isRegister(hand.space, hand.offset_offset, hand.size)) {
+        op.kind = OperandKindFFI::Register;
+        addVarnode(op.varnodes, hand.space, hand.offset_offset, hand.size);
+    } else {
+        op.kind = OperandKindFFI::Address;
+        op.value = hand.offset_offset;
+        addVarnode(op.varnodes, hand.space, hand.offset_offset, hand.size);
+    }
+    walker.popOperand();
+    return op;
+}
+
+// The operands of the instruction at the given address, in the order printAssembly displays them
+rust::Vec<OperandFFI> JingleSleigh::getOperands(const ghidra::Address &addr) const {
+    ghidra::ParserContext *pos = obtainContext(addr, ghidra::ParserContext::pcode);
+    ghidra::ParserWalker walker(pos);
+    walker.baseState();
+    const DisplayLayout *layout = &getDisplayLayout(walker.getConstructor());
+    while (layout->flowthru != -1 && isSubtable(walker.getConstructor()->getOperand(layout->flowthru))) {
+        walker.pushOperand(layout->flowthru);
+        layout = &getDisplayLayout(walker.getConstructor());
+    }
+    rust::Vec<OperandFFI> operands;
+    for (ghidra::int4 index: layout->operands) {
+        operands.push_back(describeOperand(walker, index));
+    }
+    return operands;
+}
+
+// The document is only needed to build the tables, which keep nothing pointing into it
+LanguageFFI::LanguageFFI(rust::Str slaPath) {
+    std::call_once(markupIdsInitialized, [] {
+        ghidra::AttributeId::initialize();
+        ghidra::ElementId::initialize();
+    });
+
+    ghidra::DocumentStorage documentStorage;
+    ghidra::Document *doc;
+    {
+        std::lock_guard<std::mutex> guard(xmlParserMutex);
+        doc = documentStorage.openDocument(slaPath.operator std::string());
+    }
+    documentStorage.registerTag(doc->getRoot());
+    tables.initialize(documentStorage);
+}
+
+const SleighTables &LanguageFFI::getTables() const {
+    return tables;
+}
+
+std::shared_ptr<LanguageFFI> makeLanguage(rust::Str slaPath) {
+    return std::make_shared<LanguageFFI>(slaPath);
+}
+
+ContextFFI::ContextFFI(std::shared_ptr<LanguageFFI> lang, rust::Box<ImageLoader> image)
+        : img(std::move(image)), language(std::move(lang)) {
+    sleigh = std::make_unique<JingleSleigh>(language->getTables(), &img, &contextDatabase);
+}
+
+void ContextFFI::invalidateCaches() {
+    stale = stale || decoded;
+}
+
+// Decoding is const on the Rust side, so rebuilding the caches beforehand has to be too
+void ContextFFI::flushCaches() const {
+    if (stale) {
+        sleigh->flushCaches();
+        const_cast<ContextFFI *>(this)->stale = false;
+    }
 }
 
 void ContextFFI::set_initial_context(rust::Str name, uint32_t val) {
     sleigh->setContextDefault(name.operator std::string(), val);
+    invalidateCaches();
+}
+
+// ContextInternal's overrides hide the by-name accessors of ContextDatabase
+uint32_t ContextFFI::getContextDefault(rust::Str name) const {
+    const ghidra::ContextDatabase &db = contextDatabase;
+    return db.getDefaultValue(name.operator std::string());
+}
+
+void ContextFFI::setContextRange(rust::Str name, uint64_t start, uint64_t end, uint32_t val) {
+    ghidra::AddrSpace *space = language->getTables().getDefaultCodeSpace();
+    contextDatabase.setVariableRegion(name.operator std::string(), ghidra::Address(space, start),
+                                      ghidra::Address(space, end), val);
+    invalidateCaches();
+}
+
+uint32_t ContextFFI::getContextValue(rust::Str name, uint64_t offset) const {
+    ghidra::Address addr = ghidra::Address(language->getTables().getDefaultCodeSpace(), offset);
+    const ghidra::ContextDatabase &db = contextDatabase;
+    return db.getVariable(name.operator std::string(), addr);
+}
+
+// The raw words of every context variable in effect at the given address, which together
+// determine how the instruction there decodes
+rust::Vec<uint32_t> ContextFFI::getContextWords(uint64_t offset) const {
+    ghidra::Address addr = ghidra::Address(language->getTables().getDefaultCodeSpace(), offset);
+    const ghidra::ContextDatabase &db = contextDatabase;
+    const ghidra::uintm *words = db.getContext(addr);
+    rust::Vec<uint32_t> result;
+    result.reserve(db.getContextSize());
+    for (ghidra::int4 i = 0; i < db.getContextSize(); i++) {
+        result.push_back(words[i]);
+    }
+    return result;
+}
+
+// Sleigh holds on to the address of the load image, so the image is replaced in place
+void ContextFFI::setImage(rust::Box<ImageLoader> image) {
+    img.setImage(std::move(image));
+    invalidateCaches();
 }
 
 InstructionFFI ContextFFI::get_one_instruction(uint64_t offset) const {
     PcodeCacher pcode;
     AssemblyCacher assembly;
-    ghidra::Address a = ghidra::Address(sleigh->getDefaultCodeSpace(), offset);
+    flushCaches();
+    ghidra::Address a = ghidra::Address(language->getTables().getDefaultCodeSpace(), offset);
+    decoded = true;
+    sleigh->forgetInstruction(a);
     sleigh->printAssembly(assembly, a);
-    sleigh->oneInstruction(pcode, a);
-    size_t length = sleigh->instructionLength(a);
+    // for instructions with delay slots, this covers the delay slot instructions as well, and
+    // the emitted pcode already has their semantics in the right place
+    size_t length = sleigh->oneInstruction(pcode, a);
     InstructionFFI i;
-    Disassembly d;
+    DisassemblyFFI d;
     i.ops = std::move(pcode.ops);
     d.args = std::move(assembly.body);
     d.mnemonic = std::move(assembly.mnem);
+    d.operands = sleigh->getOperands(a);
     i.disassembly = std::move(d);
+    size_t slotOffset = sleigh->instructionLength(a);
+    while (slotOffset < length) {
+        AssemblyCacher slot;
+        ghidra::Address slotAddr = a + slotOffset;
+        slotOffset += sleigh->printAssembly(slot, slotAddr);
+        DisassemblyFFI slotDisassembly;
+        slotDisassembly.args = std::move(slot.body);
+        slotDisassembly.mnemonic = std::move(slot.mnem);
+        slotDisassembly.operands = sleigh->getOperands(slotAddr);
+        i.delay_slots.push_back(std::move(slotDisassembly));
+    }
     i.address = offset;
     i.length = length;
     return i;
@@ -122,29 +629,15 @@ InstructionFFI ContextFFI::get_one_instruction(uint64_t offset) const {
 
 
 std::shared_ptr<AddrSpaceHandle> ContextFFI::getSpaceByIndex(ghidra::int4 idx) const {
-    return std::make_shared<AddrSpaceHandle>(sleigh->getSpace(idx));
+    return std::make_shared<AddrSpaceHandle>(language->getTables().getSpace(idx));
 }
 
 ghidra::int4 ContextFFI::getNumSpaces() const {
-    return sleigh->numSpaces();
-}
-
-VarnodeInfoFFI ContextFFI::getRegister(rust::Str name) const {
-    ghidra::VarnodeData vn = sleigh->getRegister(name.operator std::string());
-    VarnodeInfoFFI info;
-    info.space = std::make_unique<AddrSpaceHandle>(vn.space);
-    info.size = vn.size;
-    info.offset = vn.offset;
-    return info;
-};
-
-rust::Str ContextFFI::getRegisterName(VarnodeInfoFFI vn) const {
-    std::string name = sleigh->getRegisterName(vn.space->getRaw(), vn.offset, vn.size);
-    return {name};
+    return language->getTables().numSpaces();
 }
 
-std::unique_ptr<ContextFFI> makeContext(rust::Str slaPath, Image img) {
-    return std::make_unique<ContextFFI>(slaPath, std::move(img));
+std::unique_ptr<ContextFFI> makeContext(std::shared_ptr<LanguageFFI> language, rust::Box<ImageLoader> img) {
+    return std::make_unique<ContextFFI>(std::move(language), std::move(img));
 }
 
 VarnodeInfoFFI varnodeToFFI(ghidra::VarnodeData vn) {
@@ -167,4 +660,14 @@ rust::Vec<RegisterInfoFFI> ContextFFI::getRegisters() const {
     sleigh->getAllRegisters(reglist);
     std::transform(reglist.begin(), reglist.end(), std::back_inserter(v), collectRegInfo);
     return v;
-}
\ No newline at end of file
+}
+
+rust::Vec<rust::String> ContextFFI::getUserOpNames() const {
+    std::vector<std::string> names;
+    rust::Vec<rust::String> v;
+    sleigh->getUserOpNames(names);
+    for (const auto &name: names) {
+        v.emplace_back(name);
+    }
+    return v;
+}
@@ -1,21 +1,27 @@
 #ifndef JINGLE_SLEIGH_CONTEXT_H
 #define JINGLE_SLEIGH_CONTEXT_H
 
+#include <memory>
+#include <unordered_map>
+#include <vector>
 #include "rust/cxx.h"
 #include "sleigh/types.h"
 #include "addrspace_handle.h"
 #include "jingle_sleigh/src/ffi/instruction.rs.h"
 #include "sleigh/globalcontext.hh"
 #include "sleigh/sleigh.hh"
-#include "jingle_sleigh/src/ffi/image.rs.h"
 #include "sleigh/loadimage.hh"
 
+// Defined in Rust; reading bytes out of it goes back across the bridge, so that section bytes
+// are never copied into C++
+struct ImageLoader;
+
 class DummyLoadImage : public ghidra::LoadImage {
-    Image img;
+    rust::Box<ImageLoader> img;
 public:
-    DummyLoadImage();
+    explicit DummyLoadImage(rust::Box<ImageLoader> img);
 
-    DummyLoadImage(Image img);
+    void setImage(rust::Box<ImageLoader> img);
 
     void loadFill(ghidra::uint1 *ptr, ghidra::int4 size, const ghidra::Address &addr) override;
 
@@ -25,18 +31,157 @@ public:
 
 };
 
+// Which operands of a constructor appear in its display section, and in what order
+struct DisplayLayout {
+    // The operand a constructor whose display is a lone subtable defers its display to
+    ghidra::int4 flowthru = -1;
+    std::vector<ghidra::int4> operands;
+};
+
+// The decoding tables of a .sla file: its constructors, symbols, spaces and registers. Every
+// decoding entry point of ghidra::Sleigh is const, and the only state decoding mutates is held
+// through the loader, context database and caches it points to; the tables themselves are
+// only read. So these are built once per language and shared by every context decoding it,
+// including contexts on different threads, each of which brings its own loader, context
+// database and caches in a JingleSleigh.
+class SleighTables : public ghidra::SleighBase {
+public:
+    struct ContextField {
+        std::string name;
+        ghidra::int4 sbit;
+        ghidra::int4 ebit;
+    };
+private:
+    std::vector<ContextField> contextFields;
+public:
+    void initialize(ghidra::DocumentStorage &store) override;
+
+    void registerContext(const std::string &name, ghidra::int4 sbit, ghidra::int4 ebit) override;
+
+    // The context variables of the language, to be registered with each context's database
+    const std::vector<ContextField> &getContextFields() const;
+
+    ghidra::SubtableSymbol *getRoot() const;
+
+    ghidra::uint4 getMaxDelaySlotBytes() const;
+
+    ghidra::uint4 getUniqueAllocateMask() const;
+
+    // Decoding needs a context; see JingleSleigh
+    ghidra::int4 instructionLength(const ghidra::Address &baseaddr) const override;
+
+    ghidra::int4 oneInstruction(ghidra::PcodeEmit &emit, const ghidra::Address &baseaddr) const override;
+
+    ghidra::int4 printAssembly(ghidra::AssemblyEmit &emit, const ghidra::Address &baseaddr) const override;
+};
+
+// A parsed .sla file. It is never modified once constructed, so one can back any number of
+// contexts, including contexts on different threads.
+class LanguageFFI {
+    SleighTables tables;
+public:
+    explicit LanguageFFI(rust::Str slaPath);
+
+    const SleighTables &getTables() const;
+};
+
+std::shared_ptr<LanguageFFI> makeLanguage(rust::Str slaPath);
+
+// The per-context half of ghidra::Sleigh: decodes instructions using a language's shared
+// tables, reading bytes from its own loader and context values from its own database. The
+// decoding methods follow ghidra::Sleigh's; they are repeated here because Sleigh keeps its
+// tables and its caches in the same object.
+//
+// Register, userop and space queries are answered by the tables. The address space manager
+// this inherits from Translate is empty; ask getTables() for spaces instead.
+class JingleSleigh : public ghidra::Translate {
+    const SleighTables &tables;
+    ghidra::LoadImage *loader;
+    ghidra::ContextDatabase *contextDb;
+    std::unique_ptr<ghidra::ContextCache> contextCache;
+    std::unique_ptr<ghidra::DisassemblyCache> discache;
+    mutable ghidra::PcodeCacher pcodeCache;
+    mutable std::unordered_map<const ghidra::Constructor *, DisplayLayout> displayLayouts;
+
+    ghidra::ParserContext *obtainContext(const ghidra::Address &addr, ghidra::int4 state) const;
+
+    void resolve(ghidra::ParserContext &pos) const;
+
+    void resolveHandles(ghidra::ParserContext &pos) const;
+
+    const DisplayLayout &getDisplayLayout(ghidra::Constructor *ct) const;
+
+    OperandFFI describeOperand(ghidra::ParserWalker &walker, ghidra::int4 index) const;
+
+    void collectRegisters(ghidra::ParserWalker &walker, rust::Vec<VarnodeInfoFFI> &out) const;
+
+    bool isRegister(ghidra::AddrSpace *space, ghidra::uintb offset, ghidra::int4 size) const;
+public:
+    JingleSleigh(const SleighTables &tables, ghidra::LoadImage *ld, ghidra::ContextDatabase *c_db);
+
+    const SleighTables &getTables() const;
+
+    // The tables are built by SleighTables
+    void initialize(ghidra::DocumentStorage &store) override;
+
+    void setContextDefault(const std::string &name, ghidra::uintm val) override;
+
+    void allowContextSet(bool val) const override;
+
+    const ghidra::VarnodeData &getRegister(const std::string &nm) const override;
+
+    std::string getRegisterName(ghidra::AddrSpace *base, ghidra::uintb off, ghidra::int4 size) const override;
+
+    void getAllRegisters(std::map<ghidra::VarnodeData, std::string> &reglist) const override;
+
+    void getUserOpNames(std::vector<std::string> &res) const override;
+
+    ghidra::int4 instructionLength(const ghidra::Address &baseaddr) const override;
+
+    ghidra::int4 oneInstruction(ghidra::PcodeEmit &emit, const ghidra::Address &baseaddr) const override;
+
+    ghidra::int4 printAssembly(ghidra::AssemblyEmit &emit, const ghidra::Address &baseaddr) const override;
+
+    // Drop every cached parse and context value. Cheap, as the tables are left alone.
+    void flushCaches();
+
+    void forgetInstruction(const ghidra::Address &addr) const;
+
+    rust::Vec<OperandFFI> getOperands(const ghidra::Address &addr) const;
+};
 
 class ContextFFI {
     DummyLoadImage img;
-    ghidra::DocumentStorage documentStorage;
+    std::shared_ptr<LanguageFFI> language;
     ghidra::ContextInternal contextDatabase;
-    std::unique_ptr<ghidra::Sleigh> sleigh;
+    std::unique_ptr<JingleSleigh> sleigh;
+    // Whether anything has been decoded since SLEIGH's caches were last built. Until then there
+    // is nothing cached that changing the context or image could invalidate.
+    mutable bool decoded = false;
+    // Set when the context or image changed after decoding. Rebuilding the caches throws away
+    // every parse, so it is done once, right before the next decode, however many changes were
+    // made.
+    bool stale = false;
+
+    void invalidateCaches();
+
+    void flushCaches() const;
 public:
 
-    explicit ContextFFI(rust::Str slaPath, Image img);
+    ContextFFI(std::shared_ptr<LanguageFFI> language, rust::Box<ImageLoader> img);
 
     void set_initial_context(rust::Str name, uint32_t val);
 
+    uint32_t getContextDefault(rust::Str name) const;
+
+    void setContextRange(rust::Str name, uint64_t start, uint64_t end, uint32_t val);
+
+    uint32_t getContextValue(rust::Str name, uint64_t offset) const;
+
+    rust::Vec<uint32_t> getContextWords(uint64_t offset) const;
+
+    void setImage(rust::Box<ImageLoader> img);
+
     InstructionFFI get_one_instruction(uint64_t offset) const;
 
 
@@ -44,13 +189,11 @@ public:
 
     int getNumSpaces() const;
 
-    VarnodeInfoFFI getRegister(rust::Str name) const;
-
-    rust::Str getRegisterName(VarnodeInfoFFI name) const;
-
     rust::Vec<RegisterInfoFFI> getRegisters() const;
+
+    rust::Vec<rust::String> getUserOpNames() const;
 };
 
-std::unique_ptr<ContextFFI> makeContext(rust::Str slaPath, Image img);
+std::unique_ptr<ContextFFI> makeContext(std::shared_ptr<LanguageFFI> language, rust::Box<ImageLoader> img);
 
 #endif //JINGLE_SLEIGH_CONTEXT_H
@@ -1,20 +1,38 @@
-
 #ifndef JINGLE_EXCEPTION_H
 #define JINGLE_EXCEPTION_H
 
+#include <string>
 #include "sleigh/error.hh"
+#include "sleigh/context.hh"
+#include "sleigh/loadimage.hh"
+#include "sleigh/translate.hh"
+#include "sleigh/xml.hh"
 
 namespace rust {
     namespace behavior {
 
+    // cxx only carries a message across the bridge, so the type of the SLEIGH exception is
+    // prefixed onto it; SleighErrorClass::from_what splits it back off on the Rust side
     template <typename Try, typename Fail>
         static void trycatch(Try &&func, Fail &&fail) noexcept try {
           func();
+        } catch (const ghidra::UnimplError &e) {
+          fail("UnimplError: " + e.explain);
+        } catch (const ghidra::BadDataError &e) {
+          fail("BadDataError: " + e.explain);
+        } catch (const ghidra::DataUnavailError &e) {
+          fail("DataUnavailError: " + e.explain);
+        } catch (const ghidra::SleighError &e) {
+          fail("SleighError: " + e.explain);
+        } catch (const ghidra::ParseError &e) {
+          fail("ParseError: " + e.explain);
         } catch (const ghidra::LowlevelError &e) {
-          fail(e.explain);
+          fail("LowlevelError: " + e.explain);
+        } catch (const ghidra::DecoderError &e) {
+          fail("DecoderError: " + e.explain);
         } catch (const std::exception &e) {
-          fail(e.what());
+          fail(std::string("exception: ") + e.what());
         }
     }
 }
-#endif //JINGLE_EXCEPTION_H
\ No newline at end of file
+#endif //JINGLE_EXCEPTION_H
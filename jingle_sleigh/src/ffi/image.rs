@@ -1,21 +1,87 @@
-#[cxx::bridge]
-pub(crate) mod bridge {
-    #[derive(Debug, Clone)]
-    pub struct Perms {
-        pub(crate) read: bool,
-        pub(crate) write: bool,
-        pub(crate) exec: bool,
-    }
+use crate::context::fetch::FetchResolver;
+use crate::context::{FetchPolicy, Image};
+
+/// What `SLEIGH`'s load image reads instruction bytes out of: the image being decoded, along
+/// with the policy for fetches that fall outside of it
+pub(crate) struct ImageLoader {
+    pub(crate) image: Image,
+    pub(crate) fetch: FetchResolver,
+}
 
-    #[derive(Debug, Clone)]
-    pub struct ImageSection {
-        pub(crate) data: Vec<u8>,
-        pub(crate) base_address: usize,
-        pub(crate) perms: Perms,
+impl ImageLoader {
+    /// Fill `buf` with the bytes of the image starting at `addr`. Bytes not covered by any
+    /// section are supplied by the fetch policy's callback if there is one, and are zero
+    /// otherwise; whether reading them is an error is decided once the instruction they
+    /// belong to has been decoded.
+    pub(crate) fn load_fill(&self, buf: &mut [u8], addr: u64) {
+        buf.fill(0);
+        let start = addr as usize;
+        let end = start.saturating_add(buf.len());
+        for section in &self.image.sections {
+            let range = section.range();
+            let (lo, hi) = (start.max(range.start), end.min(range.end));
+            if lo < hi {
+                buf[lo - start..hi - start]
+                    .copy_from_slice(&section.data[lo - range.start..hi - range.start]);
+            }
+        }
+        if let FetchPolicy::Callback(_) = self.fetch.policy() {
+            for (idx, byte) in buf.iter_mut().enumerate() {
+                let addr = addr.wrapping_add(idx as u64);
+                if !self.image.contains_address(addr as usize) {
+                    *byte = self.fetch.resolve(addr).unwrap_or(0);
+                }
+            }
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::fetch::FetchResolver;
+    use crate::context::{FetchPolicy, Image, ImageSection, Perms};
+    use crate::ffi::image::ImageLoader;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_load_fill() {
+        let image = Image {
+            sections: vec![
+                ImageSection::new(0x10, vec![1, 2, 3, 4], Perms::RX),
+                ImageSection::new(0x16, vec![5, 6], Perms::RX),
+            ],
+            symbols: Default::default(),
+        };
+        let mut loader = ImageLoader {
+            image,
+            fetch: FetchResolver::default(),
+        };
+        let mut buf = [0xff; 8];
+        loader.load_fill(&mut buf, 0x12);
+        assert_eq!(buf, [3, 4, 0, 0, 5, 6, 0, 0]);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        loader.fetch = FetchResolver::new(FetchPolicy::Callback(Arc::new(move |addr| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            (addr == 0x14).then_some(0xaa)
+        })));
+        loader.load_fill(&mut buf, 0x12);
+        assert_eq!(buf, [3, 4, 0xaa, 0, 5, 6, 0, 0]);
+        // each unmapped byte is only asked about once
+        loader.load_fill(&mut buf, 0x12);
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+        assert_eq!(loader.fetch.resolve(0x14), Some(0xaa));
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
 
-    #[derive(Debug, Clone)]
-    pub struct Image {
-        pub sections: Vec<ImageSection>,
+        // a panicking callback reads as unmapped rather than unwinding into SLEIGH
+        loader.fetch = FetchResolver::new(FetchPolicy::Callback(Arc::new(|addr| {
+            assert_ne!(addr, 0x14);
+            Some(0xbb)
+        })));
+        loader.load_fill(&mut buf, 0x12);
+        assert_eq!(buf, [3, 4, 0, 0xbb, 5, 6, 0xbb, 0xbb]);
+        assert_eq!(loader.fetch.resolve(0x14), None);
     }
 }
@@ -1,12 +1,3 @@
-use crate::ffi::instruction::bridge::Disassembly;
-use std::fmt::{Display, Formatter};
-
-impl Display for Disassembly {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.mnemonic, self.args)
-    }
-}
-
 #[cxx::bridge]
 pub(crate) mod bridge {
     /// An FFI-friendly representation of a [`VarNode`](crate::VarNode)
@@ -33,23 +24,37 @@ pub(crate) mod bridge {
         space: SharedPtr<AddrSpaceHandle>,
     }
 
-    /// A display-friendly representation of an instruction, generated by SLEIGH. Sleigh does not
-    /// provide any tokenization of instruction operands, so they are all contained as a string
-    /// representation in the [args](Self::args) attribute.
-    #[derive(Clone, Debug, Serialize, Deserialize)]
-    pub struct Disassembly {
-        /// SLEIGH's name for an ISA instruction
-        pub mnemonic: String,
-        /// A combined string representation of all the arguments of the instruction.
-        pub args: String,
+    /// An FFI-friendly representation of [`OperandKind`](crate::OperandKind)
+    pub(crate) enum OperandKindFFI {
+        Register,
+        Immediate,
+        Address,
+        Memory,
+        Other,
+    }
+
+    /// An FFI-friendly representation of an [`Operand`](crate::Operand)
+    pub(crate) struct OperandFFI {
+        kind: OperandKindFFI,
+        text: String,
+        value: u64,
+        varnodes: Vec<VarnodeInfoFFI>,
+    }
+
+    /// An FFI-friendly representation of a [`Disassembly`](crate::Disassembly)
+    pub(crate) struct DisassemblyFFI {
+        mnemonic: String,
+        args: String,
+        operands: Vec<OperandFFI>,
     }
 
     /// An FFI-friendly representation of a single [`Instruction`](crate::Instruction)
     pub(crate) struct InstructionFFI {
-        disassembly: Disassembly,
+        disassembly: DisassemblyFFI,
         address: u64,
         ops: Vec<RawPcodeOp>,
         length: usize,
+        delay_slots: Vec<DisassemblyFFI>,
     }
 
     unsafe extern "C++" {
@@ -60,4 +65,7 @@ pub(crate) mod bridge {
 
         type AddrSpaceHandle = crate::ffi::addrspace::bridge::AddrSpaceHandle;
     }
+
+    // Only returned from `ContextFFI`, so the vector glue must be requested explicitly
+    impl Vec<RegisterInfoFFI> {}
 }
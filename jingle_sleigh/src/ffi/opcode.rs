@@ -1,5 +1,93 @@
 pub use bridge::OpCode;
 
+impl OpCode {
+    /// The name Ghidra uses for this opcode when printing raw p-code, e.g. `INT_ADD`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            OpCode::CPUI_COPY => "COPY",
+            OpCode::CPUI_LOAD => "LOAD",
+            OpCode::CPUI_STORE => "STORE",
+            OpCode::CPUI_BRANCH => "BRANCH",
+            OpCode::CPUI_CBRANCH => "CBRANCH",
+            OpCode::CPUI_BRANCHIND => "BRANCHIND",
+            OpCode::CPUI_CALL => "CALL",
+            OpCode::CPUI_CALLIND => "CALLIND",
+            OpCode::CPUI_CALLOTHER => "CALLOTHER",
+            OpCode::CPUI_RETURN => "RETURN",
+            OpCode::CPUI_INT_EQUAL => "INT_EQUAL",
+            OpCode::CPUI_INT_NOTEQUAL => "INT_NOTEQUAL",
+            OpCode::CPUI_INT_SLESS => "INT_SLESS",
+            OpCode::CPUI_INT_SLESSEQUAL => "INT_SLESSEQUAL",
+            OpCode::CPUI_INT_LESS => "INT_LESS",
+            OpCode::CPUI_INT_LESSEQUAL => "INT_LESSEQUAL",
+            OpCode::CPUI_INT_ZEXT => "INT_ZEXT",
+            OpCode::CPUI_INT_SEXT => "INT_SEXT",
+            OpCode::CPUI_INT_ADD => "INT_ADD",
+            OpCode::CPUI_INT_SUB => "INT_SUB",
+            OpCode::CPUI_INT_CARRY => "INT_CARRY",
+            OpCode::CPUI_INT_SCARRY => "INT_SCARRY",
+            OpCode::CPUI_INT_SBORROW => "INT_SBORROW",
+            OpCode::CPUI_INT_2COMP => "INT_2COMP",
+            OpCode::CPUI_INT_NEGATE => "INT_NEGATE",
+            OpCode::CPUI_INT_XOR => "INT_XOR",
+            OpCode::CPUI_INT_AND => "INT_AND",
+            OpCode::CPUI_INT_OR => "INT_OR",
+            OpCode::CPUI_INT_LEFT => "INT_LEFT",
+            OpCode::CPUI_INT_RIGHT => "INT_RIGHT",
+            OpCode::CPUI_INT_SRIGHT => "INT_SRIGHT",
+            OpCode::CPUI_INT_MULT => "INT_MULT",
+            OpCode::CPUI_INT_DIV => "INT_DIV",
+            OpCode::CPUI_INT_SDIV => "INT_SDIV",
+            OpCode::CPUI_INT_REM => "INT_REM",
+            OpCode::CPUI_INT_SREM => "INT_SREM",
+            OpCode::CPUI_BOOL_NEGATE => "BOOL_NEGATE",
+            OpCode::CPUI_BOOL_XOR => "BOOL_XOR",
+            OpCode::CPUI_BOOL_AND => "BOOL_AND",
+            OpCode::CPUI_BOOL_OR => "BOOL_OR",
+            OpCode::CPUI_FLOAT_EQUAL => "FLOAT_EQUAL",
+            OpCode::CPUI_FLOAT_NOTEQUAL => "FLOAT_NOTEQUAL",
+            OpCode::CPUI_FLOAT_LESS => "FLOAT_LESS",
+            OpCode::CPUI_FLOAT_LESSEQUAL => "FLOAT_LESSEQUAL",
+            OpCode::CPUI_FLOAT_NAN => "FLOAT_NAN",
+            OpCode::CPUI_FLOAT_ADD => "FLOAT_ADD",
+            OpCode::CPUI_FLOAT_DIV => "FLOAT_DIV",
+            OpCode::CPUI_FLOAT_MULT => "FLOAT_MULT",
+            OpCode::CPUI_FLOAT_SUB => "FLOAT_SUB",
+            OpCode::CPUI_FLOAT_NEG => "FLOAT_NEG",
+            OpCode::CPUI_FLOAT_ABS => "FLOAT_ABS",
+            OpCode::CPUI_FLOAT_SQRT => "FLOAT_SQRT",
+            OpCode::CPUI_FLOAT_INT2FLOAT => "INT2FLOAT",
+            OpCode::CPUI_FLOAT_FLOAT2FLOAT => "FLOAT2FLOAT",
+            OpCode::CPUI_FLOAT_TRUNC => "TRUNC",
+            OpCode::CPUI_FLOAT_CEIL => "CEIL",
+            OpCode::CPUI_FLOAT_FLOOR => "FLOOR",
+            OpCode::CPUI_FLOAT_ROUND => "ROUND",
+            OpCode::CPUI_MULTIEQUAL => "MULTIEQUAL",
+            OpCode::CPUI_INDIRECT => "INDIRECT",
+            OpCode::CPUI_PIECE => "PIECE",
+            OpCode::CPUI_SUBPIECE => "SUBPIECE",
+            OpCode::CPUI_CAST => "CAST",
+            OpCode::CPUI_PTRADD => "PTRADD",
+            OpCode::CPUI_PTRSUB => "PTRSUB",
+            OpCode::CPUI_SEGMENTOP => "SEGMENTOP",
+            OpCode::CPUI_CPOOLREF => "CPOOLREF",
+            OpCode::CPUI_NEW => "NEW",
+            OpCode::CPUI_INSERT => "INSERT",
+            OpCode::CPUI_EXTRACT => "EXTRACT",
+            OpCode::CPUI_POPCOUNT => "POPCOUNT",
+            OpCode::CPUI_LZCOUNT => "LZCOUNT",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// The opcode Ghidra prints with the given name, the inverse of [OpCode::name]
+    pub fn from_name(name: &str) -> Option<OpCode> {
+        (OpCode::CPUI_COPY.repr..OpCode::CPUI_MAX.repr)
+            .map(|repr| OpCode { repr })
+            .find(|op| op.name() != "UNKNOWN" && op.name() == name)
+    }
+}
+
 #[cxx::bridge]
 pub(crate) mod bridge {
     #[namespace = "ghidra"]
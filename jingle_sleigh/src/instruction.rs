@@ -1,21 +1,29 @@
+use crate::disassembly::Disassembly;
 use crate::error::JingleSleighError;
-pub use crate::ffi::instruction::bridge::Disassembly;
 use crate::ffi::instruction::bridge::InstructionFFI;
 use crate::pcode::display::PcodeOperationDisplay;
 use crate::pcode::PcodeOperation;
-use crate::space::SpaceManager;
+use crate::space::{SpaceManager, SymbolManager, UserOpManager};
 use crate::OpCode;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
-/// A rust representation of a SLEIGH assembly instruction
+/// A rust representation of a SLEIGH assembly instruction.
+///
+/// For architectures with delay slots (e.g. MIPS, SPARC, SuperH), an instruction with a delay
+/// slot is bundled with the instructions in its delay slot: its PCODE includes their semantics
+/// and its length covers their encodings.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Instruction {
     pub disassembly: Disassembly,
+    /// The disassembly of the instructions in this instruction's delay slot, in order
+    #[serde(default)]
+    pub delay_slots: Vec<Disassembly>,
     /// The PCODE semantics of this instruction
     /// todo: this should someday be a graph instead of a vec
     pub ops: Vec<PcodeOperation>,
-    /// The number of bytes taken up by the encoding of this assembly instruction
+    /// The number of bytes taken up by the encoding of this assembly instruction, including any
+    /// delay slot instructions
     pub length: usize,
     /// The address this instruction was read from
     pub address: u64,
@@ -25,6 +33,7 @@ pub struct Instruction {
 /// without requiring lots of pcode metadata to be stored in the instruction itself
 pub struct InstructionDisplay<'a, T: SpaceManager> {
     pub disassembly: Disassembly,
+    pub delay_slots: Vec<Disassembly>,
     pub ops: Vec<PcodeOperationDisplay<'a, T>>,
 }
 
@@ -39,10 +48,30 @@ impl Instruction {
         }
         Ok(InstructionDisplay {
             disassembly: self.disassembly.clone(),
+            delay_slots: self.delay_slots.clone(),
             ops,
         })
     }
 
+    /// Like [Instruction::display], but `CALLOTHER`s always take the name of the userop they invoke
+    /// from `ctx`, even if it does not offer itself through
+    /// [SpaceManager::as_userop_manager](crate::SpaceManager::as_userop_manager)
+    pub fn display_with_userops<'a, T: UserOpManager>(
+        &'a self,
+        ctx: &'a T,
+    ) -> Result<InstructionDisplay<'a, T>, JingleSleighError> {
+        Ok(self.display(ctx)?.with_userops(ctx))
+    }
+
+    /// Like [Instruction::display], but the targets of direct branches are shown relative
+    /// to the nearest symbol
+    pub fn display_with_symbols<'a, T: SpaceManager + SymbolManager>(
+        &'a self,
+        ctx: &'a T,
+    ) -> Result<InstructionDisplay<'a, T>, JingleSleighError> {
+        Ok(self.display(ctx)?.with_symbols(ctx))
+    }
+
     pub fn next_addr(&self) -> u64 {
         self.address + self.length as u64
     }
@@ -61,9 +90,35 @@ impl Instruction {
     }
 }
 
+impl<'a, T: SpaceManager> InstructionDisplay<'a, T> {
+    /// Show `CALLOTHER`s with the name of the userop they invoke rather than its index
+    pub fn with_userops<U: UserOpManager + ?Sized>(mut self, userops: &U) -> Self {
+        self.ops = self
+            .ops
+            .into_iter()
+            .map(|op| op.with_userops(userops))
+            .collect();
+        self
+    }
+
+    /// Show the targets of direct branches and calls as `symbol+offset`
+    pub fn with_symbols<S: SymbolManager>(mut self, symbols: &S) -> Self {
+        self.ops = self
+            .ops
+            .into_iter()
+            .map(|op| op.with_symbols(symbols))
+            .collect();
+        self
+    }
+}
+
 impl<'a, T: SpaceManager> Display for InstructionDisplay<'a, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{} {}", self.disassembly.mnemonic, self.disassembly.args)?;
+        // ghidra marks delay slot instructions with a leading underscore
+        for slot in &self.delay_slots {
+            writeln!(f, "_{} {}", slot.mnemonic, slot.args)?;
+        }
         for x in &self.ops {
             writeln!(f, "{}", x)?;
         }
@@ -75,7 +130,12 @@ impl From<InstructionFFI> for Instruction {
     fn from(value: InstructionFFI) -> Self {
         let ops = value.ops.into_iter().map(PcodeOperation::from).collect();
         Instruction {
-            disassembly: value.disassembly,
+            disassembly: value.disassembly.into(),
+            delay_slots: value
+                .delay_slots
+                .into_iter()
+                .map(Disassembly::from)
+                .collect(),
             ops,
             length: value.length,
             address: value.address,
@@ -1,21 +1,200 @@
 pub mod context;
+mod disassembly;
+pub mod emulator;
 pub(crate) mod error;
 
 pub(crate) mod ffi;
 pub(crate) mod instruction;
 pub(crate) mod pcode;
+pub(crate) mod portable;
 pub(crate) mod space;
 pub(crate) mod varnode;
 
-pub use error::JingleSleighError;
+pub use disassembly::{Disassembly, Operand, OperandKind};
+pub use error::{JingleSleighError, SleighErrorClass};
 pub use ffi::addrspace::bridge::SpaceType;
 pub use instruction::*;
 pub use pcode::*;
-pub use space::{RegisterManager, SleighEndianness, SpaceInfo, SpaceManager};
+pub use portable::PortableInstructions;
+pub use space::{
+    RegisterManager, SleighEndianness, SpaceInfo, SpaceManager, SymbolManager, UserOpManager,
+};
 pub use varnode::display::*;
 pub use varnode::{create_varnode, GeneralizedVarNode, IndirectVarNode, VarNode};
 
-#[cfg(test)]
-mod tests {
-    pub(crate) const SLEIGH_ARCH: &str = "x86:LE:64:default";
+/// Fixtures shared by the tests of this crate and of crates built on it. Not part of the API.
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+pub mod tests {
+    use crate::context::registers::RegisterTable;
+    use crate::space::{RegisterManager, SpaceManager, UserOpManager};
+    use crate::{SleighEndianness, SpaceInfo, SpaceType, VarNode};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub const SLEIGH_ARCH: &str = "x86:LE:64:default";
+
+    pub const CONST: usize = 0;
+    pub const RAM: usize = 1;
+    pub const REGISTER: usize = 2;
+    pub const UNIQUE: usize = 3;
+
+    pub fn vn(space_index: usize, offset: u64, size: usize) -> VarNode {
+        VarNode {
+            space_index,
+            offset,
+            size,
+        }
+    }
+
+    /// A stand-in for a [SleighContext](crate::context::SleighContext), with a handful of x86
+    /// registers and a `syscall` userop, for tests that do not need to decode instructions
+    pub struct TestContext {
+        spaces: Vec<SpaceInfo>,
+        registers: RegisterTable,
+    }
+
+    impl Default for TestContext {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl TestContext {
+        pub fn new() -> Self {
+            Self::with_endianness(SleighEndianness::Little)
+        }
+
+        /// A context whose spaces all have the given endianness
+        pub fn with_endianness(endianness: SleighEndianness) -> Self {
+            let space = |name: &str, index: usize, _type: SpaceType| SpaceInfo {
+                name: name.to_string(),
+                index,
+                index_size_bytes: 8,
+                word_size_bytes: 1,
+                _type,
+                endianness,
+            };
+            let reg = |offset: u64, size: usize, name: &str| {
+                let vn = VarNode {
+                    space_index: REGISTER,
+                    offset,
+                    size,
+                };
+                (vn, name.to_string())
+            };
+            Self {
+                spaces: vec![
+                    space("const", CONST, SpaceType::IPTR_CONSTANT),
+                    space("ram", RAM, SpaceType::IPTR_PROCESSOR),
+                    space("register", REGISTER, SpaceType::IPTR_PROCESSOR),
+                    space("unique", UNIQUE, SpaceType::IPTR_INTERNAL),
+                ],
+                registers: RegisterTable::new(vec![
+                    reg(0, 8, "RAX"),
+                    reg(0, 4, "EAX"),
+                    reg(0, 2, "AX"),
+                    reg(0, 1, "AL"),
+                    reg(1, 1, "AH"),
+                    reg(0x20, 8, "RSP"),
+                    reg(0x206, 1, "ZF"),
+                ]),
+            }
+        }
+    }
+
+    impl SpaceManager for TestContext {
+        fn get_space_info(&self, idx: usize) -> Option<&SpaceInfo> {
+            self.spaces.get(idx)
+        }
+
+        fn get_all_space_info(&self) -> &[SpaceInfo] {
+            &self.spaces
+        }
+
+        fn get_code_space_idx(&self) -> usize {
+            RAM
+        }
+
+        fn as_userop_manager(&self) -> Option<&dyn UserOpManager> {
+            Some(self)
+        }
+    }
+
+    impl RegisterManager for TestContext {
+        fn get_register(&self, name: &str) -> Option<VarNode> {
+            self.registers.get(name).cloned()
+        }
+
+        fn get_register_name(&self, location: VarNode) -> Option<&str> {
+            self.registers.name(&location)
+        }
+
+        fn get_registers(&self) -> Vec<(VarNode, String)> {
+            self.registers.all().to_vec()
+        }
+
+        fn get_register_space_idx(&self) -> Option<usize> {
+            self.registers.space()
+        }
+
+        fn get_parent_register(&self, name: &str) -> Option<&str> {
+            self.registers.parent(name)
+        }
+
+        fn get_child_registers(&self, name: &str) -> Vec<&str> {
+            self.registers.children(name)
+        }
+
+        fn get_covering_register(&self, location: &VarNode) -> Option<&str> {
+            self.registers.covering(location)
+        }
+
+        fn get_overlapping_registers(&self, location: &VarNode) -> Vec<&str> {
+            self.registers.overlapping(location)
+        }
+    }
+
+    impl UserOpManager for TestContext {
+        fn get_userop_name(&self, index: u64) -> Option<&str> {
+            (index == 0).then_some("syscall")
+        }
+
+        fn get_userop_index(&self, name: &str) -> Option<u64> {
+            (name == "syscall").then_some(0)
+        }
+
+        fn get_userops(&self) -> Vec<(u64, String)> {
+            vec![(0, "syscall".to_string())]
+        }
+    }
+
+    /// A file in the temporary directory, named uniquely to this process, that is deleted when
+    /// dropped, even if the test fails first
+    pub struct TempFile(PathBuf);
+
+    impl TempFile {
+        pub fn new<C: AsRef<[u8]>>(suffix: &str, contents: C) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let name = format!(
+                "jingle_{}_{}{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                suffix
+            );
+            let path = std::env::temp_dir().join(name);
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
 }
@@ -1,16 +1,73 @@
+use crate::error::JingleSleighError;
+use crate::pcode::syntax::{binary_operator, function_name, unary_operator};
 use crate::pcode::PcodeOperation;
 use crate::pcode::PcodeOperation::{
-    Branch, BranchInd, CBranch, Call, CallInd, CallOther, Copy, Int2Comp, IntAdd, IntAnd, IntCarry,
-    IntEqual, IntLeftShift, IntLess, IntLessEqual, IntNegate, IntNotEqual, IntOr, IntRightShift,
-    IntSExt, IntSignedBorrow, IntSignedCarry, IntSignedLess, IntSignedLessEqual, IntSub, IntXor,
-    IntZExt, Load, PopCount, Return, Store,
+    BoolAnd, BoolNegate, BoolOr, BoolXor, Branch, BranchInd, CBranch, CPoolRef, Call, CallInd,
+    CallOther, Cast, Copy, Extract, FloatAbs, FloatAdd, FloatCeil, FloatDiv, FloatEqual,
+    FloatFloatToFloat, FloatFloor, FloatIntToFloat, FloatLess, FloatLessEqual, FloatMult, FloatNaN,
+    FloatNeg, FloatNotEqual, FloatRound, FloatSqrt, FloatSub, FloatTrunc, Indirect, Insert,
+    Int2Comp, IntAdd, IntAnd, IntCarry, IntDiv, IntEqual, IntLeftShift, IntLess, IntLessEqual,
+    IntMult, IntNegate, IntNotEqual, IntOr, IntRem, IntRightShift, IntSExt, IntSignedBorrow,
+    IntSignedCarry, IntSignedDiv, IntSignedLess, IntSignedLessEqual, IntSignedRem,
+    IntSignedRightShift, IntSub, IntXor, IntZExt, Load, LzCount, MultiEqual, New, Piece, PopCount,
+    PtrAdd, PtrSub, Return, SegmentOp, Store, SubPiece,
 };
-use crate::space::SpaceManager;
+use crate::space::{RegisterManager, SpaceManager, SymbolManager, UserOpManager};
+use crate::{OpCode, RawVarNodeDisplay, SpaceType, VarNode};
 use std::fmt::{Display, Formatter};
 
 pub struct PcodeOperationDisplay<'a, T: SpaceManager> {
     pub(crate) op: PcodeOperation,
     pub(crate) spaces: &'a T,
+    /// The name of the userop invoked by a `CALLOTHER`, if known
+    pub(crate) userop_name: Option<String>,
+    /// The target of a direct branch, rendered relative to the nearest symbol
+    pub(crate) branch_symbol: Option<String>,
+}
+
+impl<'a, T: SpaceManager> PcodeOperationDisplay<'a, T> {
+    /// Show `CALLOTHER`s with the name of the userop they invoke rather than its index
+    pub fn with_userops<U: UserOpManager + ?Sized>(mut self, userops: &U) -> Self {
+        if let CallOther { inputs, .. } = &self.op {
+            self.userop_name = inputs
+                .first()
+                .and_then(|i| userops.get_userop_name(i.offset))
+                .map(|s| s.to_string());
+        }
+        self
+    }
+
+    /// Show the targets of direct branches and calls as `symbol+offset`
+    pub fn with_symbols<S: SymbolManager>(mut self, symbols: &S) -> Self {
+        let target = match &self.op {
+            Branch { input } | Call { input } => Some(input),
+            CBranch { input0, .. } => Some(input0),
+            _ => None,
+        };
+        self.branch_symbol = target
+            .filter(|vn| !self.is_relative(vn))
+            .and_then(|vn| symbols.get_nearest_symbol(vn.offset))
+            .map(|(sym, offset)| match offset {
+                0 => sym.name.clone(),
+                _ => format!("{}+{:#x}", sym.name, offset),
+            });
+        self
+    }
+
+    /// Branches into the constant space are relative branches within an instruction's pcode
+    fn is_relative(&self, vn: &VarNode) -> bool {
+        self.spaces
+            .get_space_info(vn.space_index)
+            .map(|s| s._type == SpaceType::IPTR_CONSTANT)
+            .unwrap_or(false)
+    }
+
+    fn fmt_branch_target(&self, vn: &VarNode) -> Result<String, std::fmt::Error> {
+        match &self.branch_symbol {
+            Some(sym) => Ok(sym.clone()),
+            None => Ok(format!("{}", vn.display(self.spaces)?)),
+        }
+    }
 }
 
 impl<'a, T> Display for PcodeOperationDisplay<'a, T>
@@ -18,6 +75,7 @@ where
     T: SpaceManager,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let opcode = self.op.opcode();
         match &self.op {
             Copy { input, output } => {
                 write!(
@@ -27,30 +85,6 @@ where
                     input.display(self.spaces)?
                 )
             }
-            PopCount { input, output } => {
-                write!(
-                    f,
-                    "{} = popcount({})",
-                    output.display(self.spaces)?,
-                    input.display(self.spaces)?
-                )
-            }
-            IntZExt { input, output } => {
-                write!(
-                    f,
-                    "{} = zext({})",
-                    output.display(self.spaces)?,
-                    input.display(self.spaces)?
-                )
-            }
-            IntSExt { input, output } => {
-                write!(
-                    f,
-                    "{} = sext({})",
-                    output.display(self.spaces)?,
-                    input.display(self.spaces)?
-                )
-            }
             Store { output, input } => {
                 write!(
                     f,
@@ -67,184 +101,257 @@ where
                     input.display(self.spaces)?
                 )
             }
-            IntCarry {
-                output,
-                input0,
-                input1,
-            } => write!(
+            IntNegate { output, input }
+            | Int2Comp { output, input }
+            | BoolNegate { output, input }
+            | FloatNeg { output, input } => write!(
                 f,
-                "{} = carry({}, {})",
+                "{} = {}{}",
                 output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?
+                unary_operator(opcode).ok_or(std::fmt::Error)?,
+                input.display(self.spaces)?
             ),
-            IntSignedCarry {
+            IntAdd {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = s.carry({}, {})",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?
-            ),
-            IntSignedBorrow {
+            }
+            | IntSub {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = s.borrow({}, {})",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?
-            ),
-            Int2Comp { output, input } => write!(
-                f,
-                "{} = -{}",
-                output.display(self.spaces)?,
-                input.display(self.spaces)?
-            ),
-            IntAdd {
+            }
+            | IntAnd {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} + {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntSub {
+            }
+            | IntOr {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} - {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntAnd {
+            }
+            | IntXor {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} & {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntOr {
+            }
+            | IntRightShift {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} v {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntXor {
+            }
+            | IntLeftShift {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} ^ {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntRightShift {
+            }
+            | IntSignedRightShift {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} >> {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntLeftShift {
+            }
+            | IntLess {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} << {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntLess {
+            }
+            | IntLessEqual {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} < {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntLessEqual {
+            }
+            | IntSignedLess {
                 output,
                 input0,
                 input1,
-            } => write!(
-                f,
-                "{} = {} <= {}",
-                output.display(self.spaces)?,
-                input0.display(self.spaces)?,
-                input1.display(self.spaces)?,
-            ),
-            IntSignedLess {
+            }
+            | IntSignedLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntMult {
+                output,
+                input0,
+                input1,
+            }
+            | IntDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntRem {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedRem {
+                output,
+                input0,
+                input1,
+            }
+            | BoolXor {
+                output,
+                input0,
+                input1,
+            }
+            | BoolAnd {
+                output,
+                input0,
+                input1,
+            }
+            | BoolOr {
+                output,
+                input0,
+                input1,
+            }
+            | FloatEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLess {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatAdd {
+                output,
+                input0,
+                input1,
+            }
+            | FloatDiv {
+                output,
+                input0,
+                input1,
+            }
+            | FloatMult {
+                output,
+                input0,
+                input1,
+            }
+            | FloatSub {
                 output,
                 input0,
                 input1,
             } => write!(
                 f,
-                "{} = {} s< {}",
+                "{} = {} {} {}",
                 output.display(self.spaces)?,
                 input0.display(self.spaces)?,
+                binary_operator(opcode).ok_or(std::fmt::Error)?,
                 input1.display(self.spaces)?,
             ),
-            IntSignedLessEqual {
+            PopCount { output, input }
+            | LzCount { output, input }
+            | IntZExt { output, input }
+            | IntSExt { output, input }
+            | FloatNaN { output, input }
+            | FloatAbs { output, input }
+            | FloatSqrt { output, input }
+            | FloatIntToFloat { output, input }
+            | FloatFloatToFloat { output, input }
+            | FloatTrunc { output, input }
+            | FloatCeil { output, input }
+            | FloatFloor { output, input }
+            | FloatRound { output, input }
+            | Cast { output, input } => write!(
+                f,
+                "{} = {}({})",
+                output.display(self.spaces)?,
+                function_name(opcode).ok_or(std::fmt::Error)?,
+                input.display(self.spaces)?
+            ),
+            IntCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedBorrow {
+                output,
+                input0,
+                input1,
+            }
+            | Indirect {
+                output,
+                input0,
+                input1,
+            }
+            | Piece {
+                output,
+                input0,
+                input1,
+            }
+            | PtrSub {
                 output,
                 input0,
                 input1,
             } => write!(
                 f,
-                "{} = {} s<= {}",
+                "{} = {}({}, {})",
                 output.display(self.spaces)?,
+                function_name(opcode).ok_or(std::fmt::Error)?,
                 input0.display(self.spaces)?,
                 input1.display(self.spaces)?,
             ),
-            IntEqual {
+            PtrAdd {
                 output,
                 input0,
                 input1,
+                input2,
+            }
+            | SegmentOp {
+                output,
+                input0,
+                input1,
+                input2,
             } => write!(
                 f,
-                "{} = {} == {}",
+                "{} = {}({}, {}, {})",
                 output.display(self.spaces)?,
+                function_name(opcode).ok_or(std::fmt::Error)?,
                 input0.display(self.spaces)?,
                 input1.display(self.spaces)?,
+                input2.display(self.spaces)?,
             ),
-            IntNotEqual {
+            SubPiece {
                 output,
                 input0,
                 input1,
             } => write!(
                 f,
-                "{} = {} != {}",
+                "{} = {}({})",
                 output.display(self.spaces)?,
                 input0.display(self.spaces)?,
                 input1.display(self.spaces)?,
@@ -253,7 +360,16 @@ where
                 if let Some(output) = output {
                     write!(f, "{} = ", output.display(self.spaces)?)?;
                 }
-                write!(f, "userop(")?;
+                let inputs = match &self.userop_name {
+                    Some(name) => {
+                        write!(f, "{}(", name)?;
+                        inputs.get(1..).unwrap_or_default()
+                    }
+                    None => {
+                        write!(f, "userop(")?;
+                        inputs.as_slice()
+                    }
+                };
                 let mut args = Vec::with_capacity(inputs.len());
                 for i in inputs {
                     args.push(format!("{}", i.display(self.spaces)?));
@@ -263,22 +379,568 @@ where
             }
             CallInd { input } => write!(f, "call [{}]", input.display(self.spaces)?),
             Return { input } => write!(f, "return [{}]", input.display(self.spaces)?),
-            Branch { input } => write!(f, "branch {}", input.display(self.spaces)?),
+            Branch { input } => write!(f, "branch {}", self.fmt_branch_target(input)?),
             CBranch { input0, input1 } => write!(
                 f,
                 "if {} branch {}",
                 input1.display(self.spaces)?,
-                input0.display(self.spaces)?
+                self.fmt_branch_target(input0)?
             ),
             BranchInd { input } => write!(f, "branch [{}]", input.display(self.spaces)?),
-            Call { input } => write!(f, "call {}", input.display(self.spaces)?),
-            IntNegate { input, output } => write!(
+            Call { input } => write!(f, "call {}", self.fmt_branch_target(input)?),
+            MultiEqual {
+                output,
+                input0,
+                input1,
+                inputs,
+            }
+            | CPoolRef {
+                output,
+                input0,
+                input1,
+                inputs,
+            } => {
+                write!(
+                    f,
+                    "{} = {}(",
+                    output.display(self.spaces)?,
+                    function_name(opcode).ok_or(std::fmt::Error)?
+                )?;
+                let mut args = vec![
+                    format!("{}", input0.display(self.spaces)?),
+                    format!("{}", input1.display(self.spaces)?),
+                ];
+                for i in inputs {
+                    args.push(format!("{}", i.display(self.spaces)?));
+                }
+                write!(f, "{})", args.join(", "))
+            }
+            New {
+                output,
+                input,
+                size,
+            } => {
+                write!(
+                    f,
+                    "{} = {}({}",
+                    output.display(self.spaces)?,
+                    function_name(opcode).ok_or(std::fmt::Error)?,
+                    input.display(self.spaces)?
+                )?;
+                if let Some(size) = size {
+                    write!(f, ", {}", size.display(self.spaces)?)?;
+                }
+                write!(f, ")")
+            }
+            Insert {
+                output,
+                input0,
+                input1,
+                position,
+                size,
+            } => write!(
                 f,
-                "{} =  ~{}",
+                "{} = {}({}, {}, {}, {})",
                 output.display(self.spaces)?,
-                input.display(self.spaces)?
+                function_name(opcode).ok_or(std::fmt::Error)?,
+                input0.display(self.spaces)?,
+                input1.display(self.spaces)?,
+                position.display(self.spaces)?,
+                size.display(self.spaces)?,
+            ),
+            Extract {
+                output,
+                input0,
+                position,
+                size,
+            } => write!(
+                f,
+                "{} = {}({}, {}, {})",
+                output.display(self.spaces)?,
+                function_name(opcode).ok_or(std::fmt::Error)?,
+                input0.display(self.spaces)?,
+                position.display(self.spaces)?,
+                size.display(self.spaces)?,
             ),
-            _ => write!(f, "<please impl print> {:?}", self.op),
         }
     }
 }
+
+/// Displays a [PcodeOperation] in Ghidra's raw p-code syntax, as printed by `SLEIGH`'s own
+/// p-code dumper: the output (if any), then the opcode name, then each input separated by
+/// spaces, e.g. `RAX = INT_ADD RAX (const,0x1,8)`. Varnodes are shown as described in
+/// [RawVarNodeDisplay].
+///
+/// Raw p-code gives `LOAD` and `STORE` the address space being accessed as their first input;
+/// it is shown by name, e.g. `EAX = LOAD ram RSP`. The first input of a `CALLOTHER` is shown
+/// as the quoted name of the userop it invokes, e.g. `CALLOTHER "syscall"`.
+#[derive(Clone, Debug)]
+pub struct RawPcodeOperationDisplay {
+    output: Option<RawVarNodeDisplay>,
+    opcode: OpCode,
+    inputs: Vec<RawInputDisplay>,
+}
+
+#[derive(Clone, Debug)]
+enum RawInputDisplay {
+    VarNode(RawVarNodeDisplay),
+    Space(String),
+    UserOp(String),
+}
+
+impl RawPcodeOperationDisplay {
+    pub(crate) fn new<T: RegisterManager + UserOpManager>(
+        op: &PcodeOperation,
+        ctx: &T,
+    ) -> Result<Self, JingleSleighError> {
+        let vn = |v: &VarNode| v.display_raw(ctx).map(RawInputDisplay::VarNode);
+        let space = |idx: usize| {
+            ctx.get_space_info(idx)
+                .map(|s| RawInputDisplay::Space(s.name.clone()))
+                .ok_or(JingleSleighError::InvalidSpaceName)
+        };
+        let (output, inputs) = match op {
+            Load { input, output } => (
+                Some(output),
+                vec![
+                    space(input.pointer_space_index)?,
+                    vn(&input.pointer_location)?,
+                ],
+            ),
+            Store { output, input } => (
+                None,
+                vec![
+                    space(output.pointer_space_index)?,
+                    vn(&output.pointer_location)?,
+                    vn(input)?,
+                ],
+            ),
+            Branch { input } | Call { input } => (None, vec![vn(input)?]),
+            CBranch { input0, input1 } => (None, vec![vn(input0)?, vn(input1)?]),
+            BranchInd { input } | CallInd { input } | Return { input } => {
+                (None, vec![vn(&input.pointer_location)?])
+            }
+            CallOther { output, inputs } => {
+                let mut args = Vec::with_capacity(inputs.len());
+                for (i, input) in inputs.iter().enumerate() {
+                    let name = match i {
+                        0 => ctx.get_userop_name(input.offset),
+                        _ => None,
+                    };
+                    match name {
+                        Some(name) => args.push(RawInputDisplay::UserOp(name.to_string())),
+                        None => args.push(vn(input)?),
+                    }
+                }
+                (output.as_ref(), args)
+            }
+            Copy { input, output }
+            | IntSExt { input, output }
+            | IntZExt { input, output }
+            | Int2Comp { output, input }
+            | IntNegate { output, input }
+            | BoolNegate { output, input }
+            | FloatNaN { output, input }
+            | FloatNeg { output, input }
+            | FloatAbs { output, input }
+            | FloatSqrt { output, input }
+            | FloatIntToFloat { output, input }
+            | FloatFloatToFloat { output, input }
+            | FloatTrunc { output, input }
+            | FloatCeil { output, input }
+            | FloatFloor { output, input }
+            | FloatRound { output, input }
+            | Cast { output, input }
+            | PopCount { input, output }
+            | LzCount { output, input } => (Some(output), vec![vn(input)?]),
+            IntEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedLess {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntLess {
+                output,
+                input0,
+                input1,
+            }
+            | IntLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntAdd {
+                output,
+                input0,
+                input1,
+            }
+            | IntSub {
+                output,
+                input0,
+                input1,
+            }
+            | IntCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedBorrow {
+                output,
+                input0,
+                input1,
+            }
+            | IntXor {
+                output,
+                input0,
+                input1,
+            }
+            | IntAnd {
+                output,
+                input0,
+                input1,
+            }
+            | IntOr {
+                output,
+                input0,
+                input1,
+            }
+            | IntLeftShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntRightShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedRightShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntMult {
+                output,
+                input0,
+                input1,
+            }
+            | IntDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntRem {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedRem {
+                output,
+                input0,
+                input1,
+            }
+            | BoolXor {
+                output,
+                input0,
+                input1,
+            }
+            | BoolAnd {
+                output,
+                input0,
+                input1,
+            }
+            | BoolOr {
+                output,
+                input0,
+                input1,
+            }
+            | FloatEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLess {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatAdd {
+                output,
+                input0,
+                input1,
+            }
+            | FloatDiv {
+                output,
+                input0,
+                input1,
+            }
+            | FloatMult {
+                output,
+                input0,
+                input1,
+            }
+            | FloatSub {
+                output,
+                input0,
+                input1,
+            }
+            | Indirect {
+                output,
+                input0,
+                input1,
+            }
+            | Piece {
+                output,
+                input0,
+                input1,
+            }
+            | SubPiece {
+                output,
+                input0,
+                input1,
+            }
+            | PtrSub {
+                output,
+                input0,
+                input1,
+            } => (Some(output), vec![vn(input0)?, vn(input1)?]),
+            MultiEqual {
+                input0,
+                input1,
+                inputs,
+                output,
+            }
+            | CPoolRef {
+                input0,
+                input1,
+                inputs,
+                output,
+            } => {
+                let mut args = vec![vn(input0)?, vn(input1)?];
+                for input in inputs {
+                    args.push(vn(input)?);
+                }
+                (Some(output), args)
+            }
+            PtrAdd {
+                output,
+                input0,
+                input1,
+                input2,
+            }
+            | SegmentOp {
+                output,
+                input0,
+                input1,
+                input2,
+            } => (Some(output), vec![vn(input0)?, vn(input1)?, vn(input2)?]),
+            New {
+                output,
+                input,
+                size,
+            } => {
+                let mut args = vec![vn(input)?];
+                if let Some(size) = size {
+                    args.push(vn(size)?);
+                }
+                (Some(output), args)
+            }
+            Insert {
+                output,
+                input0,
+                input1,
+                position,
+                size,
+            } => (
+                Some(output),
+                vec![vn(input0)?, vn(input1)?, vn(position)?, vn(size)?],
+            ),
+            Extract {
+                output,
+                input0,
+                position,
+                size,
+            } => (Some(output), vec![vn(input0)?, vn(position)?, vn(size)?]),
+        };
+        Ok(Self {
+            output: output.map(|o| o.display_raw(ctx)).transpose()?,
+            opcode: op.opcode(),
+            inputs,
+        })
+    }
+}
+
+impl Display for RawInputDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawInputDisplay::VarNode(vn) => write!(f, "{}", vn),
+            RawInputDisplay::Space(name) => write!(f, "{}", name),
+            RawInputDisplay::UserOp(name) => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+impl Display for RawPcodeOperationDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(output) = &self.output {
+            write!(f, "{} = ", output)?;
+        }
+        write!(f, "{}", self.opcode.name())?;
+        for input in &self.inputs {
+            write!(f, " {}", input)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcode::PcodeOperation::{
+        CallOther, Copy, FloatIntToFloat, IntAdd, Load, MultiEqual, Store,
+    };
+    use crate::tests::{vn, TestContext, CONST, RAM, REGISTER, UNIQUE};
+    use crate::IndirectVarNode;
+
+    #[test]
+    fn test_raw_display() {
+        let ctx = TestContext::new();
+        let rsp = IndirectVarNode {
+            pointer_space_index: RAM,
+            pointer_location: vn(REGISTER, 0x20, 8),
+            access_size_bytes: 4,
+        };
+        let cases = vec![
+            (
+                IntAdd {
+                    output: vn(REGISTER, 0, 8),
+                    input0: vn(REGISTER, 0, 8),
+                    input1: vn(CONST, 1, 8),
+                },
+                "RAX = INT_ADD RAX (const,0x1,8)",
+            ),
+            (
+                IntAdd {
+                    output: vn(REGISTER, 0x20, 8),
+                    input0: vn(REGISTER, 0x20, 8),
+                    input1: vn(CONST, 0xfffffffffffffff8, 8),
+                },
+                "RSP = INT_ADD RSP (const,0xfffffffffffffff8,8)",
+            ),
+            (
+                Copy {
+                    input: vn(CONST, u64::MAX, 8),
+                    output: vn(REGISTER, 0, 8),
+                },
+                "RAX = COPY (const,0xffffffffffffffff,8)",
+            ),
+            (
+                Copy {
+                    input: vn(REGISTER, 4, 4),
+                    output: vn(REGISTER, 1, 1),
+                },
+                "AH = COPY RAX+4:4",
+            ),
+            (
+                Load {
+                    input: rsp.clone(),
+                    output: vn(UNIQUE, 0x100, 4),
+                },
+                "(unique,0x100,4) = LOAD ram RSP",
+            ),
+            (
+                Store {
+                    output: rsp,
+                    input: vn(REGISTER, 0, 4),
+                },
+                "STORE ram RSP EAX",
+            ),
+            (
+                CallOther {
+                    output: None,
+                    inputs: vec![vn(CONST, 0, 4), vn(REGISTER, 0, 8)],
+                },
+                "CALLOTHER \"syscall\" RAX",
+            ),
+            (
+                CallOther {
+                    output: Some(vn(REGISTER, 0x206, 1)),
+                    inputs: vec![vn(CONST, 7, 4)],
+                },
+                "ZF = CALLOTHER (const,0x7,4)",
+            ),
+            (
+                FloatIntToFloat {
+                    output: vn(UNIQUE, 0, 8),
+                    input: vn(REGISTER, 0, 4),
+                },
+                "(unique,0x0,8) = INT2FLOAT EAX",
+            ),
+            (
+                MultiEqual {
+                    input0: vn(REGISTER, 0, 8),
+                    input1: vn(REGISTER, 0x20, 8),
+                    inputs: vec![vn(RAM, 0x1000, 8)],
+                    output: vn(REGISTER, 0, 8),
+                },
+                "RAX = MULTIEQUAL RAX RSP (ram,0x1000,8)",
+            ),
+        ];
+        for (op, expected) in cases {
+            assert_eq!(op.display_raw(&ctx).unwrap().to_string(), expected);
+            // every variant also has a dedicated default display
+            assert!(!op.display(&ctx).unwrap().to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_display_names_userops() {
+        let ctx = TestContext::new();
+        let op = CallOther {
+            output: None,
+            inputs: vec![vn(CONST, 0, 4), vn(REGISTER, 0, 8)],
+        };
+        assert_eq!(
+            op.display(&ctx).unwrap().to_string(),
+            "syscall(register[0]:8)"
+        );
+        let trace = [ctx];
+        assert_eq!(
+            op.display(&trace.as_slice()).unwrap().to_string(),
+            "syscall(register[0]:8)"
+        );
+    }
+}
@@ -1,4 +1,6 @@
 pub mod display;
+pub mod parse;
+mod syntax;
 
 use crate::pcode::PcodeOperation::{
     BoolAnd, BoolNegate, BoolOr, BoolXor, Branch, BranchInd, CBranch, CPoolRef, Call, CallInd,
@@ -15,8 +17,8 @@ use crate::pcode::PcodeOperation::{
 use crate::error::JingleSleighError;
 use crate::ffi::instruction::bridge::RawPcodeOp;
 pub use crate::ffi::opcode::OpCode;
-use crate::pcode::display::PcodeOperationDisplay;
-use crate::space::SpaceManager;
+use crate::pcode::display::{PcodeOperationDisplay, RawPcodeOperationDisplay};
+use crate::space::{RegisterManager, SpaceManager, SymbolManager, UserOpManager};
 use crate::varnode::{IndirectVarNode, VarNode};
 use crate::GeneralizedVarNode;
 use serde::{Deserialize, Serialize};
@@ -391,12 +393,46 @@ impl PcodeOperation {
         &self,
         ctx: &'a T,
     ) -> Result<PcodeOperationDisplay<'a, T>, JingleSleighError> {
-        Ok(PcodeOperationDisplay {
+        let display = PcodeOperationDisplay {
             op: self.clone(),
             spaces: ctx,
+            userop_name: None,
+            branch_symbol: None,
+        };
+        Ok(match ctx.as_userop_manager() {
+            Some(userops) => display.with_userops(userops),
+            None => display,
         })
     }
 
+    /// Like [PcodeOperation::display], but `CALLOTHER`s always take the name of the userop they invoke
+    /// from `ctx`, even if it does not offer itself through
+    /// [SpaceManager::as_userop_manager](crate::SpaceManager::as_userop_manager)
+    pub fn display_with_userops<'a, T: UserOpManager>(
+        &self,
+        ctx: &'a T,
+    ) -> Result<PcodeOperationDisplay<'a, T>, JingleSleighError> {
+        Ok(self.display(ctx)?.with_userops(ctx))
+    }
+
+    /// Like [PcodeOperation::display], but the targets of direct branches are shown relative
+    /// to the nearest symbol
+    pub fn display_with_symbols<'a, T: SpaceManager + SymbolManager>(
+        &self,
+        ctx: &'a T,
+    ) -> Result<PcodeOperationDisplay<'a, T>, JingleSleighError> {
+        Ok(self.display(ctx)?.with_symbols(ctx))
+    }
+
+    /// Display this operation in Ghidra's raw p-code syntax, with registers and userops shown
+    /// by name. See [RawPcodeOperationDisplay] for the format.
+    pub fn display_raw<T: RegisterManager + UserOpManager>(
+        &self,
+        ctx: &T,
+    ) -> Result<RawPcodeOperationDisplay, JingleSleighError> {
+        RawPcodeOperationDisplay::new(self, ctx)
+    }
+
     pub fn output(&self) -> Option<GeneralizedVarNode> {
         match self {
             Copy { output, .. } => Some(GeneralizedVarNode::from(output)),
@@ -0,0 +1,1265 @@
+use crate::error::JingleSleighError;
+use crate::instruction::Instruction;
+use crate::pcode::syntax::{BINARY_OPERATORS, FUNCTIONS, UNARY_OPERATORS};
+use crate::pcode::PcodeOperation;
+use crate::pcode::PcodeOperation::{
+    BoolAnd, BoolNegate, BoolOr, BoolXor, Branch, BranchInd, CBranch, CPoolRef, Call, CallInd,
+    CallOther, Cast, Copy, Extract, FloatAbs, FloatAdd, FloatCeil, FloatDiv, FloatEqual,
+    FloatFloatToFloat, FloatFloor, FloatIntToFloat, FloatLess, FloatLessEqual, FloatMult, FloatNaN,
+    FloatNeg, FloatNotEqual, FloatRound, FloatSqrt, FloatSub, FloatTrunc, Indirect, Insert,
+    Int2Comp, IntAdd, IntAnd, IntCarry, IntDiv, IntEqual, IntLeftShift, IntLess, IntLessEqual,
+    IntMult, IntNegate, IntNotEqual, IntOr, IntRem, IntRightShift, IntSExt, IntSignedBorrow,
+    IntSignedCarry, IntSignedDiv, IntSignedLess, IntSignedLessEqual, IntSignedRem,
+    IntSignedRightShift, IntSub, IntXor, IntZExt, Load, LzCount, MultiEqual, New, Piece, PopCount,
+    PtrAdd, PtrSub, Return, SegmentOp, Store, SubPiece,
+};
+use crate::space::{RegisterManager, SpaceManager, UserOpManager};
+use crate::{Disassembly, IndirectVarNode, OpCode, SpaceType, VarNode};
+use std::collections::HashMap;
+
+/// `SLEIGH` stores the index of the userop a `CALLOTHER` invokes in a 4-byte constant
+const USEROP_INDEX_SIZE: usize = 4;
+
+/// Turns the textual form of `PCODE` back into [PcodeOperation]s and [Instruction]s, allowing
+/// `PCODE` to be written by hand, e.g. for test fixtures.
+///
+/// Each operation is accepted in either of the syntaxes `jingle` prints:
+/// * that of [PcodeOperationDisplay](crate::display::PcodeOperationDisplay), e.g.
+///   `register[0]:8 = register[0]:8 + 1:8` or `unique[100]:4 = *(ram[register[20]:8]:4)`. The
+///   sleigh-style indirection `*[ram]register[20]:8` is also accepted; the access size is then
+///   taken from the value being loaded or stored.
+/// * Ghidra's raw syntax, as printed by
+///   [RawPcodeOperationDisplay](crate::display::RawPcodeOperationDisplay), e.g.
+///   `RAX = INT_ADD RAX (const,0x1,8)`.
+///
+/// Register names are accepted in place of varnodes in either syntax once the parser has been
+/// given the registers with [PcodeParser::with_registers]. Similarly, `CALLOTHER`s naming their
+/// userop need [PcodeParser::with_userops]. Branch targets shown relative to symbols cannot be
+/// parsed.
+pub struct PcodeParser<'a, T: SpaceManager> {
+    spaces: &'a T,
+    registers: HashMap<String, VarNode>,
+    userops: HashMap<String, u64>,
+}
+
+impl<'a, T: SpaceManager> PcodeParser<'a, T> {
+    pub fn new(spaces: &'a T) -> Self {
+        Self {
+            spaces,
+            registers: HashMap::new(),
+            userops: HashMap::new(),
+        }
+    }
+
+    /// Accept the given registers' names wherever a varnode is expected
+    pub fn with_registers<R: RegisterManager>(mut self, registers: &R) -> Self {
+        self.registers = registers
+            .get_registers()
+            .into_iter()
+            .map(|(vn, name)| (name, vn))
+            .collect();
+        self
+    }
+
+    /// Accept `CALLOTHER`s that name the userop they invoke
+    pub fn with_userops<U: UserOpManager>(mut self, userops: &U) -> Self {
+        self.userops = userops
+            .get_userops()
+            .into_iter()
+            .map(|(idx, name)| (name, idx))
+            .collect();
+        self
+    }
+
+    /// Parse a single operation
+    pub fn parse_operation(&self, text: &str) -> Result<PcodeOperation, JingleSleighError> {
+        self.parse_line(text)
+            .map_err(|message| JingleSleighError::PcodeParse { line: 1, message })
+    }
+
+    /// Parse one operation per line, skipping blank lines
+    pub fn parse_operations(&self, text: &str) -> Result<Vec<PcodeOperation>, JingleSleighError> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                self.parse_line(line)
+                    .map_err(|message| JingleSleighError::PcodeParse {
+                        line: idx + 1,
+                        message,
+                    })
+            })
+            .collect()
+    }
+
+    /// Parse an instruction as printed by [InstructionDisplay](crate::InstructionDisplay): its
+    /// disassembly, then the disassembly of any delay slot instructions with a leading `_`,
+    /// then one operation per line.
+    ///
+    /// Only the text of the disassembly is recovered, so its operands are left empty. The
+    /// address and length of the instruction are not part of its text, and are left zero.
+    pub fn parse_instruction(&self, text: &str) -> Result<Instruction, JingleSleighError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+        let disassembly = lines
+            .next()
+            .map(|(_, line)| parse_disassembly(line))
+            .ok_or(JingleSleighError::PcodeParse {
+                line: 1,
+                message: "expected an instruction".to_string(),
+            })?;
+        let mut delay_slots = vec![];
+        while let Some((_, line)) = lines.next_if(|(_, line)| line.starts_with('_')) {
+            delay_slots.push(parse_disassembly(&line[1..]));
+        }
+        let mut ops = vec![];
+        for (line, text) in lines {
+            ops.push(
+                self.parse_line(text)
+                    .map_err(|message| JingleSleighError::PcodeParse { line, message })?,
+            );
+        }
+        Ok(Instruction {
+            disassembly,
+            delay_slots,
+            ops,
+            length: 0,
+            address: 0,
+        })
+    }
+
+    fn parse_line(&self, text: &str) -> Result<PcodeOperation, String> {
+        let text = text.trim();
+        let (lhs, rhs) = match text.split_once(" = ") {
+            Some((lhs, rhs)) => (Some(lhs.trim()), rhs.trim()),
+            None => (None, text),
+        };
+        let head = rhs.split_whitespace().next().unwrap_or_default();
+        if let Some(opcode) = OpCode::from_name(head) {
+            return self.parse_raw(lhs, opcode, &rhs[head.len()..]);
+        }
+        match lhs {
+            None => self.parse_statement(rhs),
+            Some(lhs) if lhs.starts_with('*') => {
+                let mut c = Cursor::new(lhs);
+                let (space, pointer, size) = self.indirect(&mut c)?;
+                c.end()?;
+                let input = self.complete_varnode(rhs, false)?;
+                Ok(Store {
+                    output: IndirectVarNode {
+                        pointer_space_index: space,
+                        pointer_location: pointer,
+                        access_size_bytes: size.unwrap_or(input.size),
+                    },
+                    input,
+                })
+            }
+            Some(lhs) => {
+                let output = self.complete_varnode(lhs, false)?;
+                self.parse_expression(output, rhs)
+            }
+        }
+    }
+
+    /// Parse the right-hand side of an assignment in the default syntax
+    fn parse_expression(&self, output: VarNode, rhs: &str) -> Result<PcodeOperation, String> {
+        let mut c = Cursor::new(rhs);
+        if c.peek("*") {
+            let (space, pointer, size) = self.indirect(&mut c)?;
+            c.end()?;
+            return Ok(Load {
+                input: IndirectVarNode {
+                    pointer_space_index: space,
+                    pointer_location: pointer,
+                    access_size_bytes: size.unwrap_or(output.size),
+                },
+                output,
+            });
+        }
+        for (prefix, opcode) in UNARY_OPERATORS {
+            if c.eat(prefix) {
+                let input = self.varnode(&mut c, false)?;
+                c.end()?;
+                return build(*opcode, Some(output), vec![input]);
+            }
+        }
+        let start = c.pos;
+        if let Some(name) = c.call_name() {
+            if let Some((_, opcode)) = FUNCTIONS.iter().find(|(f, _)| *f == name) {
+                let args = self.arguments(&mut c)?;
+                c.end()?;
+                return build(*opcode, Some(output), args);
+            }
+            if !self.registers.contains_key(name) {
+                let inputs = self.userop_arguments(name, &mut c)?;
+                c.end()?;
+                return Ok(CallOther {
+                    output: Some(output),
+                    inputs,
+                });
+            }
+            // a register being truncated, e.g. `RAX(0:8)`
+            c.pos = start;
+        }
+        let input0 = self.varnode(&mut c, false)?;
+        if c.at_end() {
+            return Ok(Copy {
+                input: input0,
+                output,
+            });
+        }
+        if c.eat("(") {
+            let input1 = self.varnode(&mut c, false)?;
+            c.expect(")")?;
+            c.end()?;
+            return Ok(SubPiece {
+                output,
+                input0,
+                input1,
+            });
+        }
+        let operator = c.token();
+        let (_, opcode) = BINARY_OPERATORS
+            .iter()
+            .find(|(op, _)| *op == operator)
+            .ok_or_else(|| format!("unknown operator `{}`", operator))?;
+        let input1 = self.varnode(&mut c, false)?;
+        c.end()?;
+        build(*opcode, Some(output), vec![input0, input1])
+    }
+
+    /// Parse an operation without an output in the default syntax
+    fn parse_statement(&self, text: &str) -> Result<PcodeOperation, String> {
+        let mut c = Cursor::new(text);
+        let op = if c.eat_word("if") {
+            let input1 = self.varnode(&mut c, false)?;
+            if !c.eat_word("branch") {
+                return Err("expected `branch`".to_string());
+            }
+            let input0 = self.varnode(&mut c, false)?;
+            CBranch { input0, input1 }
+        } else if c.eat_word("branch") {
+            match self.bracketed_indirect(&mut c)? {
+                Some(input) => BranchInd { input },
+                None => Branch {
+                    input: self.varnode(&mut c, false)?,
+                },
+            }
+        } else if c.eat_word("call") {
+            match self.bracketed_indirect(&mut c)? {
+                Some(input) => CallInd { input },
+                None => Call {
+                    input: self.varnode(&mut c, false)?,
+                },
+            }
+        } else if c.eat_word("return") {
+            let input = self
+                .bracketed_indirect(&mut c)?
+                .ok_or("expected `[` after `return`")?;
+            Return { input }
+        } else {
+            let name = c.call_name().ok_or("expected an operation")?;
+            let inputs = self.userop_arguments(name, &mut c)?;
+            CallOther {
+                output: None,
+                inputs,
+            }
+        };
+        c.end()?;
+        Ok(op)
+    }
+
+    /// Parse an operation in Ghidra's raw syntax, given everything after its opcode name
+    fn parse_raw(
+        &self,
+        lhs: Option<&str>,
+        opcode: OpCode,
+        rest: &str,
+    ) -> Result<PcodeOperation, String> {
+        let output = lhs.map(|o| self.complete_varnode(o, true)).transpose()?;
+        let mut c = Cursor::new(rest);
+        let space = match opcode {
+            OpCode::CPUI_LOAD | OpCode::CPUI_STORE => Some(self.space(c.word())?),
+            _ => None,
+        };
+        let mut inputs = vec![];
+        while !c.at_end() {
+            if c.eat("\"") {
+                let name = c.until('"');
+                c.expect("\"")?;
+                let index = *self
+                    .userops
+                    .get(name)
+                    .ok_or_else(|| format!("unknown userop `{}`", name))?;
+                inputs.push(self.constant(index, USEROP_INDEX_SIZE)?);
+            } else {
+                inputs.push(self.varnode(&mut c, true)?);
+            }
+        }
+        // raw `PCODE` only names the pointer of an indirect access; sizes come from the value
+        // accessed, and indirect branches go through the code space
+        let code_space = self.spaces.get_code_space_idx();
+        let code_size = self
+            .spaces
+            .get_space_info(code_space)
+            .map(|s| s.index_size_bytes as usize)
+            .unwrap_or_default();
+        let indirect = |space: usize, pointer: &VarNode, size: usize| IndirectVarNode {
+            pointer_space_index: space,
+            pointer_location: pointer.clone(),
+            access_size_bytes: size,
+        };
+        match (opcode, space, output, inputs.as_slice()) {
+            (OpCode::CPUI_LOAD, Some(space), Some(output), [pointer]) => Ok(Load {
+                input: indirect(space, pointer, output.size),
+                output,
+            }),
+            (OpCode::CPUI_STORE, Some(space), None, [pointer, input]) => Ok(Store {
+                output: indirect(space, pointer, input.size),
+                input: input.clone(),
+            }),
+            (OpCode::CPUI_BRANCHIND, None, None, [pointer]) => Ok(BranchInd {
+                input: indirect(code_space, pointer, code_size),
+            }),
+            (OpCode::CPUI_CALLIND, None, None, [pointer]) => Ok(CallInd {
+                input: indirect(code_space, pointer, code_size),
+            }),
+            (OpCode::CPUI_RETURN, None, None, [pointer]) => Ok(Return {
+                input: indirect(code_space, pointer, code_size),
+            }),
+            (
+                OpCode::CPUI_LOAD
+                | OpCode::CPUI_STORE
+                | OpCode::CPUI_BRANCHIND
+                | OpCode::CPUI_CALLIND
+                | OpCode::CPUI_RETURN,
+                ..,
+            ) => Err(format!("malformed {}", opcode.name())),
+            (_, _, output, inputs) => build(opcode, output, inputs.to_vec()),
+        }
+    }
+
+    /// Parse an indirect location: `*(space[pointer]:size)`, or `*[space]pointer`, which
+    /// leaves the size to the caller
+    fn indirect(&self, c: &mut Cursor) -> Result<(usize, VarNode, Option<usize>), String> {
+        c.expect("*")?;
+        if c.eat("[") {
+            let space = self.space(c.word())?;
+            c.expect("]")?;
+            let pointer = self.varnode(c, false)?;
+            return Ok((space, pointer, None));
+        }
+        c.expect("(")?;
+        let space = self.space(c.word())?;
+        c.expect("[")?;
+        let pointer = self.varnode(c, false)?;
+        c.expect("]")?;
+        c.expect(":")?;
+        let size = decimal(c.word())?;
+        c.expect(")")?;
+        Ok((space, pointer, Some(size)))
+    }
+
+    /// The `[*(...)]` operand of an indirect branch, call or return, if there is one
+    fn bracketed_indirect(&self, c: &mut Cursor) -> Result<Option<IndirectVarNode>, String> {
+        if !c.eat("[") {
+            return Ok(None);
+        }
+        let (space, pointer, size) = self.indirect(c)?;
+        c.expect("]")?;
+        let size = match size {
+            Some(size) => size,
+            None => self
+                .spaces
+                .get_space_info(space)
+                .map(|s| s.index_size_bytes as usize)
+                .unwrap_or_default(),
+        };
+        Ok(Some(IndirectVarNode {
+            pointer_space_index: space,
+            pointer_location: pointer,
+            access_size_bytes: size,
+        }))
+    }
+
+    /// The inputs of a `CALLOTHER` shown as a call to the named userop
+    fn userop_arguments(&self, name: &str, c: &mut Cursor) -> Result<Vec<VarNode>, String> {
+        let mut inputs = match name {
+            "userop" => vec![],
+            _ => {
+                let index = *self
+                    .userops
+                    .get(name)
+                    .ok_or_else(|| format!("unknown function or userop `{}`", name))?;
+                vec![self.constant(index, USEROP_INDEX_SIZE)?]
+            }
+        };
+        inputs.extend(self.arguments(c)?);
+        Ok(inputs)
+    }
+
+    /// A parenthesized, comma-separated list of varnodes
+    fn arguments(&self, c: &mut Cursor) -> Result<Vec<VarNode>, String> {
+        c.expect("(")?;
+        let mut args = vec![];
+        if c.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.varnode(c, false)?);
+            if c.eat(")") {
+                return Ok(args);
+            }
+            c.expect(",")?;
+        }
+    }
+
+    fn complete_varnode(&self, text: &str, raw: bool) -> Result<VarNode, String> {
+        let mut c = Cursor::new(text);
+        let vn = self.varnode(&mut c, raw)?;
+        c.end()?;
+        Ok(vn)
+    }
+
+    /// Parse a varnode in any of the forms `jingle` prints: `space[offset]:size` and the
+    /// constant `offset:size` with hexadecimal offsets and sizes; raw `(space,offset,size)`;
+    /// or a register name, optionally followed by a decimal `+offset` and `:size`.
+    ///
+    /// In raw `PCODE` constants are always written out in full, so a bare `name:size` there
+    /// refers to part of a register, even if the name happens to look like a number.
+    fn varnode(&self, c: &mut Cursor, raw: bool) -> Result<VarNode, String> {
+        if c.eat("(") {
+            let space_index = self.space(c.word())?;
+            c.expect(",")?;
+            let offset = hex(c.word())?;
+            c.expect(",")?;
+            let size = decimal(c.word())?;
+            c.expect(")")?;
+            return Ok(VarNode {
+                space_index,
+                offset,
+                size,
+            });
+        }
+        let word = c.word();
+        if word.is_empty() {
+            return Err(format!("expected a varnode at `{}`", c.rest()));
+        }
+        if c.eat_adjacent("[") {
+            let space_index = self.space(word)?;
+            let offset = hex(c.word())?;
+            c.expect("]")?;
+            c.expect(":")?;
+            let size = hex(c.word())? as usize;
+            return Ok(VarNode {
+                space_index,
+                offset,
+                size,
+            });
+        }
+        if !raw && c.peek_adjacent(":") && word.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            c.expect(":")?;
+            let size = hex(c.word())? as usize;
+            return self.constant(hex(word)?, size);
+        }
+        let register = self
+            .registers
+            .get(word)
+            .ok_or_else(|| format!("unknown register `{}`", word))?;
+        let mut vn = register.clone();
+        if c.eat_adjacent("+") {
+            let delta = decimal(c.word())? as u64;
+            vn.offset = vn
+                .offset
+                .checked_add(delta)
+                .ok_or_else(|| format!("`{}+{}` is past the end of its space", word, delta))?;
+        }
+        if c.eat_adjacent(":") {
+            vn.size = decimal(c.word())?;
+        }
+        Ok(vn)
+    }
+
+    fn constant(&self, offset: u64, size: usize) -> Result<VarNode, String> {
+        let space_index = self
+            .spaces
+            .get_all_space_info()
+            .iter()
+            .position(|s| s._type == SpaceType::IPTR_CONSTANT)
+            .ok_or("no constant space")?;
+        Ok(VarNode {
+            space_index,
+            offset,
+            size,
+        })
+    }
+
+    fn space(&self, name: &str) -> Result<usize, String> {
+        self.spaces
+            .get_all_space_info()
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| format!("unknown space `{}`", name))
+    }
+}
+
+/// Build an operation that takes only direct varnodes from its opcode and operands
+fn build(
+    opcode: OpCode,
+    output: Option<VarNode>,
+    inputs: Vec<VarNode>,
+) -> Result<PcodeOperation, String> {
+    let arity = |expected: usize| {
+        if inputs.len() == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} takes {} inputs, not {}",
+                opcode.name(),
+                expected,
+                inputs.len()
+            ))
+        }
+    };
+    let i = |idx: usize| inputs[idx].clone();
+    match (opcode, &output) {
+        (OpCode::CPUI_CALLOTHER, _) => return Ok(CallOther { output, inputs }),
+        (OpCode::CPUI_BRANCH | OpCode::CPUI_CALL | OpCode::CPUI_CBRANCH, Some(_)) => {
+            return Err(format!("{} has no output", opcode.name()))
+        }
+        (OpCode::CPUI_BRANCH, None) => {
+            arity(1)?;
+            return Ok(Branch { input: i(0) });
+        }
+        (OpCode::CPUI_CALL, None) => {
+            arity(1)?;
+            return Ok(Call { input: i(0) });
+        }
+        (OpCode::CPUI_CBRANCH, None) => {
+            arity(2)?;
+            return Ok(CBranch {
+                input0: i(0),
+                input1: i(1),
+            });
+        }
+        _ => {}
+    }
+    let output = output.ok_or_else(|| format!("{} needs an output", opcode.name()))?;
+    macro_rules! one_in_one_out {
+        ($op:tt) => {{
+            arity(1)?;
+            $op {
+                output,
+                input: i(0),
+            }
+        }};
+    }
+    macro_rules! two_in_one_out {
+        ($op:tt) => {{
+            arity(2)?;
+            $op {
+                output,
+                input0: i(0),
+                input1: i(1),
+            }
+        }};
+    }
+    let op = match opcode {
+        OpCode::CPUI_COPY => one_in_one_out!(Copy),
+        OpCode::CPUI_INT_EQUAL => two_in_one_out!(IntEqual),
+        OpCode::CPUI_INT_NOTEQUAL => two_in_one_out!(IntNotEqual),
+        OpCode::CPUI_INT_SLESS => two_in_one_out!(IntSignedLess),
+        OpCode::CPUI_INT_SLESSEQUAL => two_in_one_out!(IntSignedLessEqual),
+        OpCode::CPUI_INT_LESS => two_in_one_out!(IntLess),
+        OpCode::CPUI_INT_LESSEQUAL => two_in_one_out!(IntLessEqual),
+        OpCode::CPUI_INT_ZEXT => one_in_one_out!(IntZExt),
+        OpCode::CPUI_INT_SEXT => one_in_one_out!(IntSExt),
+        OpCode::CPUI_INT_ADD => two_in_one_out!(IntAdd),
+        OpCode::CPUI_INT_SUB => two_in_one_out!(IntSub),
+        OpCode::CPUI_INT_CARRY => two_in_one_out!(IntCarry),
+        OpCode::CPUI_INT_SCARRY => two_in_one_out!(IntSignedCarry),
+        OpCode::CPUI_INT_SBORROW => two_in_one_out!(IntSignedBorrow),
+        OpCode::CPUI_INT_2COMP => one_in_one_out!(Int2Comp),
+        OpCode::CPUI_INT_NEGATE => one_in_one_out!(IntNegate),
+        OpCode::CPUI_INT_XOR => two_in_one_out!(IntXor),
+        OpCode::CPUI_INT_AND => two_in_one_out!(IntAnd),
+        OpCode::CPUI_INT_OR => two_in_one_out!(IntOr),
+        OpCode::CPUI_INT_LEFT => two_in_one_out!(IntLeftShift),
+        OpCode::CPUI_INT_RIGHT => two_in_one_out!(IntRightShift),
+        OpCode::CPUI_INT_SRIGHT => two_in_one_out!(IntSignedRightShift),
+        OpCode::CPUI_INT_MULT => two_in_one_out!(IntMult),
+        OpCode::CPUI_INT_DIV => two_in_one_out!(IntDiv),
+        OpCode::CPUI_INT_SDIV => two_in_one_out!(IntSignedDiv),
+        OpCode::CPUI_INT_REM => two_in_one_out!(IntRem),
+        OpCode::CPUI_INT_SREM => two_in_one_out!(IntSignedRem),
+        OpCode::CPUI_BOOL_NEGATE => one_in_one_out!(BoolNegate),
+        OpCode::CPUI_BOOL_XOR => two_in_one_out!(BoolXor),
+        OpCode::CPUI_BOOL_AND => two_in_one_out!(BoolAnd),
+        OpCode::CPUI_BOOL_OR => two_in_one_out!(BoolOr),
+        OpCode::CPUI_FLOAT_EQUAL => two_in_one_out!(FloatEqual),
+        OpCode::CPUI_FLOAT_NOTEQUAL => two_in_one_out!(FloatNotEqual),
+        OpCode::CPUI_FLOAT_LESS => two_in_one_out!(FloatLess),
+        OpCode::CPUI_FLOAT_LESSEQUAL => two_in_one_out!(FloatLessEqual),
+        OpCode::CPUI_FLOAT_NAN => one_in_one_out!(FloatNaN),
+        OpCode::CPUI_FLOAT_ADD => two_in_one_out!(FloatAdd),
+        OpCode::CPUI_FLOAT_DIV => two_in_one_out!(FloatDiv),
+        OpCode::CPUI_FLOAT_MULT => two_in_one_out!(FloatMult),
+        OpCode::CPUI_FLOAT_SUB => two_in_one_out!(FloatSub),
+        OpCode::CPUI_FLOAT_NEG => one_in_one_out!(FloatNeg),
+        OpCode::CPUI_FLOAT_ABS => one_in_one_out!(FloatAbs),
+        OpCode::CPUI_FLOAT_SQRT => one_in_one_out!(FloatSqrt),
+        OpCode::CPUI_FLOAT_INT2FLOAT => one_in_one_out!(FloatIntToFloat),
+        OpCode::CPUI_FLOAT_FLOAT2FLOAT => one_in_one_out!(FloatFloatToFloat),
+        OpCode::CPUI_FLOAT_TRUNC => one_in_one_out!(FloatTrunc),
+        OpCode::CPUI_FLOAT_CEIL => one_in_one_out!(FloatCeil),
+        OpCode::CPUI_FLOAT_FLOOR => one_in_one_out!(FloatFloor),
+        OpCode::CPUI_FLOAT_ROUND => one_in_one_out!(FloatRound),
+        OpCode::CPUI_MULTIEQUAL | OpCode::CPUI_CPOOLREF => {
+            if inputs.len() < 2 {
+                return Err(format!("{} takes at least 2 inputs", opcode.name()));
+            }
+            let (input0, input1, inputs) = (i(0), i(1), inputs[2..].to_vec());
+            match opcode {
+                OpCode::CPUI_MULTIEQUAL => MultiEqual {
+                    input0,
+                    input1,
+                    inputs,
+                    output,
+                },
+                _ => CPoolRef {
+                    input0,
+                    input1,
+                    inputs,
+                    output,
+                },
+            }
+        }
+        OpCode::CPUI_INDIRECT => two_in_one_out!(Indirect),
+        OpCode::CPUI_PIECE => two_in_one_out!(Piece),
+        OpCode::CPUI_SUBPIECE => two_in_one_out!(SubPiece),
+        OpCode::CPUI_CAST => one_in_one_out!(Cast),
+        OpCode::CPUI_PTRADD => {
+            arity(3)?;
+            PtrAdd {
+                output,
+                input0: i(0),
+                input1: i(1),
+                input2: i(2),
+            }
+        }
+        OpCode::CPUI_PTRSUB => two_in_one_out!(PtrSub),
+        OpCode::CPUI_SEGMENTOP => {
+            arity(3)?;
+            SegmentOp {
+                output,
+                input0: i(0),
+                input1: i(1),
+                input2: i(2),
+            }
+        }
+        OpCode::CPUI_NEW => {
+            if inputs.len() != 2 {
+                arity(1)?;
+            }
+            New {
+                output,
+                input: i(0),
+                size: inputs.get(1).cloned(),
+            }
+        }
+        OpCode::CPUI_INSERT => {
+            arity(4)?;
+            Insert {
+                output,
+                input0: i(0),
+                input1: i(1),
+                position: i(2),
+                size: i(3),
+            }
+        }
+        OpCode::CPUI_EXTRACT => {
+            arity(3)?;
+            Extract {
+                output,
+                input0: i(0),
+                position: i(1),
+                size: i(2),
+            }
+        }
+        OpCode::CPUI_POPCOUNT => one_in_one_out!(PopCount),
+        OpCode::CPUI_LZCOUNT => one_in_one_out!(LzCount),
+        _ => return Err(format!("{} needs an indirect operand", opcode.name())),
+    };
+    Ok(op)
+}
+
+fn parse_disassembly(line: &str) -> Disassembly {
+    let (mnemonic, args) = line.split_once(' ').unwrap_or((line, ""));
+    Disassembly {
+        mnemonic: mnemonic.to_string(),
+        args: args.to_string(),
+        operands: vec![],
+    }
+}
+
+fn hex(text: &str) -> Result<u64, String> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    u64::from_str_radix(digits, 16).map_err(|_| format!("`{}` is not a hexadecimal number", text))
+}
+
+fn decimal(text: &str) -> Result<usize, String> {
+    text.parse()
+        .map_err(|_| format!("`{}` is not a decimal number", text))
+}
+
+/// A position within a line of `PCODE` being parsed
+struct Cursor<'s> {
+    text: &'s str,
+    pos: usize,
+}
+
+impl<'s> Cursor<'s> {
+    fn new(text: &'s str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        match self.at_end() {
+            true => Ok(()),
+            false => Err(format!("unexpected `{}`", self.rest())),
+        }
+    }
+
+    fn peek(&mut self, s: &str) -> bool {
+        self.skip_whitespace();
+        self.peek_adjacent(s)
+    }
+
+    /// Like [Cursor::peek], but without skipping whitespace first
+    fn peek_adjacent(&self, s: &str) -> bool {
+        self.rest().starts_with(s)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        self.skip_whitespace();
+        self.eat_adjacent(s)
+    }
+
+    fn eat_adjacent(&mut self, s: &str) -> bool {
+        let found = self.peek_adjacent(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), String> {
+        match self.eat(s) {
+            true => Ok(()),
+            false => Err(format!("expected `{}` at `{}`", s, self.rest())),
+        }
+    }
+
+    /// Consume a keyword, if it is the next whole word
+    fn eat_word(&mut self, word: &str) -> bool {
+        let start = self.pos;
+        if self.word() == word {
+            return true;
+        }
+        self.pos = start;
+        false
+    }
+
+    /// An identifier or number
+    fn word(&mut self) -> &'s str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '$')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Everything up to the next whitespace
+    fn token(&mut self) -> &'s str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn until(&mut self, end: char) -> &'s str {
+        let rest = self.rest();
+        let len = rest.find(end).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// The name of a function call, if one comes next. The opening parenthesis is left for
+    /// the arguments.
+    fn call_name(&mut self) -> Option<&'s str> {
+        let start = self.pos;
+        let name = self.word();
+        if !name.is_empty() && self.peek_adjacent("(") {
+            return Some(name);
+        }
+        self.pos = start;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcode::parse::PcodeParser;
+    use crate::pcode::PcodeOperation;
+    use crate::pcode::PcodeOperation::*;
+    use crate::tests::{vn, TestContext, CONST, RAM, REGISTER, UNIQUE};
+    use crate::{IndirectVarNode, JingleSleighError, VarNode};
+
+    fn rax() -> VarNode {
+        vn(REGISTER, 0, 8)
+    }
+
+    fn tmp() -> VarNode {
+        vn(UNIQUE, 0x100, 8)
+    }
+
+    fn one() -> VarNode {
+        vn(CONST, 1, 8)
+    }
+
+    /// One of every operation, shaped like `SLEIGH` would emit it
+    fn all_operations() -> Vec<PcodeOperation> {
+        let ptr = |size| IndirectVarNode {
+            pointer_space_index: RAM,
+            pointer_location: vn(REGISTER, 0x20, 8),
+            access_size_bytes: size,
+        };
+        let (o, a, b) = (rax(), tmp(), one());
+        vec![
+            Copy {
+                input: a.clone(),
+                output: o.clone(),
+            },
+            Load {
+                input: ptr(4),
+                output: vn(REGISTER, 0, 4),
+            },
+            Store {
+                output: ptr(8),
+                input: o.clone(),
+            },
+            Branch {
+                input: vn(RAM, 0x401000, 8),
+            },
+            CBranch {
+                input0: vn(CONST, 2, 4),
+                input1: vn(REGISTER, 0x206, 1),
+            },
+            BranchInd { input: ptr(8) },
+            Call {
+                input: vn(RAM, 0x401000, 8),
+            },
+            CallInd { input: ptr(8) },
+            CallOther {
+                output: None,
+                inputs: vec![vn(CONST, 0, 4), o.clone()],
+            },
+            CallOther {
+                output: Some(a.clone()),
+                inputs: vec![vn(CONST, 0, 4)],
+            },
+            Return { input: ptr(8) },
+            IntEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntNotEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedLess {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedLessEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntLess {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntLessEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSExt {
+                input: vn(REGISTER, 0, 4),
+                output: o.clone(),
+            },
+            IntZExt {
+                input: vn(REGISTER, 1, 1),
+                output: o.clone(),
+            },
+            IntAdd {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSub {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntCarry {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedCarry {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedBorrow {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            Int2Comp {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            IntNegate {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            IntXor {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntAnd {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntOr {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntLeftShift {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntRightShift {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedRightShift {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntMult {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntDiv {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedDiv {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntRem {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            IntSignedRem {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            BoolNegate {
+                output: vn(UNIQUE, 0, 1),
+                input: vn(REGISTER, 0x206, 1),
+            },
+            BoolXor {
+                output: vn(UNIQUE, 0, 1),
+                input0: vn(REGISTER, 0x206, 1),
+                input1: vn(UNIQUE, 1, 1),
+            },
+            BoolAnd {
+                output: vn(UNIQUE, 0, 1),
+                input0: vn(REGISTER, 0x206, 1),
+                input1: vn(UNIQUE, 1, 1),
+            },
+            BoolOr {
+                output: vn(UNIQUE, 0, 1),
+                input0: vn(REGISTER, 0x206, 1),
+                input1: vn(UNIQUE, 1, 1),
+            },
+            FloatEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatNotEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatLess {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatLessEqual {
+                output: vn(UNIQUE, 0, 1),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatNaN {
+                output: vn(UNIQUE, 0, 1),
+                input: a.clone(),
+            },
+            FloatAdd {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatDiv {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatMult {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatSub {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: o.clone(),
+            },
+            FloatNeg {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            FloatAbs {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            FloatSqrt {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            FloatIntToFloat {
+                output: o.clone(),
+                input: vn(REGISTER, 0, 4),
+            },
+            FloatFloatToFloat {
+                output: o.clone(),
+                input: vn(REGISTER, 0, 4),
+            },
+            FloatTrunc {
+                output: vn(REGISTER, 0, 4),
+                input: a.clone(),
+            },
+            FloatCeil {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            FloatFloor {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            FloatRound {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            MultiEqual {
+                input0: a.clone(),
+                input1: o.clone(),
+                inputs: vec![vn(RAM, 0x1000, 8)],
+                output: o.clone(),
+            },
+            Indirect {
+                output: o.clone(),
+                input0: o.clone(),
+                input1: vn(CONST, 0x10, 8),
+            },
+            Piece {
+                output: o.clone(),
+                input0: vn(REGISTER, 0, 4),
+                input1: vn(UNIQUE, 0, 4),
+            },
+            SubPiece {
+                output: vn(REGISTER, 4, 4),
+                input0: o.clone(),
+                input1: vn(CONST, 4, 4),
+            },
+            Cast {
+                output: o.clone(),
+                input: a.clone(),
+            },
+            PtrAdd {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+                input2: vn(CONST, 4, 8),
+            },
+            PtrSub {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: b.clone(),
+            },
+            SegmentOp {
+                output: o.clone(),
+                input0: vn(CONST, 0, 8),
+                input1: a.clone(),
+                input2: b.clone(),
+            },
+            CPoolRef {
+                input0: a.clone(),
+                input1: b.clone(),
+                inputs: vec![],
+                output: o.clone(),
+            },
+            New {
+                output: o.clone(),
+                input: a.clone(),
+                size: None,
+            },
+            New {
+                output: o.clone(),
+                input: a.clone(),
+                size: Some(b.clone()),
+            },
+            Insert {
+                output: o.clone(),
+                input0: a.clone(),
+                input1: vn(REGISTER, 1, 1),
+                position: vn(CONST, 8, 4),
+                size: vn(CONST, 8, 4),
+            },
+            Extract {
+                output: vn(REGISTER, 1, 1),
+                input0: a.clone(),
+                position: vn(CONST, 8, 4),
+                size: vn(CONST, 8, 4),
+            },
+            PopCount {
+                input: a.clone(),
+                output: vn(REGISTER, 0, 1),
+            },
+            LzCount {
+                output: vn(REGISTER, 0, 1),
+                input: a,
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        let ctx = TestContext::new();
+        let parser = PcodeParser::new(&ctx)
+            .with_registers(&ctx)
+            .with_userops(&ctx);
+        for op in all_operations() {
+            let printed = op.display(&ctx).unwrap().to_string();
+            assert_eq!(parser.parse_operation(&printed).unwrap(), op, "{}", printed);
+            let printed = op.display_with_userops(&ctx).unwrap().to_string();
+            assert_eq!(parser.parse_operation(&printed).unwrap(), op, "{}", printed);
+            let printed = op.display_raw(&ctx).unwrap().to_string();
+            assert_eq!(parser.parse_operation(&printed).unwrap(), op, "{}", printed);
+        }
+    }
+
+    #[test]
+    fn hand_written() {
+        let ctx = TestContext::new();
+        let parser = PcodeParser::new(&ctx).with_registers(&ctx);
+        let instr = parser
+            .parse_instruction(
+                "PUSH RAX\n\
+                 RSP = RSP - 8:8\n\
+                 *[ram]RSP = RAX\n\
+                 \n\
+                 AH = RAX+1:1",
+            )
+            .unwrap();
+        assert_eq!(instr.disassembly.mnemonic, "PUSH");
+        assert_eq!(instr.disassembly.args, "RAX");
+        let rsp = vn(REGISTER, 0x20, 8);
+        assert_eq!(
+            instr.ops,
+            vec![
+                IntSub {
+                    output: rsp.clone(),
+                    input0: rsp.clone(),
+                    input1: vn(CONST, 8, 8),
+                },
+                Store {
+                    output: IndirectVarNode {
+                        pointer_space_index: RAM,
+                        pointer_location: rsp,
+                        access_size_bytes: 8,
+                    },
+                    input: rax(),
+                },
+                Copy {
+                    input: vn(REGISTER, 1, 1),
+                    output: vn(REGISTER, 1, 1),
+                },
+            ]
+        );
+
+        let err = parser
+            .parse_operations("RAX = COPY RAX\nRAX = RAX ?? RAX")
+            .unwrap_err();
+        assert!(matches!(err, JingleSleighError::PcodeParse { line: 2, .. }));
+        // userop names need the userop table
+        assert!(parser.parse_operation("syscall()").is_err());
+        assert!(matches!(
+            parser.parse_operation("RAX = RSP+18446744073709551615:1"),
+            Err(JingleSleighError::PcodeParse { line: 1, .. })
+        ));
+    }
+}
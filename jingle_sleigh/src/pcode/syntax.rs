@@ -0,0 +1,97 @@
+use crate::OpCode;
+
+// The operators and function names of the default p-code syntax. Both PcodeOperationDisplay
+// and PcodeParser read them from here, so the printer and parser can't drift apart.
+
+/// Prefix operators, e.g. `~a`. `f-` precedes `-` so that trying them in order finds the
+/// longer one first.
+pub(crate) const UNARY_OPERATORS: &[(&str, OpCode)] = &[
+    ("~", OpCode::CPUI_INT_NEGATE),
+    ("!", OpCode::CPUI_BOOL_NEGATE),
+    ("f-", OpCode::CPUI_FLOAT_NEG),
+    ("-", OpCode::CPUI_INT_2COMP),
+];
+
+/// Infix operators, e.g. `a + b`
+pub(crate) const BINARY_OPERATORS: &[(&str, OpCode)] = &[
+    ("+", OpCode::CPUI_INT_ADD),
+    ("-", OpCode::CPUI_INT_SUB),
+    ("&", OpCode::CPUI_INT_AND),
+    ("v", OpCode::CPUI_INT_OR),
+    ("^", OpCode::CPUI_INT_XOR),
+    (">>", OpCode::CPUI_INT_RIGHT),
+    ("<<", OpCode::CPUI_INT_LEFT),
+    ("s>>", OpCode::CPUI_INT_SRIGHT),
+    ("<", OpCode::CPUI_INT_LESS),
+    ("<=", OpCode::CPUI_INT_LESSEQUAL),
+    ("s<", OpCode::CPUI_INT_SLESS),
+    ("s<=", OpCode::CPUI_INT_SLESSEQUAL),
+    ("==", OpCode::CPUI_INT_EQUAL),
+    ("!=", OpCode::CPUI_INT_NOTEQUAL),
+    ("*", OpCode::CPUI_INT_MULT),
+    ("/", OpCode::CPUI_INT_DIV),
+    ("s/", OpCode::CPUI_INT_SDIV),
+    ("%", OpCode::CPUI_INT_REM),
+    ("s%", OpCode::CPUI_INT_SREM),
+    ("^^", OpCode::CPUI_BOOL_XOR),
+    ("&&", OpCode::CPUI_BOOL_AND),
+    ("||", OpCode::CPUI_BOOL_OR),
+    ("f==", OpCode::CPUI_FLOAT_EQUAL),
+    ("f!=", OpCode::CPUI_FLOAT_NOTEQUAL),
+    ("f<", OpCode::CPUI_FLOAT_LESS),
+    ("f<=", OpCode::CPUI_FLOAT_LESSEQUAL),
+    ("f+", OpCode::CPUI_FLOAT_ADD),
+    ("f/", OpCode::CPUI_FLOAT_DIV),
+    ("f*", OpCode::CPUI_FLOAT_MULT),
+    ("f-", OpCode::CPUI_FLOAT_SUB),
+];
+
+/// Operations written as function calls, e.g. `zext(a)`
+pub(crate) const FUNCTIONS: &[(&str, OpCode)] = &[
+    ("popcount", OpCode::CPUI_POPCOUNT),
+    ("lzcount", OpCode::CPUI_LZCOUNT),
+    ("zext", OpCode::CPUI_INT_ZEXT),
+    ("sext", OpCode::CPUI_INT_SEXT),
+    ("carry", OpCode::CPUI_INT_CARRY),
+    ("s.carry", OpCode::CPUI_INT_SCARRY),
+    ("s.borrow", OpCode::CPUI_INT_SBORROW),
+    ("nan", OpCode::CPUI_FLOAT_NAN),
+    ("abs", OpCode::CPUI_FLOAT_ABS),
+    ("sqrt", OpCode::CPUI_FLOAT_SQRT),
+    ("int2float", OpCode::CPUI_FLOAT_INT2FLOAT),
+    ("float2float", OpCode::CPUI_FLOAT_FLOAT2FLOAT),
+    ("trunc", OpCode::CPUI_FLOAT_TRUNC),
+    ("ceil", OpCode::CPUI_FLOAT_CEIL),
+    ("floor", OpCode::CPUI_FLOAT_FLOOR),
+    ("round", OpCode::CPUI_FLOAT_ROUND),
+    ("cast", OpCode::CPUI_CAST),
+    ("indirect", OpCode::CPUI_INDIRECT),
+    ("piece", OpCode::CPUI_PIECE),
+    ("ptradd", OpCode::CPUI_PTRADD),
+    ("ptrsub", OpCode::CPUI_PTRSUB),
+    ("segment", OpCode::CPUI_SEGMENTOP),
+    ("multiequal", OpCode::CPUI_MULTIEQUAL),
+    ("cpool", OpCode::CPUI_CPOOLREF),
+    ("newobject", OpCode::CPUI_NEW),
+    ("insert", OpCode::CPUI_INSERT),
+    ("extract", OpCode::CPUI_EXTRACT),
+];
+
+pub(crate) fn unary_operator(opcode: OpCode) -> Option<&'static str> {
+    lookup(UNARY_OPERATORS, opcode)
+}
+
+pub(crate) fn binary_operator(opcode: OpCode) -> Option<&'static str> {
+    lookup(BINARY_OPERATORS, opcode)
+}
+
+pub(crate) fn function_name(opcode: OpCode) -> Option<&'static str> {
+    lookup(FUNCTIONS, opcode)
+}
+
+fn lookup(table: &[(&'static str, OpCode)], opcode: OpCode) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(_, op)| *op == opcode)
+        .map(|(text, _)| *text)
+}
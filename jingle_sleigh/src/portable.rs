@@ -0,0 +1,707 @@
+use crate::context::SleighContext;
+use crate::disassembly::Disassembly;
+use crate::error::JingleSleighError;
+use crate::error::JingleSleighError::{IncompatibleSpace, PortableLanguageMismatch};
+use crate::instruction::Instruction;
+use crate::pcode::PcodeOperation;
+use crate::pcode::PcodeOperation::*;
+use crate::space::{SpaceInfo, SpaceManager};
+use crate::varnode::{IndirectVarNode, VarNode};
+#[cfg(feature = "bincode")]
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "json", feature = "bincode"))]
+use std::io::{Read, Write};
+
+/// The version of the portable format written by this version of `jingle`
+const FORMAT_VERSION: u32 = 1;
+
+/// A stream of lifted [Instruction]s that can be stored and loaded into another [SleighContext].
+///
+/// The space indices stored in [VarNode]s are only meaningful to the context that produced them,
+/// so this records the space table of that context and the id of its language alongside the
+/// instructions. [bind](Self::bind) then looks each space up by name in the context being loaded
+/// into and rewrites the instructions to use its indices.
+///
+/// With the `json` and `bincode` features, this can be encoded as JSON or as a compact binary
+/// format, e.g. to cache lifted code on disk.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PortableInstructions {
+    version: u32,
+    language_id: String,
+    spaces: Vec<SpaceInfo>,
+    instructions: Vec<Instruction>,
+}
+
+impl PortableInstructions {
+    /// Package instructions lifted by the given context
+    pub fn new(ctx: &SleighContext, instructions: Vec<Instruction>) -> Self {
+        Self::from_spaces(ctx.language().id(), ctx, instructions)
+    }
+
+    /// Package instructions whose space indices refer to the given spaces
+    pub fn from_spaces<T: SpaceManager>(
+        language_id: &str,
+        spaces: &T,
+        instructions: Vec<Instruction>,
+    ) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            language_id: language_id.to_string(),
+            spaces: spaces.get_all_space_info().to_vec(),
+            instructions,
+        }
+    }
+
+    /// The id of the language the instructions were lifted with
+    pub fn language_id(&self) -> &str {
+        &self.language_id
+    }
+
+    /// The spaces of the context the instructions were lifted with
+    pub fn spaces(&self) -> &[SpaceInfo] {
+        &self.spaces
+    }
+
+    /// Rewrite the instructions to refer to the spaces of the given context, which must be for
+    /// the language they were lifted with
+    pub fn bind(self, ctx: &SleighContext) -> Result<Vec<Instruction>, JingleSleighError> {
+        if self.language_id != ctx.language().id() {
+            return Err(PortableLanguageMismatch {
+                expected: self.language_id,
+                found: ctx.language().id().to_string(),
+            });
+        }
+        self.bind_to_spaces(ctx)
+    }
+
+    /// Rewrite the instructions to refer to the given spaces, matching spaces by name. Unlike
+    /// [bind](Self::bind), this does not check the language: register offsets and userop
+    /// indices are kept as they are, so they are only meaningful if the languages agree on them.
+    pub fn bind_to_spaces<T: SpaceManager>(
+        self,
+        spaces: &T,
+    ) -> Result<Vec<Instruction>, JingleSleighError> {
+        let map = SpaceMap::new(&self.spaces, spaces);
+        let mut instructions = self.instructions;
+        for instr in instructions.iter_mut() {
+            map.disassembly(&mut instr.disassembly)?;
+            for slot in instr.delay_slots.iter_mut() {
+                map.disassembly(slot)?;
+            }
+            for op in instr.ops.iter_mut() {
+                map.operation(op)?;
+            }
+        }
+        Ok(instructions)
+    }
+
+    /// Encode as JSON
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, JingleSleighError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Decode from JSON written by [to_json](Self::to_json)
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, JingleSleighError> {
+        serde_json::from_str::<Self>(json)?.checked()
+    }
+
+    /// Encode as JSON into the given writer
+    #[cfg(feature = "json")]
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<(), JingleSleighError> {
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    /// Decode from JSON read from the given reader
+    #[cfg(feature = "json")]
+    pub fn read_json<R: Read>(reader: R) -> Result<Self, JingleSleighError> {
+        serde_json::from_reader::<_, Self>(reader)?.checked()
+    }
+
+    /// Encode in `jingle`'s binary format, a variable-length integer encoding that is
+    /// considerably smaller than JSON
+    #[cfg(feature = "bincode")]
+    pub fn to_binary(&self) -> Result<Vec<u8>, JingleSleighError> {
+        Ok(bincode_options().serialize(self)?)
+    }
+
+    /// Decode from bytes written by [to_binary](Self::to_binary). Lengths recorded in the
+    /// input are checked against its size before anything is allocated for them, so corrupt
+    /// input fails rather than exhausting memory.
+    #[cfg(feature = "bincode")]
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, JingleSleighError> {
+        bincode_options()
+            .with_limit(bytes.len() as u64)
+            .deserialize::<Self>(bytes)?
+            .checked()
+    }
+
+    /// Encode in `jingle`'s binary format into the given writer
+    #[cfg(feature = "bincode")]
+    pub fn write_binary<W: Write>(&self, writer: W) -> Result<(), JingleSleighError> {
+        Ok(bincode_options().serialize_into(writer, self)?)
+    }
+
+    /// Decode from `jingle`'s binary format read from the given reader. The reader is read to
+    /// its end first, so that the input can be checked as in [from_binary](Self::from_binary).
+    #[cfg(feature = "bincode")]
+    pub fn read_binary<R: Read>(mut reader: R) -> Result<Self, JingleSleighError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| Box::new(bincode::ErrorKind::Io(e)))?;
+        Self::from_binary(&bytes)
+    }
+
+    #[cfg(any(feature = "json", feature = "bincode"))]
+    fn checked(self) -> Result<Self, JingleSleighError> {
+        match self.version {
+            FORMAT_VERSION => Ok(self),
+            v => Err(JingleSleighError::PortableVersion(v)),
+        }
+    }
+}
+
+#[cfg(feature = "bincode")]
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+/// Maps the space indices of one space table to those of another
+struct SpaceMap<'a> {
+    stored: &'a [SpaceInfo],
+    indices: Vec<Option<usize>>,
+}
+
+impl<'a> SpaceMap<'a> {
+    fn new<T: SpaceManager>(stored: &'a [SpaceInfo], target: &T) -> Self {
+        let indices = stored
+            .iter()
+            .map(|space| {
+                target
+                    .get_all_space_info()
+                    .iter()
+                    .find(|t| {
+                        t.name == space.name
+                            && t.index_size_bytes == space.index_size_bytes
+                            && t.word_size_bytes == space.word_size_bytes
+                            && t._type == space._type
+                            && t.endianness == space.endianness
+                    })
+                    .map(|t| t.index)
+            })
+            .collect();
+        Self { stored, indices }
+    }
+
+    fn index(&self, index: usize) -> Result<usize, JingleSleighError> {
+        match self.indices.get(index) {
+            Some(Some(idx)) => Ok(*idx),
+            Some(None) => Err(IncompatibleSpace(self.stored[index].name.clone())),
+            None => Err(IncompatibleSpace(format!("#{}", index))),
+        }
+    }
+
+    fn varnode(&self, vn: &mut VarNode) -> Result<(), JingleSleighError> {
+        vn.space_index = self.index(vn.space_index)?;
+        Ok(())
+    }
+
+    fn indirect(&self, vn: &mut IndirectVarNode) -> Result<(), JingleSleighError> {
+        vn.pointer_space_index = self.index(vn.pointer_space_index)?;
+        self.varnode(&mut vn.pointer_location)
+    }
+
+    fn disassembly(&self, disassembly: &mut Disassembly) -> Result<(), JingleSleighError> {
+        for operand in disassembly.operands.iter_mut() {
+            for vn in operand.varnodes.iter_mut() {
+                self.varnode(vn)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn operation(&self, op: &mut PcodeOperation) -> Result<(), JingleSleighError> {
+        match op {
+            Branch { input } | Call { input } => self.varnode(input),
+            BranchInd { input } | CallInd { input } | Return { input } => self.indirect(input),
+            Load { input, output } => {
+                self.indirect(input)?;
+                self.varnode(output)
+            }
+            Store { output, input } => {
+                self.indirect(output)?;
+                self.varnode(input)
+            }
+            CBranch { input0, input1 } => {
+                self.varnode(input0)?;
+                self.varnode(input1)
+            }
+            CallOther { output, inputs } => {
+                output.iter_mut().try_for_each(|vn| self.varnode(vn))?;
+                inputs.iter_mut().try_for_each(|vn| self.varnode(vn))
+            }
+            Copy { input, output }
+            | IntSExt { input, output }
+            | IntZExt { input, output }
+            | Int2Comp { input, output }
+            | IntNegate { input, output }
+            | BoolNegate { input, output }
+            | FloatNaN { input, output }
+            | FloatNeg { input, output }
+            | FloatAbs { input, output }
+            | FloatSqrt { input, output }
+            | FloatIntToFloat { input, output }
+            | FloatFloatToFloat { input, output }
+            | FloatTrunc { input, output }
+            | FloatCeil { input, output }
+            | FloatFloor { input, output }
+            | FloatRound { input, output }
+            | Cast { input, output }
+            | PopCount { input, output }
+            | LzCount { input, output } => {
+                self.varnode(input)?;
+                self.varnode(output)
+            }
+            IntEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedLess {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntLess {
+                output,
+                input0,
+                input1,
+            }
+            | IntLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | IntAdd {
+                output,
+                input0,
+                input1,
+            }
+            | IntSub {
+                output,
+                input0,
+                input1,
+            }
+            | IntCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedCarry {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedBorrow {
+                output,
+                input0,
+                input1,
+            }
+            | IntXor {
+                output,
+                input0,
+                input1,
+            }
+            | IntAnd {
+                output,
+                input0,
+                input1,
+            }
+            | IntOr {
+                output,
+                input0,
+                input1,
+            }
+            | IntLeftShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntRightShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedRightShift {
+                output,
+                input0,
+                input1,
+            }
+            | IntMult {
+                output,
+                input0,
+                input1,
+            }
+            | IntDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedDiv {
+                output,
+                input0,
+                input1,
+            }
+            | IntRem {
+                output,
+                input0,
+                input1,
+            }
+            | IntSignedRem {
+                output,
+                input0,
+                input1,
+            }
+            | BoolXor {
+                output,
+                input0,
+                input1,
+            }
+            | BoolAnd {
+                output,
+                input0,
+                input1,
+            }
+            | BoolOr {
+                output,
+                input0,
+                input1,
+            }
+            | FloatEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatNotEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLess {
+                output,
+                input0,
+                input1,
+            }
+            | FloatLessEqual {
+                output,
+                input0,
+                input1,
+            }
+            | FloatAdd {
+                output,
+                input0,
+                input1,
+            }
+            | FloatDiv {
+                output,
+                input0,
+                input1,
+            }
+            | FloatMult {
+                output,
+                input0,
+                input1,
+            }
+            | FloatSub {
+                output,
+                input0,
+                input1,
+            }
+            | Indirect {
+                output,
+                input0,
+                input1,
+            }
+            | Piece {
+                output,
+                input0,
+                input1,
+            }
+            | SubPiece {
+                output,
+                input0,
+                input1,
+            }
+            | PtrSub {
+                output,
+                input0,
+                input1,
+            } => {
+                self.varnode(input0)?;
+                self.varnode(input1)?;
+                self.varnode(output)
+            }
+            PtrAdd {
+                output,
+                input0,
+                input1,
+                input2,
+            }
+            | SegmentOp {
+                output,
+                input0,
+                input1,
+                input2,
+            } => {
+                self.varnode(input0)?;
+                self.varnode(input1)?;
+                self.varnode(input2)?;
+                self.varnode(output)
+            }
+            MultiEqual {
+                input0,
+                input1,
+                inputs,
+                output,
+            }
+            | CPoolRef {
+                input0,
+                input1,
+                inputs,
+                output,
+            } => {
+                self.varnode(input0)?;
+                self.varnode(input1)?;
+                inputs.iter_mut().try_for_each(|vn| self.varnode(vn))?;
+                self.varnode(output)
+            }
+            New {
+                output,
+                input,
+                size,
+            } => {
+                self.varnode(input)?;
+                size.iter_mut().try_for_each(|vn| self.varnode(vn))?;
+                self.varnode(output)
+            }
+            Insert {
+                output,
+                input0,
+                input1,
+                position,
+                size,
+            } => {
+                self.varnode(input0)?;
+                self.varnode(input1)?;
+                self.varnode(position)?;
+                self.varnode(size)?;
+                self.varnode(output)
+            }
+            Extract {
+                output,
+                input0,
+                position,
+                size,
+            } => {
+                self.varnode(input0)?;
+                self.varnode(position)?;
+                self.varnode(size)?;
+                self.varnode(output)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcode::PcodeOperation;
+    use crate::portable::PortableInstructions;
+    use crate::space::{SpaceInfo, SpaceManager};
+    use crate::tests::{TestContext, SLEIGH_ARCH};
+    use crate::JingleSleighError::IncompatibleSpace;
+    #[cfg(feature = "bincode")]
+    use crate::JingleSleighError::PortableBinary;
+    #[cfg(feature = "json")]
+    use crate::JingleSleighError::PortableVersion;
+    use crate::{Disassembly, IndirectVarNode, Instruction, Operand, OperandKind, VarNode};
+
+    /// The spaces of a [TestContext], in a different order
+    struct Shuffled(Vec<SpaceInfo>);
+
+    impl Shuffled {
+        fn new(ctx: &TestContext) -> Self {
+            let mut spaces = ctx.get_all_space_info().to_vec();
+            spaces.reverse();
+            for (idx, space) in spaces.iter_mut().enumerate() {
+                space.index = idx;
+            }
+            Self(spaces)
+        }
+
+        fn index(&self, name: &str) -> usize {
+            self.0.iter().position(|s| s.name == name).unwrap()
+        }
+    }
+
+    impl SpaceManager for Shuffled {
+        fn get_space_info(&self, idx: usize) -> Option<&SpaceInfo> {
+            self.0.get(idx)
+        }
+
+        fn get_all_space_info(&self) -> &[SpaceInfo] {
+            &self.0
+        }
+
+        fn get_code_space_idx(&self) -> usize {
+            self.index("ram")
+        }
+    }
+
+    /// `PUSH RAX`, in the spaces of whichever context `vn` builds varnodes for
+    fn push(vn: impl Fn(&str, u64, usize) -> VarNode) -> Instruction {
+        let rsp = vn("register", 0x20, 8);
+        Instruction {
+            disassembly: Disassembly {
+                mnemonic: "PUSH".to_string(),
+                args: "RAX".to_string(),
+                operands: vec![Operand {
+                    kind: OperandKind::Register,
+                    text: "RAX".to_string(),
+                    value: None,
+                    varnodes: vec![vn("register", 0, 8)],
+                }],
+            },
+            delay_slots: vec![],
+            ops: vec![
+                PcodeOperation::IntSub {
+                    output: rsp.clone(),
+                    input0: rsp.clone(),
+                    input1: vn("const", 8, 8),
+                },
+                PcodeOperation::Store {
+                    output: IndirectVarNode {
+                        pointer_space_index: vn("ram", 0, 8).space_index,
+                        pointer_location: rsp,
+                        access_size_bytes: 8,
+                    },
+                    input: vn("unique", 0x100, 8),
+                },
+            ],
+            length: 1,
+            address: 0x1000,
+        }
+    }
+
+    /// `PUSH RAX` in the spaces of a [TestContext], along with the same instruction as it
+    /// should come out after being bound to the [Shuffled] spaces of that context
+    fn rebind_fixture(ctx: &TestContext) -> (PortableInstructions, Shuffled, Instruction) {
+        let shuffled = Shuffled::new(ctx);
+        let portable = PortableInstructions::from_spaces(
+            SLEIGH_ARCH,
+            ctx,
+            vec![push(|space, offset, size| {
+                ctx.varnode(space, offset, size).unwrap()
+            })],
+        );
+        let expected = push(|space, offset, size| VarNode {
+            space_index: shuffled.index(space),
+            offset,
+            size,
+        });
+        (portable, shuffled, expected)
+    }
+
+    #[test]
+    fn rebind() {
+        let ctx = TestContext::new();
+        let (portable, shuffled, expected) = rebind_fixture(&ctx);
+        assert_eq!(portable.language_id(), SLEIGH_ARCH);
+        let bound = portable.bind_to_spaces(&shuffled).unwrap();
+        assert_eq!(bound[0].ops, expected.ops);
+        assert_eq!(bound[0].disassembly.operands, expected.disassembly.operands);
+        assert_eq!(bound[0].address, 0x1000);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn rebind_json() {
+        let ctx = TestContext::new();
+        let (portable, shuffled, expected) = rebind_fixture(&ctx);
+        let json = portable.to_json().unwrap();
+        let loaded = PortableInstructions::from_json(&json).unwrap();
+        assert_eq!(loaded.language_id(), SLEIGH_ARCH);
+        let bound = loaded.bind_to_spaces(&shuffled).unwrap();
+        assert_eq!(bound[0].ops, expected.ops);
+        assert_eq!(bound[0].disassembly.operands, expected.disassembly.operands);
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn rebind_binary() {
+        let ctx = TestContext::new();
+        let (portable, shuffled, expected) = rebind_fixture(&ctx);
+        let bytes = portable.to_binary().unwrap();
+        #[cfg(feature = "json")]
+        assert!(bytes.len() < portable.to_json().unwrap().len() / 4);
+        let bound = PortableInstructions::from_binary(&bytes)
+            .unwrap()
+            .bind_to_spaces(&shuffled)
+            .unwrap();
+        assert_eq!(bound[0].ops, expected.ops);
+        assert_eq!(bound[0].address, 0x1000);
+        let read = PortableInstructions::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(read.language_id(), SLEIGH_ARCH);
+    }
+
+    #[test]
+    fn incompatible() {
+        let ctx = TestContext::new();
+        let (portable, mut shuffled, _) = rebind_fixture(&ctx);
+        let idx = shuffled.index("register");
+        shuffled.0[idx].index_size_bytes = 2;
+        match portable.bind_to_spaces(&shuffled) {
+            Err(IncompatibleSpace(name)) => assert_eq!(name, "register"),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn incompatible_version() {
+        let ctx = TestContext::new();
+        let portable = PortableInstructions::from_spaces(SLEIGH_ARCH, &ctx, vec![]);
+        let json = portable
+            .to_json()
+            .unwrap()
+            .replace("\"version\":1", "\"version\":1000");
+        assert!(matches!(
+            PortableInstructions::from_json(&json),
+            Err(PortableVersion(1000))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn corrupt_binary() {
+        // version 1, then a language id claiming to be 2^64 - 1 bytes long
+        let mut corrupt = vec![1, 0xfd];
+        corrupt.extend(u64::MAX.to_le_bytes());
+        assert!(matches!(
+            PortableInstructions::from_binary(&corrupt),
+            Err(PortableBinary(_))
+        ));
+    }
+}
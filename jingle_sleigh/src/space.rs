@@ -1,3 +1,4 @@
+use crate::context::ImageSymbol;
 use crate::ffi::addrspace::bridge::SpaceType;
 use crate::ffi::context_ffi::bridge::AddrSpaceHandle;
 use crate::space::SleighEndianness::{Big, Little};
@@ -8,7 +9,7 @@ use serde::{Deserialize, Serialize};
 
 /// What program-analysis library wouldn't be complete without an enum
 /// for endianness?
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum SleighEndianness {
     Big,
     Little,
@@ -99,6 +100,13 @@ pub trait SpaceManager {
     /// Returns the index that `SLEIGH` claims is the "main" space in which instructions reside
     fn get_code_space_idx(&self) -> usize;
 
+    /// This type as a [`UserOpManager`], if it holds the userop table. Lets code that is only
+    /// given spaces, such as [`PcodeOperation::display`](crate::PcodeOperation::display), name
+    /// userops whenever it can.
+    fn as_userop_manager(&self) -> Option<&dyn UserOpManager> {
+        None
+    }
+
     /// A helper function to generate a [`VarNode`] using the name of a space
     fn varnode(&self, name: &str, offset: u64, size: usize) -> Result<VarNode, JingleSleighError> {
         for (space_index, space) in self.get_all_space_info().iter().enumerate() {
@@ -120,12 +128,59 @@ pub trait RegisterManager: SpaceManager {
     /// Given a register name, get a corresponding [`VarNode`], if one exists
     fn get_register(&self, name: &str) -> Option<VarNode>;
 
-    /// Given a [`VarNode`], get the name of the corresponding architectural register, if one exists
-
+    /// Given a [`VarNode`], get the name of the architectural register occupying exactly that
+    /// location, if one exists. See [`RegisterManager::get_covering_register`] for locations
+    /// that are only part of a register.
     fn get_register_name(&self, location: VarNode) -> Option<&str>;
 
     /// Get a listing of all register name/[`VarNode`] pairs
     fn get_registers(&self) -> Vec<(VarNode, String)>;
+
+    /// Get the index of the space the registers reside in, if there are any registers
+    fn get_register_space_idx(&self) -> Option<usize> {
+        self.get_registers().first().map(|(vn, _)| vn.space_index)
+    }
+
+    /// Given a register name, get the name of the smallest register that contains it, e.g.
+    /// `EAX` for `AX` on x86
+    fn get_parent_register(&self, name: &str) -> Option<&str>;
+
+    /// Given a register name, get the names of the registers it is the
+    /// [parent](RegisterManager::get_parent_register) of, e.g. `AL` and `AH` for `AX` on x86
+    fn get_child_registers(&self, name: &str) -> Vec<&str>;
+
+    /// Given a [`VarNode`], get the name of the smallest register containing all of it, if one
+    /// exists. Unlike [`RegisterManager::get_register_name`], this can name parts of registers
+    /// that the architecture does not name, such as the upper half of `EAX`.
+    fn get_covering_register(&self, location: &VarNode) -> Option<&str>;
+
+    /// Given a [`VarNode`], get the names of all registers sharing at least one byte with it
+    fn get_overlapping_registers(&self, location: &VarNode) -> Vec<&str>;
+}
+
+/// This trait indicates that the implementing type holds the table of `SLEIGH` user-defined
+/// operations ("userops"). `CALLOTHER` operations refer to these by an index that is only
+/// meaningful for a particular compiled `.sla` file, so the name is the stable identifier.
+pub trait UserOpManager: SpaceManager {
+    /// Given a userop index, get the name `SLEIGH` declared it with, if it exists
+    fn get_userop_name(&self, index: u64) -> Option<&str>;
+
+    /// Given a userop name, get its index, if it exists
+    fn get_userop_index(&self, name: &str) -> Option<u64>;
+
+    /// Get a listing of all userop index/name pairs
+    fn get_userops(&self) -> Vec<(u64, String)>;
+}
+
+/// This trait indicates that the implementing type holds the symbols of the binary being
+/// analyzed, allowing addresses to be rendered relative to named locations.
+pub trait SymbolManager: SpaceManager {
+    /// Given a symbol name, get the address it refers to, if it exists
+    fn get_symbol_address(&self, name: &str) -> Option<u64>;
+
+    /// Given an address, get the closest symbol at or before it, along with the offset of the
+    /// address from that symbol
+    fn get_nearest_symbol(&self, address: u64) -> Option<(&ImageSymbol, u64)>;
 }
 
 /// `jingle` models traces of code using slices, so it is helpful to implement some of these
@@ -142,4 +197,8 @@ impl<T: SpaceManager> SpaceManager for &[T] {
     fn get_code_space_idx(&self) -> usize {
         self[0].get_code_space_idx()
     }
+
+    fn as_userop_manager(&self) -> Option<&dyn UserOpManager> {
+        self[0].as_userop_manager()
+    }
 }
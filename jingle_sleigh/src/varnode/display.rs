@@ -16,6 +16,26 @@ pub struct IndirectVarNodeDisplay {
     pub access_size_bytes: usize,
 }
 
+/// A [`VarNode`](crate::VarNode) in Ghidra's raw p-code syntax. Locations within a register
+/// are shown by name, with the offset into the register and the size when those differ from
+/// the register itself, e.g. `EAX`, `AH` or `RAX+4:4`. Everything else is shown as
+/// `(space,offset,size)`, e.g. `(unique,0x100,8)` or `(const,0x1,4)`.
+#[derive(Clone, Debug)]
+pub enum RawVarNodeDisplay {
+    Register {
+        name: String,
+        /// The offset of the location from the start of the register
+        offset: u64,
+        size: usize,
+        register_size: usize,
+    },
+    Space {
+        space_name: String,
+        offset: u64,
+        size: usize,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum GeneralizedVarNodeDisplay {
     Direct(VarNodeDisplay),
@@ -36,6 +56,33 @@ impl Display for VarNodeDisplay {
     }
 }
 
+impl Display for RawVarNodeDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawVarNodeDisplay::Register {
+                name,
+                offset,
+                size,
+                register_size,
+            } => {
+                write!(f, "{}", name)?;
+                if *offset != 0 {
+                    write!(f, "+{}", offset)?;
+                }
+                if size != register_size {
+                    write!(f, ":{}", size)?;
+                }
+                Ok(())
+            }
+            RawVarNodeDisplay::Space {
+                space_name,
+                offset,
+                size,
+            } => write!(f, "({},{:#x},{})", space_name, offset, size),
+        }
+    }
+}
+
 impl Display for IndirectVarNodeDisplay {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
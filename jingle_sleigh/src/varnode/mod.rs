@@ -3,9 +3,9 @@ pub mod display;
 use crate::error::JingleSleighError;
 
 use crate::ffi::instruction::bridge::VarnodeInfoFFI;
-use crate::space::SpaceManager;
+use crate::space::{RegisterManager, SpaceManager};
 pub use crate::varnode::display::{
-    GeneralizedVarNodeDisplay, IndirectVarNodeDisplay, VarNodeDisplay,
+    GeneralizedVarNodeDisplay, IndirectVarNodeDisplay, RawVarNodeDisplay, VarNodeDisplay,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -18,8 +18,7 @@ use std::fmt::Debug;
 ///
 /// In `jingle`, we follow `SLEIGH`'s convention and display these as
 /// `<space>\[<offset>\]:<size>`. In the case of constants, we simplify this to `<offset>:<size>`.
-/// For registers, we will (soon! (TM)) perform a register lookup and instead show the pretty
-/// architecture-defined register name.
+/// [`VarNode::display_raw`] instead shows registers by their architecture-defined names.
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct VarNode {
     /// The index at which the relevant space can be found in a [`SpaceManager`]
@@ -43,12 +42,39 @@ impl VarNode {
             .ok_or(JingleSleighError::InvalidSpaceName)
     }
 
+    /// Display this location in Ghidra's raw p-code syntax, naming the register it falls
+    /// within, if any
+    pub fn display_raw<T: RegisterManager>(
+        &self,
+        ctx: &T,
+    ) -> Result<RawVarNodeDisplay, JingleSleighError> {
+        let register = (ctx.get_register_space_idx() == Some(self.space_index))
+            .then(|| ctx.get_covering_register(self))
+            .flatten()
+            .and_then(|name| Some((name, ctx.get_register(name)?)));
+        if let Some((name, register)) = register {
+            return Ok(RawVarNodeDisplay::Register {
+                name: name.to_string(),
+                offset: self.offset - register.offset,
+                size: self.size,
+                register_size: register.size,
+            });
+        }
+        ctx.get_space_info(self.space_index)
+            .map(|space_info| RawVarNodeDisplay::Space {
+                space_name: space_info.name.clone(),
+                offset: self.offset,
+                size: self.size,
+            })
+            .ok_or(JingleSleighError::InvalidSpaceName)
+    }
+
     pub fn covers(&self, other: &VarNode) -> bool {
         if self.space_index != other.space_index {
             return false;
         }
-        let self_range = self.offset..(self.offset + self.size as u64);
-        let other = other.offset..(other.offset + other.size as u64);
+        let self_range = self.offset..self.offset.saturating_add(self.size as u64);
+        let other = other.offset..other.offset.saturating_add(other.size as u64);
         self_range.start <= other.start && self_range.end >= other.end
     }
 }